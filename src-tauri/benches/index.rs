@@ -0,0 +1,146 @@
+//! Compares the memory use and lookup speed of the package index against the
+//! representation it replaced, using a generated index the size of Lethal Company's.
+//!
+//! Run with `cargo bench --features bench --bench index`.
+//!
+//! Each representation's memory is measured in a fresh process, so the strings both
+//! of them intern are counted towards each, as they would be in the app.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    env,
+    hint::black_box,
+    process::Command,
+    sync::atomic::{AtomicIsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use gale::bench::{self, CompactIndex, LegacyIndex, PACKAGE_COUNT};
+
+const LOOKUP_COUNT: usize = 10_000;
+
+/// Makes the process only build the given index and print how much memory it uses.
+const MEASURE_ARG: &str = "--measure=";
+
+/// Counts the bytes allocated by the process.
+struct CountingAlloc;
+
+static ALLOCATED: AtomicIsize = AtomicIsize::new(0);
+
+fn track(delta: isize) {
+    ALLOCATED.fetch_add(delta, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        track(layout.size() as isize);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        track(-(layout.size() as isize));
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        track(new_size as isize - layout.size() as isize);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Returns the result of `f` and how many bytes it left allocated.
+fn measure<T>(f: impl FnOnce() -> T) -> (T, isize) {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let value = f();
+    (value, ALLOCATED.load(Ordering::Relaxed) - before)
+}
+
+/// Builds an index in a new process and returns how many bytes it takes up.
+fn measure_in_process(index: &str) -> isize {
+    let output = Command::new(env::current_exe().unwrap())
+        .arg(format!("{MEASURE_ARG}{index}"))
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "measuring the {index} index failed"
+    );
+
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .trim()
+        .parse()
+        .unwrap()
+}
+
+fn time(f: impl FnMut(usize)) -> Duration {
+    let start = Instant::now();
+    (0..LOOKUP_COUNT).for_each(f);
+    start.elapsed() / LOOKUP_COUNT as u32
+}
+
+fn main() {
+    let json = bench::generate_index();
+
+    if let Some(index) =
+        env::args().find_map(|arg| arg.strip_prefix(MEASURE_ARG).map(str::to_owned))
+    {
+        let bytes = match index.as_str() {
+            "legacy" => measure(|| LegacyIndex::build(&json)).1,
+            "compact" => measure(|| CompactIndex::build(&json)).1,
+            _ => panic!("unknown index {index}"),
+        };
+
+        println!("{bytes}");
+        return;
+    }
+
+    let legacy_bytes = measure_in_process("legacy");
+    let compact_bytes = measure_in_process("compact");
+
+    let legacy = LegacyIndex::build(&json);
+    let compact = CompactIndex::build(&json);
+
+    let names = legacy.names();
+    let ids = legacy.ids();
+
+    let pick = |i: usize| (i * 7_919) % PACKAGE_COUNT;
+
+    let legacy_find = time(|i| {
+        black_box(legacy.find_package(&names[pick(i)]));
+    });
+    let compact_find = time(|i| {
+        black_box(compact.find_package(&names[pick(i)]));
+    });
+    let legacy_get = time(|i| {
+        let (package, version) = ids[pick(i)];
+        black_box(legacy.get_mod(package, version));
+    });
+    let compact_get = time(|i| {
+        let (package, version) = ids[pick(i)];
+        black_box(compact.get_mod(package, version));
+    });
+
+    let mb = |bytes: isize| bytes as f64 / 1_000_000.0;
+
+    println!("{PACKAGE_COUNT} packages");
+    println!(
+        "memory:          legacy {:.1} MB, compact {:.1} MB ({:.0}%)",
+        mb(legacy_bytes),
+        mb(compact_bytes),
+        100.0 * compact_bytes as f64 / legacy_bytes as f64
+    );
+    println!("find by name:    legacy {legacy_find:?}, compact {compact_find:?}");
+    println!("get by uuid:     legacy {legacy_get:?}, compact {compact_get:?}");
+
+    assert!(compact_bytes < legacy_bytes);
+    assert_eq!(compact.package_count(), legacy.package_count());
+
+    for name in names.iter().take(1_000) {
+        assert_eq!(compact.dependant_count(name), legacy.dependant_count(name));
+    }
+}
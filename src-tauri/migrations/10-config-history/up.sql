@@ -0,0 +1,12 @@
+CREATE TABLE config_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    profile_id INTEGER NOT NULL,
+    file TEXT NOT NULL,
+    section TEXT NOT NULL,
+    entry TEXT NOT NULL,
+    old_value JSON,
+    new_value JSON NOT NULL,
+    changed_at INTEGER NOT NULL
+);
+
+CREATE INDEX config_history_file ON config_history (profile_id, file);
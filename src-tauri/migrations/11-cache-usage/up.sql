@@ -0,0 +1,7 @@
+CREATE TABLE cache_usage (
+    full_name TEXT NOT NULL,
+    version TEXT NOT NULL,
+    game_slug TEXT,
+    last_used INTEGER NOT NULL,
+    PRIMARY KEY (full_name, version)
+);
@@ -0,0 +1,2 @@
+DROP TABLE cache_object_refs;
+DROP TABLE cache_objects;
@@ -0,0 +1,14 @@
+CREATE TABLE cache_objects (
+    hash TEXT NOT NULL PRIMARY KEY,
+    size INTEGER NOT NULL
+);
+
+CREATE TABLE cache_object_refs (
+    full_name TEXT NOT NULL,
+    version TEXT NOT NULL,
+    path TEXT NOT NULL,
+    hash TEXT NOT NULL,
+    PRIMARY KEY (full_name, version, path)
+);
+
+CREATE INDEX cache_object_refs_hash ON cache_object_refs (hash);
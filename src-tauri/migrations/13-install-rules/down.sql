@@ -0,0 +1,2 @@
+ALTER TABLE managed_games DROP COLUMN install_rules;
+ALTER TABLE profiles DROP COLUMN install_rules;
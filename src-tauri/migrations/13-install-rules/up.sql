@@ -0,0 +1,2 @@
+ALTER TABLE managed_games ADD COLUMN install_rules JSON NOT NULL DEFAULT '[]';
+ALTER TABLE profiles ADD COLUMN install_rules JSON NOT NULL DEFAULT '[]';
@@ -0,0 +1,2 @@
+DROP TABLE watchlist_changes;
+DROP TABLE watched_packages;
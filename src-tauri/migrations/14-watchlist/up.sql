@@ -0,0 +1,21 @@
+CREATE TABLE watched_packages (
+    game_slug TEXT NOT NULL,
+    full_name TEXT NOT NULL,
+    last_version TEXT,
+    is_deprecated INTEGER NOT NULL DEFAULT 0,
+    added_at INTEGER NOT NULL,
+    PRIMARY KEY (game_slug, full_name)
+);
+
+CREATE TABLE watchlist_changes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    game_slug TEXT NOT NULL,
+    full_name TEXT NOT NULL,
+    kind JSON NOT NULL,
+    version TEXT,
+    changelog TEXT,
+    detected_at INTEGER NOT NULL,
+    seen INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX watchlist_changes_game ON watchlist_changes (game_slug, seen);
@@ -2,13 +2,17 @@ use std::{path::PathBuf, process};
 
 use clap::Parser;
 use eyre::{Context, OptionExt, Result, eyre};
+use itertools::Itertools;
 use tauri::AppHandle;
 use tracing::{debug, error, info};
 
 use crate::{
     game::{self},
     logger,
-    profile::{self, ModManager, install::InstallOptions},
+    profile::{
+        self, ModManager,
+        install::{InstallOptions, plan::InstallPlan},
+    },
     state::ManagerExt,
 };
 
@@ -51,6 +55,12 @@ struct Cli {
     #[arg(long)]
     vanilla: bool,
 
+    /// Log what `--install` would do instead of installing anything.
+    ///
+    /// Only local mods can be planned from the command line, so this requires `--install`.
+    #[arg(long, requires = "install")]
+    dry_run: bool,
+
     #[arg(long)]
     no_gui: bool,
 }
@@ -66,6 +76,7 @@ impl Cli {
             launch,
             no_gui,
             vanilla,
+            dry_run,
         } = self;
 
         if let Some(slug) = &game {
@@ -95,6 +106,19 @@ impl Cli {
             manager.save_all(app)?;
         }
 
+        if let Some(path) = install.as_ref().filter(|_| dry_run) {
+            drop(manager);
+
+            let plan = profile::import::plan_local_mod(path, app)?;
+            log_plan(&plan);
+
+            if no_gui {
+                process::exit(0);
+            }
+
+            return Ok(());
+        }
+
         if let Some(path) = install {
             drop(manager);
 
@@ -141,6 +165,35 @@ impl Cli {
     }
 }
 
+/// Writes the plan to the log, since release builds on Windows have no console to print to.
+fn log_plan(plan: &InstallPlan) {
+    if plan.installs.is_empty() {
+        info!("dry run: no dependencies to install");
+    }
+
+    for install in &plan.installs {
+        let source = if install.cached { "cached" } else { "download" };
+
+        info!(
+            "dry run: install {}-{} ({})",
+            install.package.full_name, install.package.version, source
+        );
+    }
+
+    for conflict in &plan.conflicts {
+        info!(
+            "dry run: conflict {} ({})",
+            conflict.path,
+            conflict.mods.iter().join(", ")
+        );
+    }
+
+    info!(
+        "dry run: {} to download",
+        humansize::format_size(plan.download_size, humansize::BINARY)
+    );
+}
+
 async fn install_local_mod(path: PathBuf, app: &AppHandle) -> Result<()> {
     profile::import::import_local_mod(path, None, app, InstallOptions::default()).await
 }
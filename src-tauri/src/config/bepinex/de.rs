@@ -107,6 +107,12 @@ impl EntryBuilder {
             .transpose()?;
 
         let value = self.value.ok_or(anyhow!("missing entry value"))?;
+
+        let unknown_options = match &self.acceptable_values {
+            Some(options) => Self::unknown_options(&value, options, self.is_flags),
+            None => Vec::new(),
+        };
+
         let value = Self::parse_value(
             value,
             self.acceptable_values,
@@ -121,9 +127,25 @@ impl EntryBuilder {
             default_value,
             value,
             description: self.description,
+            unknown_options,
         })
     }
 
+    fn unknown_options(string: &str, options: &[String], is_flags: bool) -> Vec<String> {
+        let values = match is_flags {
+            // an empty set of flags is written as 0
+            true if string == "0" => Vec::new(),
+            true => string.split(", ").collect(),
+            false => vec![string],
+        };
+
+        values
+            .into_iter()
+            .filter(|value| !options.iter().any(|opt| opt == value))
+            .map(str::to_owned)
+            .collect()
+    }
+
     fn parse_value(
         string: String,
         options: Option<Vec<String>>,
@@ -2,7 +2,10 @@ use std::io::{BufRead, Write};
 
 use eyre::{OptionExt, Result, eyre};
 
-use super::frontend::{self, Num};
+use super::{
+    frontend::{self, Num},
+    validate::ValidationError,
+};
 
 pub mod de;
 pub mod ser;
@@ -55,6 +58,37 @@ impl File {
             .and_then(|section| section.find_entry(entry))
     }
 
+    pub fn entry_snapshot(&self, section: &str, entry: &str) -> Option<String> {
+        let entry = self
+            .sections
+            .iter()
+            .find(|s| s.name == section)?
+            .entries
+            .iter()
+            .find(|e| e.name() == entry)?;
+
+        match entry {
+            EntryKind::Normal(entry) => ser::value_to_string(&entry.value).ok(),
+            EntryKind::Orphaned { value, .. } => Some(value.clone()),
+        }
+    }
+
+    /// Returns every entry whose current value doesn't satisfy its declared constraints.
+    pub fn validate(&self) -> Vec<(&str, &str, ValidationError)> {
+        self.sections
+            .iter()
+            .flat_map(|section| {
+                section.entries.iter().filter_map(|entry| match entry {
+                    EntryKind::Normal(entry) => entry
+                        .check()
+                        .err()
+                        .map(|err| (section.name.as_str(), entry.name.as_str(), err)),
+                    EntryKind::Orphaned { .. } => None,
+                })
+            })
+            .collect()
+    }
+
     pub fn reset_all(&mut self) -> Result<()> {
         for section in &mut self.sections {
             for entry in &mut section.entries {
@@ -130,8 +164,7 @@ impl EntryKind {
     }
 
     pub fn set(&mut self, value: frontend::Value) -> Result<()> {
-        self.as_normal_mut()?.value = value.into();
-        Ok(())
+        self.as_normal_mut()?.set(value.into())
     }
 
     pub fn reset(&mut self) -> Result<frontend::Value> {
@@ -152,6 +185,9 @@ pub struct Entry {
     type_name: String,
     default_value: Option<Value>,
     value: Value,
+    /// Options in the value that aren't among the acceptable values.
+    /// These are dropped when parsing, so we keep track of them for validation.
+    unknown_options: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -173,11 +209,131 @@ pub enum Value {
 }
 
 impl Entry {
+    fn set(&mut self, value: Value) -> Result<()> {
+        self.value = self.validate(value)?;
+        self.unknown_options.clear();
+        Ok(())
+    }
+
     fn reset(&mut self) -> Result<frontend::Value> {
         self.value = self.default_value.clone().ok_or_eyre("no default value")?;
+        self.unknown_options.clear();
         Ok(self.value.clone().into())
     }
 
+    /// Checks a new value against the constraints of this entry.
+    ///
+    /// Returns the value with the entry's declared range and options,
+    /// since those should not be changed by the caller.
+    fn validate(&self, value: Value) -> Result<Value, ValidationError> {
+        let mismatch = || ValidationError::TypeMismatch {
+            expected: self.type_name.clone(),
+        };
+
+        let value = match (&self.value, value) {
+            (Value::Boolean(_), Value::Boolean(value)) => Value::Boolean(value),
+            (Value::String(_), Value::String(value) | Value::Other(value)) => Value::String(value),
+            (Value::Other(_), Value::String(value) | Value::Other(value)) => Value::Other(value),
+            (Value::Int32(current), Value::Int32(new)) => {
+                ValidationError::check_range(new.value, current.range.as_ref())?;
+
+                Value::Int32(Num {
+                    value: new.value,
+                    range: current.range.clone(),
+                })
+            }
+            (Value::Single(current), Value::Single(new)) => {
+                ValidationError::check_range(new.value, current.range.as_ref())?;
+
+                Value::Single(Num {
+                    value: new.value,
+                    range: current.range.clone(),
+                })
+            }
+            // the frontend only deals in single precision floats
+            (Value::Double(current), Value::Single(new)) => {
+                let value = new.value as f64;
+                ValidationError::check_range(value, current.range.as_ref())?;
+
+                Value::Double(Num {
+                    value,
+                    range: current.range.clone(),
+                })
+            }
+            (Value::Double(current), Value::Double(new)) => {
+                ValidationError::check_range(new.value, current.range.as_ref())?;
+
+                Value::Double(Num {
+                    value: new.value,
+                    range: current.range.clone(),
+                })
+            }
+            (
+                Value::Enum { options, .. },
+                Value::Enum {
+                    index,
+                    options: new_options,
+                },
+            ) => {
+                let index = find_option(options, &new_options, index)?;
+
+                Value::Enum {
+                    index,
+                    options: options.clone(),
+                }
+            }
+            (
+                Value::Flags { options, .. },
+                Value::Flags {
+                    indicies,
+                    options: new_options,
+                },
+            ) => {
+                let indicies = indicies
+                    .into_iter()
+                    .map(|index| find_option(options, &new_options, index))
+                    .collect::<Result<_, _>>()?;
+
+                Value::Flags {
+                    indicies,
+                    options: options.clone(),
+                }
+            }
+            _ => return Err(mismatch()),
+        };
+
+        return Ok(value);
+
+        fn find_option(
+            options: &[String],
+            new_options: &[String],
+            index: usize,
+        ) -> Result<usize, ValidationError> {
+            let option = new_options
+                .get(index)
+                .ok_or_else(|| ValidationError::UnknownOption {
+                    option: index.to_string(),
+                })?;
+
+            options.iter().position(|opt| opt == option).ok_or_else(|| {
+                ValidationError::UnknownOption {
+                    option: option.clone(),
+                }
+            })
+        }
+    }
+
+    /// Checks the current value against the constraints of this entry.
+    fn check(&self) -> Result<(), ValidationError> {
+        if let Some(option) = self.unknown_options.first() {
+            return Err(ValidationError::UnknownOption {
+                option: option.clone(),
+            });
+        }
+
+        self.validate(self.value.clone()).map(|_| ())
+    }
+
     fn to_frontend(&self) -> frontend::Entry {
         frontend::Entry {
             name: self.name.clone(),
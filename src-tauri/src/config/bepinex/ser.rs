@@ -164,6 +164,13 @@ pub fn to_writer<W: Write>(file: &File, writer: W) -> io::Result<()> {
     serializer.writer.flush()
 }
 
+pub fn value_to_string(value: &Value) -> io::Result<String> {
+    let mut serializer = Serializer { writer: Vec::new() };
+    serializer.write_value(value)?;
+
+    unsafe { Ok(String::from_utf8_unchecked(serializer.writer)) }
+}
+
 #[allow(unused)]
 pub fn to_string(file: &File) -> io::Result<String> {
     let mut vec = Vec::new();
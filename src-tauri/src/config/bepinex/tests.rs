@@ -43,6 +43,7 @@ impl EntryKind {
             type_name: type_name.to_owned(),
             default_value,
             value,
+            unknown_options: Vec::new(),
         }
         .into()
     }
@@ -211,3 +212,74 @@ fn check_from_string() {
 
     assert_eq!(left, right);
 }
+
+#[test]
+fn check_validate_range() {
+    let mut file = test_file();
+    let entry = file.find_entry("Section2", "Entry4").unwrap();
+
+    let valid = frontend::Value::Int(Num {
+        value: 10,
+        range: None,
+    });
+    assert!(entry.set(valid).is_ok());
+
+    let invalid = frontend::Value::Int(Num {
+        value: 11,
+        range: Some(0..100),
+    });
+    assert!(entry.set(invalid).is_err());
+}
+
+#[test]
+fn check_validate_options() {
+    let mut file = test_file();
+    let entry = file.find_entry("Section1", "Entry3").unwrap();
+
+    let invalid = frontend::Value::Enum {
+        index: 0,
+        options: vec!["Impossible".to_owned()],
+    };
+    assert!(entry.set(invalid).is_err());
+
+    let mismatch = frontend::Value::Bool(true);
+    assert!(entry.set(mismatch).is_err());
+}
+
+#[test]
+fn check_validate_on_disk() {
+    let str = TEST_STR.replace("Entry3 = Easy", "Entry3 = Nightmare");
+    let file = de::from_reader(str.as_bytes()).unwrap();
+
+    assert_eq!(
+        file.validate(),
+        vec![(
+            "Section1",
+            "Entry3",
+            ValidationError::UnknownOption {
+                option: "Nightmare".to_owned()
+            }
+        )]
+    );
+}
+
+#[test]
+fn check_validate_after_fix() {
+    let str = TEST_STR.replace("Entry3 = Easy", "Entry3 = Nightmare");
+    let mut file: File = de::from_reader(str.as_bytes()).unwrap();
+
+    let entry = file.find_entry("Section1", "Entry3").unwrap();
+    let valid = frontend::Value::Enum {
+        index: 1,
+        options: vec!["Easy".to_owned(), "Medium".to_owned(), "Hard".to_owned()],
+    };
+    entry.set(valid).unwrap();
+    assert!(file.validate().is_empty());
+
+    let mut file: File = de::from_reader(str.as_bytes()).unwrap();
+    file.find_entry("Section1", "Entry3")
+        .unwrap()
+        .reset()
+        .unwrap();
+    assert!(file.validate().is_empty());
+}
@@ -1,10 +1,21 @@
-use std::path::Path;
+use std::{
+    cmp::Reverse,
+    path::{Path, PathBuf},
+};
 
+use chrono::{DateTime, Utc};
 use eyre::{Context, eyre};
 use tauri::{AppHandle, command};
+use tracing::warn;
 
-use super::{AnyFileKind, frontend};
-use crate::{state::ManagerExt, util::cmd::Result};
+use super::{
+    AnyFileKind, frontend,
+    history::ConfigChange,
+    orphans::{OrphanAction, OrphanedFile},
+    search::SearchHit,
+    validate::ValidationReport,
+};
+use crate::{profile::export::modpack, state::ManagerExt, util::cmd::Result};
 
 #[command]
 pub fn get_config_files(app: AppHandle) -> Result<Vec<frontend::File>> {
@@ -13,9 +24,59 @@ pub fn get_config_files(app: AppHandle) -> Result<Vec<frontend::File>> {
 
     profile.refresh_config();
 
+    if let Err(err) = app.config_watcher().watch(profile, &app) {
+        warn!("failed to watch config files: {:#}", err);
+    }
+
     Ok(profile.config_cache.to_frontend())
 }
 
+#[command]
+pub fn search_config(
+    query: String,
+    all_profiles: bool,
+    max_results: usize,
+    app: AppHandle,
+) -> Result<Vec<SearchHit>> {
+    let mut manager = app.lock_manager();
+
+    let game = manager.active_game_mut();
+
+    // inactive profiles are only read the first time they're searched, since
+    // re-reading every profile's config on each keystroke is too slow
+    for profile in &mut game.profiles {
+        let is_active = profile.id == game.active_profile_id;
+
+        if is_active || (all_profiles && profile.config_cache.0.is_empty()) {
+            profile.refresh_config();
+        }
+    }
+
+    let mut hits = if all_profiles {
+        game.profiles
+            .iter()
+            .flat_map(|profile| profile.search_config(&query))
+            .collect()
+    } else {
+        manager.active_profile().search_config(&query)
+    };
+
+    hits.sort_by_key(|hit| Reverse(hit.score));
+    hits.truncate(max_results);
+
+    Ok(hits)
+}
+
+#[command]
+pub fn validate_profile_config(app: AppHandle) -> Result<ValidationReport> {
+    let mut manager = app.lock_manager();
+    let profile = manager.active_profile_mut();
+
+    profile.refresh_config();
+
+    Ok(profile.config_cache.validate())
+}
+
 #[command]
 pub fn set_config_entry(
     file: &Path,
@@ -25,17 +86,12 @@ pub fn set_config_entry(
     app: AppHandle,
 ) -> Result<()> {
     let mut manager = app.lock_manager();
-
     let profile = manager.active_profile_mut();
-    let file = profile.config_cache.find_file(file)?;
 
-    match &mut file.kind {
-        AnyFileKind::BepInEx(file) => file.find_entry(section, entry)?.set(value),
-        AnyFileKind::GDWeave(file) => file.set(entry, value),
-        _ => return Err(eyre!("unsupported for this format").into()),
-    }?;
+    profile.edit_config(file, Some((section, entry)), app.db(), |kind| {
+        kind.set_entry(section, entry, value)
+    })?;
 
-    file.write(&profile.path).context("failed to write file")?;
     Ok(())
 }
 
@@ -47,32 +103,66 @@ pub fn reset_config_entry(
     app: AppHandle,
 ) -> Result<frontend::Value> {
     let mut manager = app.lock_manager();
-
     let profile = manager.active_profile_mut();
-    let file = profile.config_cache.find_file(file)?;
 
-    let value = match &mut file.kind {
+    let value = profile.edit_config(file, Some((section, entry)), app.db(), |kind| match kind {
         AnyFileKind::BepInEx(file) => file.find_entry(section, entry)?.reset(),
-        _ => return Err(eyre!("unsupported for this format").into()),
-    }?;
+        _ => Err(eyre!("unsupported for this format")),
+    })?;
 
-    file.write(&profile.path).context("failed to write file")?;
     Ok(value)
 }
 
 #[command]
 pub fn reset_config_file(file: &Path, app: AppHandle) -> Result<()> {
     let mut manager = app.lock_manager();
-
     let profile = manager.active_profile_mut();
-    let file = profile.config_cache.find_file(file)?;
 
-    match &mut file.kind {
-        AnyFileKind::BepInEx(file) => file.reset_all()?,
-        _ => return Err(eyre!("unsupported for this format").into()),
-    };
+    profile.edit_config(file, None, app.db(), |kind| match kind {
+        AnyFileKind::BepInEx(file) => file.reset_all(),
+        _ => Err(eyre!("unsupported for this format")),
+    })?;
+
+    Ok(())
+}
+
+#[command]
+pub fn get_config_history(
+    file: &Path,
+    section: Option<&str>,
+    entry: Option<&str>,
+    limit: usize,
+    app: AppHandle,
+) -> Result<Vec<ConfigChange>> {
+    let manager = app.lock_manager();
+    let profile = manager.active_profile();
+
+    let history = app
+        .db()
+        .config_history(profile.id, file, section.zip(entry), limit)?;
+
+    Ok(history)
+}
+
+#[command]
+pub fn undo_config_change(id: i64, app: AppHandle) -> Result<()> {
+    let mut manager = app.lock_manager();
+
+    manager
+        .active_profile_mut()
+        .undo_config_change(id, app.db())?;
+
+    Ok(())
+}
+
+#[command]
+pub fn revert_config_file(file: &Path, time: DateTime<Utc>, app: AppHandle) -> Result<()> {
+    let mut manager = app.lock_manager();
+
+    manager
+        .active_profile_mut()
+        .revert_config_file(file, time, app.db())?;
 
-    file.write(&profile.path).context("failed to write file")?;
     Ok(())
 }
 
@@ -110,3 +200,41 @@ pub fn delete_config_file(file: &Path, app: AppHandle) -> Result<()> {
 
     Ok(())
 }
+
+#[command]
+pub fn find_orphaned_config(app: AppHandle) -> Result<Vec<OrphanedFile>> {
+    let mut manager = app.lock_manager();
+    let profile = manager.active_profile_mut();
+
+    Ok(profile.find_orphaned_config())
+}
+
+#[command]
+pub fn resolve_orphaned_config(
+    files: Vec<PathBuf>,
+    action: OrphanAction,
+    app: AppHandle,
+) -> Result<()> {
+    let mut manager = app.lock_manager();
+
+    let game = manager.active_game;
+    let profile = manager.active_profile_mut();
+
+    profile.remove_config_files(&files, action)?;
+
+    if let OrphanAction::Exclude = action {
+        modpack::refresh_args(profile, &app.lock_thunderstore(), game);
+
+        let args = profile
+            .modpack
+            .as_mut()
+            .expect("modpack args should be set");
+        for file in files {
+            args.include_files.insert(file, false);
+        }
+
+        profile.save(&app, false)?;
+    }
+
+    Ok(())
+}
@@ -57,7 +57,7 @@ pub struct Entry {
     pub value: Value,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase", tag = "type", content = "content")]
 pub enum Value {
     Bool(bool),
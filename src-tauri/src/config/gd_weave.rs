@@ -27,6 +27,10 @@ impl File {
         Ok(())
     }
 
+    pub fn entry_snapshot(&self, name: &str) -> Option<String> {
+        self.entries.get(name).map(Value::to_string)
+    }
+
     pub fn to_frontend(&self) -> Result<frontend::FileData> {
         let entries = self
             .entries
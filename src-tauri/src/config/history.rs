@@ -0,0 +1,308 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use eyre::{Context, OptionExt, Result, bail};
+use serde::Serialize;
+use tracing::warn;
+
+use super::{AnyFileKind, frontend};
+use crate::{db::Db, profile::Profile};
+
+/// A single changed entry in a config file.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryChange {
+    pub section: String,
+    pub entry: String,
+    /// `None` if the entry didn't exist before the change.
+    pub old_value: Option<frontend::Value>,
+    pub new_value: frontend::Value,
+}
+
+/// An [`EntryChange`] as recorded in the database.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChange {
+    pub id: i64,
+    pub file: PathBuf,
+    #[serde(flatten)]
+    pub change: EntryChange,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl AnyFileKind {
+    fn entry_values(&self) -> Vec<(String, String, frontend::Value)> {
+        let data = match self {
+            AnyFileKind::BepInEx(file) => file.to_frontend(),
+            AnyFileKind::GDWeave(file) => match file.to_frontend() {
+                Ok(data) => data,
+                Err(_) => return Vec::new(),
+            },
+            AnyFileKind::Err(_) | AnyFileKind::Unsupported => return Vec::new(),
+        };
+
+        data.sections
+            .into_iter()
+            .flat_map(|section| {
+                section
+                    .entries
+                    .into_iter()
+                    .map(move |entry| (section.name.clone(), entry.name, entry.value))
+            })
+            .collect()
+    }
+
+    pub(super) fn set_entry(
+        &mut self,
+        section: &str,
+        entry: &str,
+        value: frontend::Value,
+    ) -> Result<()> {
+        match self {
+            AnyFileKind::BepInEx(file) => file.find_entry(section, entry)?.set(value),
+            AnyFileKind::GDWeave(file) => file.set(entry, value),
+            _ => bail!("unsupported for this format"),
+        }
+    }
+}
+
+impl Profile {
+    /// Applies `f` to a config file and writes it back to disk,
+    /// recording every changed entry in the config history.
+    ///
+    /// The file is reloaded first if it was changed on disk. If `entry` is given and
+    /// was among the changes, the edit is rejected instead.
+    pub(super) fn edit_config<T, F>(
+        &mut self,
+        file: &Path,
+        entry: Option<(&str, &str)>,
+        db: &Db,
+        f: F,
+    ) -> Result<T>
+    where
+        F: FnOnce(&mut AnyFileKind) -> Result<T>,
+    {
+        let profile_id = self.id;
+        let file = self.config_cache.find_file(file)?;
+
+        file.reload_if_stale(entry, &self.path, &self.game.mod_loader)?;
+
+        let before = file.kind.entry_values();
+        let res = f(&mut file.kind)?;
+
+        file.write(&self.path).context("failed to write file")?;
+
+        let changes = diff(before, file.kind.entry_values());
+        if !changes.is_empty()
+            && let Err(err) = db.insert_config_changes(profile_id, &file.relative_path, &changes)
+        {
+            warn!("failed to record config history: {:#}", err);
+        }
+
+        Ok(res)
+    }
+
+    /// Sets an entry back to the value it had before the given change.
+    pub fn undo_config_change(&mut self, id: i64, db: &Db) -> Result<()> {
+        let change = db.config_change(self.id, id)?;
+
+        let old_value = change
+            .change
+            .old_value
+            .ok_or_eyre("entry did not exist before this change")?;
+
+        let (section, entry) = (&change.change.section, &change.change.entry);
+
+        self.edit_config(&change.file, Some((section, entry)), db, |kind| {
+            kind.set_entry(section, entry, old_value)
+        })
+    }
+
+    /// Restores every entry in a file to the value it had at the given point in time.
+    pub fn revert_config_file(&mut self, file: &Path, time: DateTime<Utc>, db: &Db) -> Result<()> {
+        let changes = db.config_changes_since(self.id, file, time)?;
+
+        // changes are ordered from oldest to newest, so the first change
+        // to each entry holds the value from before the given time
+        let mut values = HashMap::new();
+        for change in changes {
+            let EntryChange {
+                section,
+                entry,
+                old_value,
+                ..
+            } = change.change;
+
+            values.entry((section, entry)).or_insert(old_value);
+        }
+
+        self.edit_config(file, None, db, |kind| {
+            for ((section, entry), value) in values {
+                // entries that didn't exist back then are left alone
+                let Some(value) = value else {
+                    continue;
+                };
+
+                if let Err(err) = kind.set_entry(&section, &entry, value) {
+                    warn!("failed to revert {}.{}: {:#}", section, entry, err);
+                }
+            }
+
+            Ok(())
+        })
+    }
+}
+
+fn diff(
+    before: Vec<(String, String, frontend::Value)>,
+    after: Vec<(String, String, frontend::Value)>,
+) -> Vec<EntryChange> {
+    let mut before: HashMap<_, _> = before
+        .into_iter()
+        .map(|(section, entry, value)| ((section, entry), value))
+        .collect();
+
+    after
+        .into_iter()
+        .filter_map(|(section, entry, new_value)| {
+            let old_value = before.remove(&(section.clone(), entry.clone()));
+
+            // compare the serialized values, since NaN floats are never equal to themselves
+            let unchanged = old_value.as_ref().is_some_and(|old_value| {
+                serde_json::to_string(old_value).ok() == serde_json::to_string(&new_value).ok()
+            });
+
+            (!unchanged).then_some(EntryChange {
+                section,
+                entry,
+                old_value,
+                new_value,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        fs,
+    };
+
+    use super::*;
+    use crate::{
+        config::{ConfigCache, frontend::Num},
+        db, game,
+        profile::install::rules::InstallRules,
+    };
+
+    const CONFIG: &str = "[General]
+
+## An entry
+# Setting type: Int32
+# Default value: 1
+Entry = 1
+";
+
+    fn int(value: i32) -> frontend::Value {
+        frontend::Value::Int(Num { value, range: None })
+    }
+
+    fn float(value: f32) -> frontend::Value {
+        frontend::Value::Float(Num { value, range: None })
+    }
+
+    fn values(
+        values: impl IntoIterator<Item = (&'static str, frontend::Value)>,
+    ) -> Vec<(String, String, frontend::Value)> {
+        values
+            .into_iter()
+            .map(|(entry, value)| ("General".to_owned(), entry.to_owned(), value))
+            .collect()
+    }
+
+    #[test]
+    fn diff_changed_and_added_entries() {
+        let before = values([("Changed", int(1)), ("Same", int(1)), ("Removed", int(1))]);
+        let after = values([("Changed", int(2)), ("Same", int(1)), ("Added", int(1))]);
+
+        let changes = diff(before, after);
+
+        assert_eq!(changes.len(), 2);
+
+        assert_eq!(changes[0].entry, "Changed");
+        assert_eq!(changes[0].old_value, Some(int(1)));
+        assert_eq!(changes[0].new_value, int(2));
+
+        assert_eq!(changes[1].entry, "Added");
+        assert_eq!(changes[1].old_value, None);
+        assert_eq!(changes[1].new_value, int(1));
+    }
+
+    #[test]
+    fn diff_unchanged_nan() {
+        let before = values([("Entry", float(f32::NAN))]);
+        let after = values([("Entry", float(f32::NAN))]);
+
+        assert!(diff(before, after).is_empty());
+    }
+
+    #[test]
+    fn edit_then_undo() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("BepInEx/config/Mod.cfg");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, CONFIG).unwrap();
+
+        let mut profile = Profile {
+            id: 1,
+            name: "Default".to_owned(),
+            path: temp.path().to_owned(),
+            mods: Vec::new(),
+            game: game::from_slug("repo").unwrap(),
+            ignored_version_updates: HashSet::new(),
+            ignored_package_updates: HashSet::new(),
+            config_cache: ConfigCache::default(),
+            linked_config: HashMap::new(),
+            modpack: None,
+            sync: None,
+            custom_args: String::new(),
+            missing: false,
+            install_rules: InstallRules::default(),
+        };
+
+        profile.config_cache.refresh_paths(
+            &profile.path,
+            &profile.game.mod_loader,
+            std::slice::from_ref(&path),
+        );
+
+        let db = db::in_memory().unwrap();
+        let file = Path::new("BepInEx/config/Mod.cfg");
+        let entry = Some(("General", "Entry"));
+
+        profile
+            .edit_config(file, entry, &db, |kind| {
+                kind.set_entry("General", "Entry", int(5))
+            })
+            .unwrap();
+
+        assert!(fs::read_to_string(&path).unwrap().contains("Entry = 5"));
+
+        let history = db.config_history(profile.id, file, entry, 10).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].change.old_value, Some(int(1)));
+
+        profile.undo_config_change(history[0].id, &db).unwrap();
+
+        assert!(fs::read_to_string(&path).unwrap().contains("Entry = 1"));
+
+        // undoing is recorded as a change of its own
+        let history = db.config_history(profile.id, file, entry, 10).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].change.new_value, int(1));
+    }
+}
@@ -7,7 +7,7 @@ use std::{
     time::SystemTime,
 };
 
-use eyre::{Context, OptionExt, Result};
+use eyre::{Context, OptionExt, Result, bail};
 use itertools::Itertools;
 use rayon::prelude::*;
 use tracing::debug;
@@ -23,6 +23,11 @@ mod bepinex;
 pub mod commands;
 mod frontend;
 mod gd_weave;
+pub mod history;
+pub mod orphans;
+pub mod search;
+pub mod validate;
+pub mod watch;
 
 #[derive(Debug, Default)]
 pub struct ConfigCache(Vec<AnyFile>);
@@ -51,7 +56,7 @@ impl AnyFile {
             .to_string_lossy()
     }
 
-    fn write(&self, profile_dir: &Path) -> Result<()> {
+    fn write(&mut self, profile_dir: &Path) -> Result<()> {
         debug!("writing config file to {}", self.relative_path.display());
 
         let path = profile_dir.join(&self.relative_path);
@@ -64,7 +69,75 @@ impl AnyFile {
             AnyFileKind::GDWeave(file) => file.write(writer),
             AnyFileKind::Err(_) => Ok(()),
             AnyFileKind::Unsupported => Ok(()),
+        }?;
+
+        // don't pick up our own write as an external change
+        self.read_time = SystemTime::now();
+
+        Ok(())
+    }
+
+    /// Checks whether the file belongs to a mod, given its lowercase name.
+    fn matches_mod(&self, mod_name: &str) -> bool {
+        // check the segments of the file name for a match
+        let file_name = self.file_stem().to_lowercase();
+        if file_name.split('.').any(|segment| segment == mod_name) {
+            return true;
+        }
+
+        let Some(metadata_name) = self.kind.mod_name() else {
+            return false;
+        };
+
+        metadata_name.to_lowercase() == mod_name
+    }
+
+    /// Whether the file has been modified on disk since we last read or wrote it.
+    fn is_stale(&self, profile_dir: &Path) -> bool {
+        fs::metadata(profile_dir.join(&self.relative_path))
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| modified > self.read_time)
+    }
+
+    /// Re-reads the file if it has been changed on disk, for example by the game.
+    ///
+    /// Fails if the given entry differs between the cached and the new version, since
+    /// writing the cached version back would discard the external change.
+    fn reload_if_stale(
+        &mut self,
+        entry: Option<(&str, &str)>,
+        profile_dir: &Path,
+        mod_loader: &ModLoader,
+    ) -> Result<()> {
+        if !self.is_stale(profile_dir) {
+            return Ok(());
+        }
+
+        let path = profile_dir.join(&self.relative_path);
+        let Some(kind) = read_kind(&path, mod_loader) else {
+            return Ok(());
+        };
+
+        debug!(
+            "{} was changed on disk, reloading before write",
+            self.relative_path.display()
+        );
+
+        let conflict = entry.filter(|(section, entry)| {
+            self.kind.entry_snapshot(section, entry) != kind.entry_snapshot(section, entry)
+        });
+
+        self.kind = kind;
+        self.read_time = SystemTime::now();
+
+        if let Some((_, entry)) = conflict {
+            bail!(
+                "{} was changed on disk while you were editing it, reload the file and try again",
+                entry
+            );
         }
+
+        Ok(())
     }
 }
 
@@ -75,6 +148,15 @@ impl AnyFileKind {
             _ => None,
         }
     }
+
+    /// Returns the serialized value of an entry, used to compare entries across reads.
+    fn entry_snapshot(&self, section: &str, entry: &str) -> Option<String> {
+        match self {
+            Self::BepInEx(file) => file.entry_snapshot(section, entry),
+            Self::GDWeave(file) => file.entry_snapshot(entry),
+            _ => None,
+        }
+    }
 }
 
 impl Profile {
@@ -83,6 +165,21 @@ impl Profile {
         self.link_config();
     }
 
+    /// Re-reads the given absolute paths, which are expected to be inside the profile directory.
+    ///
+    /// Returns the relative paths of the files that were changed or removed.
+    pub fn refresh_config_paths(&mut self, paths: &[PathBuf]) -> ConfigChanges {
+        let changes = self
+            .config_cache
+            .refresh_paths(&self.path, &self.game.mod_loader, paths);
+
+        if !changes.is_empty() {
+            self.link_config();
+        }
+
+        changes
+    }
+
     fn link_config(&mut self) {
         for profile_mod in &self.mods {
             let lowercase_name = profile_mod.ident().name().to_lowercase();
@@ -90,26 +187,59 @@ impl Profile {
                 .config_cache
                 .0
                 .iter()
-                .find(|file| matches(file, &lowercase_name));
+                .find(|file| file.matches_mod(&lowercase_name));
 
             if let Some(file) = file {
                 self.linked_config
                     .insert(profile_mod.uuid(), file.relative_path.clone());
             }
         }
+    }
+}
 
-        fn matches(file: &AnyFile, mod_name: &str) -> bool {
-            // check the segments of the file name for a match
-            let file_name = file.file_stem().to_lowercase();
-            if file_name.split('.').any(|segment| segment == mod_name) {
-                return true;
-            }
+#[derive(Debug, Default)]
+pub struct ConfigChanges {
+    pub changed: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+}
 
-            let Some(metadata_name) = file.kind.mod_name() else {
-                return false;
-            };
+impl ConfigChanges {
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+fn read_kind(path: &Path, mod_loader: &ModLoader) -> Option<AnyFileKind> {
+    const EXTENSIONS: &[&str] = &["cfg", "txt", "json", "yml", "yaml", "ini", "xml"];
 
-            metadata_name.to_lowercase() == mod_name
+    let extension = path.extension().and_then(|ext| ext.to_str())?;
+
+    let kind = match (&mod_loader.kind, extension) {
+        (ModLoaderKind::BepInEx { .. } | ModLoaderKind::BepisLoader { .. }, "cfg") => {
+            read_file(path, bepinex::File::read, AnyFileKind::BepInEx)
+        }
+        (ModLoaderKind::GDWeave {}, "json") => {
+            read_file(path, gd_weave::File::read, AnyFileKind::GDWeave)
+        }
+        (_, ext) if EXTENSIONS.contains(&ext) => AnyFileKind::Unsupported,
+        _ => return None,
+    };
+
+    return Some(kind);
+
+    fn read_file<T, F, G>(path: &Path, f: F, g: G) -> AnyFileKind
+    where
+        F: FnOnce(BufReader<fs::File>) -> Result<T>,
+        G: FnOnce(T) -> AnyFileKind,
+    {
+        let file = fs::File::open(path)
+            .map(BufReader::new)
+            .context("failed to open file")
+            .and_then(f);
+
+        match file {
+            Ok(file) => g(file),
+            Err(err) => AnyFileKind::Err(err),
         }
     }
 }
@@ -147,6 +277,72 @@ impl ConfigCache {
         self.resolve_duplicate_names();
     }
 
+    fn refresh_paths(
+        &mut self,
+        profile: &Path,
+        mod_loader: &ModLoader,
+        paths: &[PathBuf],
+    ) -> ConfigChanges {
+        let mut changes = ConfigChanges::default();
+
+        for path in paths {
+            let Ok(relative_path) = path.strip_prefix(profile) else {
+                continue;
+            };
+
+            if !path.exists() {
+                let relative_path: PathBuf =
+                    relative_path.to_string_lossy().replace('\\', "/").into();
+                let len = self.0.len();
+                self.0.retain(|file| file.relative_path != relative_path);
+
+                if self.0.len() != len {
+                    changes.removed.push(relative_path);
+                }
+
+                continue;
+            }
+
+            let Some(config_dir) = mod_loader
+                .mod_config_dirs()
+                .iter()
+                .map(|dir| profile.join(dir))
+                .find(|dir| path.starts_with(dir))
+                .or_else(|| {
+                    mod_loader
+                        .mod_config_dirs()
+                        .is_empty()
+                        .then(|| profile.to_owned())
+                })
+            else {
+                continue;
+            };
+
+            let Some(Ok(entry)) = WalkDir::new(path).max_depth(0).into_iter().next() else {
+                continue;
+            };
+
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            if let Some((file, index)) = self.read_file(entry, profile, &config_dir, mod_loader) {
+                changes.changed.push(file.relative_path.clone());
+
+                match index {
+                    Some(index) => self.0[index] = file,
+                    None => self.0.push(file),
+                };
+            }
+        }
+
+        if !changes.changed.is_empty() {
+            self.resolve_duplicate_names();
+        }
+
+        changes
+    }
+
     fn read_file(
         &self,
         entry: walkdir::DirEntry,
@@ -154,10 +350,6 @@ impl ConfigCache {
         config_dir: &Path,
         mod_loader: &ModLoader,
     ) -> Option<(AnyFile, Option<usize>)> {
-        const EXTENSIONS: &[&str] = &["cfg", "txt", "json", "yml", "yaml", "ini", "xml"];
-
-        let extension = entry.path().extension().and_then(|ext| ext.to_str())?;
-
         let relative_path: PathBuf = entry
             .path()
             .strip_prefix(root)
@@ -175,16 +367,7 @@ impl ConfigCache {
             return None;
         }
 
-        let kind = match (&mod_loader.kind, extension) {
-            (ModLoaderKind::BepInEx { .. } | ModLoaderKind::BepisLoader { .. }, "cfg") => {
-                read_file(&entry, bepinex::File::read, AnyFileKind::BepInEx)
-            }
-            (ModLoaderKind::GDWeave {}, "json") => {
-                read_file(&entry, gd_weave::File::read, AnyFileKind::GDWeave)
-            }
-            (_, ext) if EXTENSIONS.contains(&ext) => AnyFileKind::Unsupported,
-            _ => return None,
-        };
+        let kind = read_kind(entry.path(), mod_loader)?;
 
         let display_name = match kind.mod_name() {
             Some(name) => Cow::Borrowed(name),
@@ -209,23 +392,7 @@ impl ConfigCache {
             kind,
         };
 
-        return Some((file, curr_index));
-
-        fn read_file<T, F, G>(entry: &walkdir::DirEntry, f: F, g: G) -> AnyFileKind
-        where
-            F: FnOnce(BufReader<fs::File>) -> Result<T>,
-            G: FnOnce(T) -> AnyFileKind,
-        {
-            let file = fs::File::open(entry.path())
-                .map(BufReader::new)
-                .context("failed to open file")
-                .and_then(f);
-
-            match file {
-                Ok(file) => g(file),
-                Err(err) => AnyFileKind::Err(err),
-            }
-        }
+        Some((file, curr_index))
     }
 
     fn needs_refresh(&self, curr_index: Option<usize>, entry: &walkdir::DirEntry) -> bool {
@@ -323,3 +490,87 @@ impl ConfigCache {
             .ok_or_eyre("file not found")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use super::*;
+    use crate::game;
+
+    const CONFIG: &str = "[General]\n\nEntry = 1\n\nOther = 1\n";
+
+    fn mod_loader() -> &'static ModLoader<'static> {
+        &game::from_slug("repo").unwrap().mod_loader
+    }
+
+    fn setup() -> (tempfile::TempDir, PathBuf) {
+        let profile = tempfile::tempdir().unwrap();
+        let path = profile.path().join("BepInEx/config/Mod.cfg");
+
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, CONFIG).unwrap();
+
+        (profile, path)
+    }
+
+    #[test]
+    fn refresh_changed_and_removed_paths() {
+        let (profile, path) = setup();
+        let outside = profile.path().join("BepInEx/plugins/Mod.cfg");
+        fs::create_dir_all(outside.parent().unwrap()).unwrap();
+        fs::write(&outside, CONFIG).unwrap();
+
+        let mut cache = ConfigCache::default();
+        let changes = cache.refresh_paths(profile.path(), mod_loader(), &[path.clone(), outside]);
+
+        assert_eq!(changes.changed, [PathBuf::from("BepInEx/config/Mod.cfg")]);
+        assert!(changes.removed.is_empty());
+        assert_eq!(cache.0.len(), 1);
+        assert!(matches!(cache.0[0].kind, AnyFileKind::BepInEx(_)));
+
+        fs::remove_file(&path).unwrap();
+
+        let changes = cache.refresh_paths(profile.path(), mod_loader(), &[path]);
+
+        assert!(changes.changed.is_empty());
+        assert_eq!(changes.removed, [PathBuf::from("BepInEx/config/Mod.cfg")]);
+        assert!(cache.0.is_empty());
+    }
+
+    #[test]
+    fn reload_stale_file() {
+        let (profile, path) = setup();
+
+        let mut cache = ConfigCache::default();
+        cache.refresh_paths(profile.path(), mod_loader(), std::slice::from_ref(&path));
+
+        let file = &mut cache.0[0];
+        assert!(!file.is_stale(profile.path()));
+
+        fs::write(&path, CONFIG.replace("Other = 1", "Other = 2")).unwrap();
+        file.read_time = UNIX_EPOCH;
+
+        // a different entry than the one being edited changed
+        file.reload_if_stale(Some(("General", "Entry")), profile.path(), mod_loader())
+            .unwrap();
+        assert_eq!(
+            file.kind.entry_snapshot("General", "Other").as_deref(),
+            Some("2")
+        );
+        assert!(!file.is_stale(profile.path()));
+
+        fs::write(&path, CONFIG.replace("Entry = 1", "Entry = 2")).unwrap();
+        file.read_time = UNIX_EPOCH;
+
+        // the edited entry changed, so the edit would overwrite it
+        assert!(
+            file.reload_if_stale(Some(("General", "Entry")), profile.path(), mod_loader())
+                .is_err()
+        );
+        assert_eq!(
+            file.kind.entry_snapshot("General", "Entry").as_deref(),
+            Some("2")
+        );
+    }
+}
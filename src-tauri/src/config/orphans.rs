@@ -0,0 +1,232 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use eyre::{Context, Result, ensure};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::AnyFile;
+use crate::{
+    profile::Profile,
+    util::{self, error::IoResultExt},
+};
+
+/// Directory in the profile where archived config files are moved to.
+pub const ARCHIVE_DIR: &str = "config_archive";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanedFile {
+    pub relative_path: PathBuf,
+    pub display_name: String,
+    pub plugin_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OrphanAction {
+    /// Move the files into [`ARCHIVE_DIR`], out of the reach of the game.
+    Archive,
+    /// Move the files to the recycle bin.
+    Delete,
+    /// Leave the files in place, but exclude them from modpack exports.
+    Exclude,
+}
+
+impl Profile {
+    /// Finds config files that don't belong to any of the profile's mods,
+    /// usually left behind by mods that have since been removed.
+    pub fn find_orphaned_config(&mut self) -> Vec<OrphanedFile> {
+        // without dedicated config directories we can't tell config files apart from everything else
+        if self.game.mod_loader.mod_config_dirs().is_empty() {
+            return Vec::new();
+        }
+
+        self.refresh_config();
+
+        let mut mod_names = self
+            .mods
+            .iter()
+            .map(|profile_mod| normalize(profile_mod.ident().name()))
+            .collect_vec();
+
+        // the mod loader's own config, e.g. BepInEx.cfg
+        mod_names.push(normalize(self.game.mod_loader.as_str()));
+
+        self.config_cache
+            .0
+            .iter()
+            .filter(|file| !mod_names.iter().any(|name| loosely_matches_mod(file, name)))
+            .map(|file| OrphanedFile {
+                relative_path: file.relative_path.clone(),
+                display_name: file.display_name.clone(),
+                plugin_name: file.kind.mod_name().map(str::to_owned),
+            })
+            .collect()
+    }
+
+    /// Archives or deletes the given config files.
+    ///
+    /// [`OrphanAction::Exclude`] is handled by the caller, since it requires the modpack args.
+    pub fn remove_config_files(&mut self, files: &[PathBuf], action: OrphanAction) -> Result<()> {
+        for file in files {
+            ensure!(
+                util::fs::is_enclosed(file) && self.config_cache.find_file(file).is_ok(),
+                "{} is not a config file in this profile",
+                file.display()
+            );
+        }
+
+        match action {
+            OrphanAction::Archive => {
+                for file in files {
+                    archive_file(&self.path, file)?;
+                }
+            }
+            OrphanAction::Delete => {
+                trash::delete_all(files.iter().map(|file| self.path.join(file)))
+                    .context("failed to move files to recycle bin")?;
+            }
+            OrphanAction::Exclude => return Ok(()),
+        }
+
+        info!(count = files.len(), ?action, "removed config files");
+
+        self.config_cache
+            .0
+            .retain(|cached| !files.contains(&cached.relative_path));
+
+        Ok(())
+    }
+}
+
+/// Checks whether a file belongs to a mod, given its normalized name.
+///
+/// This is looser than [`AnyFile::matches_mod`], which is used to link config files,
+/// since mods often name their package and plugin slightly differently and
+/// a missed match here would report a file that's still in use.
+fn loosely_matches_mod(file: &AnyFile, mod_name: &str) -> bool {
+    // check the segments of the file name for a match
+    if file
+        .file_stem()
+        .split('.')
+        .any(|segment| normalize(segment) == mod_name)
+    {
+        return true;
+    }
+
+    file.kind
+        .mod_name()
+        .is_some_and(|metadata_name| normalize(metadata_name) == mod_name)
+}
+
+/// Lowercases `name` and removes spaces, dashes and underscores.
+fn normalize(name: &str) -> String {
+    name.to_lowercase().replace([' ', '-', '_'], "")
+}
+
+fn archive_file(profile_dir: &Path, file: &Path) -> Result<()> {
+    let src = profile_dir.join(file);
+    let dest = unused_path(profile_dir.join(ARCHIVE_DIR).join(file));
+
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).fs_context("creating archive directory", parent)?;
+    }
+
+    fs::rename(&src, &dest).fs_context("moving config file to archive", &src)?;
+
+    Ok(())
+}
+
+/// Appends a counter to the file name of `path`, like `Plugin (2).cfg`,
+/// until it doesn't collide with an existing file.
+///
+/// This keeps earlier archived copies of the same file around.
+fn unused_path(path: PathBuf) -> PathBuf {
+    if !path.exists() {
+        return path;
+    }
+
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+
+    (2..)
+        .map(|counter| path.with_file_name(format!("{stem} ({counter}){extension}")))
+        .find(|candidate| !candidate.exists())
+        .expect("there should be an unused file name")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use super::*;
+    use crate::config::{AnyFileKind, bepinex};
+
+    fn file(relative_path: &str, kind: AnyFileKind) -> AnyFile {
+        AnyFile {
+            display_name: relative_path.to_owned(),
+            relative_path: relative_path.into(),
+            read_time: SystemTime::now(),
+            kind,
+        }
+    }
+
+    #[test]
+    fn normalize_names() {
+        assert_eq!(normalize("More_Suits"), "moresuits");
+        assert_eq!(normalize("Lethal-Company API"), "lethalcompanyapi");
+    }
+
+    #[test]
+    fn loose_matches() {
+        let cfg = file(
+            "BepInEx/config/x753.More_Suits.cfg",
+            AnyFileKind::Unsupported,
+        );
+
+        assert!(loosely_matches_mod(&cfg, &normalize("MoreSuits")));
+        assert!(loosely_matches_mod(&cfg, &normalize("x753")));
+        assert!(!loosely_matches_mod(&cfg, &normalize("Suits")));
+
+        let metadata = "## Settings file was created by plugin More Suits v1.4.0\n\
+            ## Plugin GUID: x753.MoreSuits\n";
+        let cfg = file(
+            "BepInEx/config/com.x753.suits.cfg",
+            AnyFileKind::BepInEx(bepinex::File::read(metadata.as_bytes()).unwrap()),
+        );
+
+        assert!(loosely_matches_mod(&cfg, &normalize("More_Suits")));
+        assert!(!loosely_matches_mod(&cfg, &normalize("LessSuits")));
+    }
+
+    #[test]
+    fn archive_keeps_earlier_copies() {
+        let profile_dir = tempfile::tempdir().unwrap();
+        let config_dir = profile_dir.path().join("BepInEx").join("config");
+        fs::create_dir_all(&config_dir).unwrap();
+
+        let file = Path::new("BepInEx/config/Plugin.cfg");
+
+        for contents in ["first", "second"] {
+            fs::write(config_dir.join("Plugin.cfg"), contents).unwrap();
+            archive_file(profile_dir.path(), file).unwrap();
+        }
+
+        let archive_dir = profile_dir.path().join(ARCHIVE_DIR).join("BepInEx/config");
+        assert_eq!(
+            fs::read_to_string(archive_dir.join("Plugin.cfg")).unwrap(),
+            "first"
+        );
+        assert_eq!(
+            fs::read_to_string(archive_dir.join("Plugin (2).cfg")).unwrap(),
+            "second"
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use std::path::PathBuf;
+
+use itertools::Itertools;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::{AnyFile, AnyFileKind, frontend};
+use crate::{profile::Profile, util::fuzzy};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub profile_id: i64,
+    pub profile_name: String,
+    pub file: PathBuf,
+    pub file_name: String,
+    pub section: String,
+    pub entry: String,
+    pub value: String,
+    pub owner: Option<SearchHitOwner>,
+    pub score: u32,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHitOwner {
+    pub uuid: Uuid,
+    pub name: String,
+}
+
+impl Profile {
+    /// Searches the names, descriptions and values of all config entries in the profile.
+    ///
+    /// Every word in the query has to match at least one of the fields.
+    ///
+    /// Only the cached config files are searched, the caller is responsible for refreshing them.
+    pub fn search_config(&self, query: &str) -> Vec<SearchHit> {
+        let terms = query
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect_vec();
+
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        let mut hits = Vec::new();
+
+        for file in &self.config_cache.0 {
+            let data = match &file.kind {
+                AnyFileKind::BepInEx(data) => data.to_frontend(),
+                AnyFileKind::GDWeave(data) => match data.to_frontend() {
+                    Ok(data) => data,
+                    Err(_) => continue,
+                },
+                AnyFileKind::Err(_) | AnyFileKind::Unsupported => continue,
+            };
+
+            let owner = self.config_owner(file);
+            let file_name = file.display_name.to_lowercase();
+
+            for section in &data.sections {
+                let section_name = section.name.to_lowercase();
+
+                for entry in &section.entries {
+                    let value = value_to_string(&entry.value);
+
+                    let fields = [
+                        (entry.name.to_lowercase(), 3),
+                        (value.to_lowercase(), 2),
+                        (section_name.clone(), 1),
+                        (file_name.clone(), 1),
+                    ];
+
+                    let description = entry
+                        .description
+                        .as_deref()
+                        .unwrap_or_default()
+                        .to_lowercase();
+
+                    let Some(score) = score_terms(&terms, &fields, &description) else {
+                        continue;
+                    };
+
+                    hits.push(SearchHit {
+                        profile_id: self.id,
+                        profile_name: self.name.clone(),
+                        file: file.relative_path.clone(),
+                        file_name: file.display_name.clone(),
+                        section: section.name.clone(),
+                        entry: entry.name.clone(),
+                        value,
+                        owner: owner.clone(),
+                        score,
+                    });
+                }
+            }
+        }
+
+        hits
+    }
+
+    fn config_owner(&self, file: &AnyFile) -> Option<SearchHitOwner> {
+        self.mods.iter().find_map(|profile_mod| {
+            let name = profile_mod.ident().name().to_owned();
+
+            file.matches_mod(&name.to_lowercase())
+                .then(|| SearchHitOwner {
+                    uuid: profile_mod.uuid(),
+                    name,
+                })
+        })
+    }
+}
+
+fn score_terms(terms: &[String], fields: &[(String, u32)], description: &str) -> Option<u32> {
+    terms
+        .iter()
+        .map(|term| {
+            fields
+                .iter()
+                .filter_map(|(text, weight)| fuzzy::score(term, text).map(|score| score * weight))
+                .chain(description_score(term, description))
+                .max()
+        })
+        .sum()
+}
+
+/// Scores `term` appearing in an entry's description.
+///
+/// Descriptions are long enough to contain almost any term's characters in order,
+/// so only substrings and misspelled words count as a match.
+fn description_score(term: &str, description: &str) -> Option<u32> {
+    if description.contains(term) {
+        return fuzzy::score(term, description);
+    }
+
+    description
+        .split(|char: char| !char.is_alphanumeric())
+        .filter_map(|word| fuzzy::typo_score(term, word))
+        .max()
+}
+
+fn value_to_string(value: &frontend::Value) -> String {
+    use frontend::Value;
+
+    match value {
+        Value::Bool(bool) => bool.to_string(),
+        Value::String(str) => str.clone(),
+        Value::Int(num) => num.value.to_string(),
+        Value::Float(num) => num.value.to_string(),
+        Value::Enum { index, options } => options.get(*index).cloned().unwrap_or_default(),
+        Value::Flags { indicies, options } => indicies
+            .iter()
+            .filter_map(|index| options.get(*index))
+            .join(", "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DESCRIPTION: &str = "multiplier for the walking speed of enemies";
+
+    #[test]
+    fn description_substrings_and_typos() {
+        assert!(description_score("speed", DESCRIPTION).is_some());
+        assert!(description_score("enemes", DESCRIPTION).is_some());
+        assert!(description_score("speed", DESCRIPTION) > description_score("sped", DESCRIPTION));
+    }
+
+    #[test]
+    fn description_scattered_letters_dont_match() {
+        assert_eq!(description_score("mtsp", DESCRIPTION), None);
+        assert_eq!(description_score("damage", DESCRIPTION), None);
+    }
+}
@@ -0,0 +1,98 @@
+use std::{fmt::Display, path::PathBuf};
+
+use serde::Serialize;
+use thiserror::Error;
+
+use super::{AnyFileKind, ConfigCache};
+
+/// A config value that doesn't satisfy the constraints declared in its file.
+#[derive(Debug, Error, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum ValidationError {
+    #[error("{value} is out of range, expected a value from {min} to {max}")]
+    OutOfRange {
+        value: String,
+        min: String,
+        max: String,
+    },
+    #[error("'{option}' is not one of the acceptable values")]
+    UnknownOption { option: String },
+    #[error("expected a value of type {expected}")]
+    TypeMismatch { expected: String },
+}
+
+impl ValidationError {
+    pub fn check_range<T>(value: T, range: Option<&std::ops::Range<T>>) -> Result<(), Self>
+    where
+        T: PartialOrd + Display,
+    {
+        let Some(range) = range else {
+            return Ok(());
+        };
+
+        // BepInEx ranges are inclusive on both ends
+        if value >= range.start && value <= range.end {
+            return Ok(());
+        }
+
+        Err(Self::OutOfRange {
+            value: value.to_string(),
+            min: range.start.to_string(),
+            max: range.end.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationReport {
+    pub invalid_entries: Vec<InvalidEntry>,
+    pub unreadable_files: Vec<UnreadableFile>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvalidEntry {
+    pub file: PathBuf,
+    pub section: String,
+    pub entry: String,
+    pub message: String,
+    pub error: ValidationError,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnreadableFile {
+    pub file: PathBuf,
+    pub error: String,
+}
+
+impl ConfigCache {
+    /// Checks every cached entry against the constraints declared in its file.
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+
+        for file in &self.0 {
+            match &file.kind {
+                AnyFileKind::BepInEx(data) => {
+                    for (section, entry, error) in data.validate() {
+                        report.invalid_entries.push(InvalidEntry {
+                            file: file.relative_path.clone(),
+                            section: section.to_owned(),
+                            entry: entry.to_owned(),
+                            message: error.to_string(),
+                            error,
+                        });
+                    }
+                }
+                AnyFileKind::Err(err) => report.unreadable_files.push(UnreadableFile {
+                    file: file.relative_path.clone(),
+                    error: format!("{err:#}"),
+                }),
+                AnyFileKind::GDWeave(_) | AnyFileKind::Unsupported => (),
+            }
+        }
+
+        report
+    }
+}
@@ -0,0 +1,231 @@
+use std::{
+    collections::HashSet,
+    mem,
+    path::{Path, PathBuf},
+    sync::{Mutex, mpsc},
+    thread,
+    time::Duration,
+};
+
+use eyre::{Context, Result};
+use itertools::Itertools;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use tauri::AppHandle;
+use tracing::{debug, trace, warn};
+use walkdir::WalkDir;
+
+use super::frontend;
+use crate::{profile::Profile, state::ManagerExt};
+
+/// How long to wait for more events before re-reading the changed files.
+///
+/// Games usually truncate and rewrite config files in several steps,
+/// which shows up as a burst of events.
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Watches the config directories of a single profile and keeps
+/// its config cache up to date with changes made outside of Gale.
+#[derive(Default)]
+pub struct ConfigWatcher(Mutex<Option<Watched>>);
+
+struct Watched {
+    profile_id: i64,
+    profile_dir: PathBuf,
+    watcher: RecommendedWatcher,
+    /// Config directories that didn't exist the last time they were checked.
+    missing: Vec<PathBuf>,
+}
+
+impl Watched {
+    /// Starts watching the given config directories and returns the ones that exist.
+    ///
+    /// For directories that don't exist yet, their closest existing parent is watched
+    /// instead, so they can be picked up once they're created.
+    fn watch_dirs(&mut self, dirs: Vec<PathBuf>) -> Result<Vec<PathBuf>> {
+        let mut watched = Vec::new();
+
+        for dir in dirs {
+            if dir.exists() {
+                self.watcher
+                    .watch(&dir, RecursiveMode::Recursive)
+                    .with_context(|| format!("failed to watch {}", dir.display()))?;
+
+                watched.push(dir);
+                continue;
+            }
+
+            let parent = dir
+                .ancestors()
+                .skip(1)
+                .take_while(|parent| parent.starts_with(&self.profile_dir))
+                .find(|parent| parent.exists());
+
+            if let Some(parent) = parent {
+                self.watcher
+                    .watch(parent, RecursiveMode::NonRecursive)
+                    .with_context(|| format!("failed to watch {}", parent.display()))?;
+            }
+
+            self.missing.push(dir);
+        }
+
+        Ok(watched)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigChangedEvent {
+    profile_id: i64,
+    changed: Vec<frontend::File>,
+    removed: Vec<PathBuf>,
+}
+
+impl ConfigWatcher {
+    /// Starts watching the given profile, replacing the previously watched one.
+    ///
+    /// Does nothing if the profile is already being watched.
+    pub fn watch(&self, profile: &Profile, app: &AppHandle) -> Result<()> {
+        let mut watched = self.0.lock().unwrap();
+
+        if watched
+            .as_ref()
+            .is_some_and(|watched| watched.profile_id == profile.id)
+        {
+            return Ok(());
+        }
+
+        // drop the old watcher first, which also stops its event thread
+        *watched = None;
+
+        let (tx, rx) = mpsc::channel();
+
+        let watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+                Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+                    for path in event.paths {
+                        tx.send(path).ok();
+                    }
+                }
+                Ok(_) => (),
+                Err(err) => warn!("config watcher error: {:#}", err),
+            })
+            .context("failed to create config watcher")?;
+
+        let config_dirs = match profile.game.mod_loader.mod_config_dirs() {
+            [] => &["."],
+            dirs => dirs,
+        };
+
+        let mut new = Watched {
+            profile_id: profile.id,
+            profile_dir: profile.path.clone(),
+            watcher,
+            missing: Vec::new(),
+        };
+
+        new.watch_dirs(
+            config_dirs
+                .iter()
+                .map(|dir| profile.path.join(dir))
+                .collect(),
+        )?;
+
+        let profile_id = profile.id;
+        let app = app.to_owned();
+        thread::spawn(move || handle_events(rx, profile_id, app));
+
+        debug!("watching config files of profile {}", profile.name);
+
+        *watched = Some(new);
+
+        Ok(())
+    }
+
+    /// Starts watching the config directories of the given profile that have been
+    /// created since it started being watched.
+    ///
+    /// Returns the files that are already inside of them, since they
+    /// may have been written before the directories were watched.
+    fn watch_created(&self, profile_id: i64) -> Result<Vec<PathBuf>> {
+        let mut watched = self.0.lock().unwrap();
+
+        let Some(watched) = watched
+            .as_mut()
+            .filter(|watched| watched.profile_id == profile_id && !watched.missing.is_empty())
+        else {
+            return Ok(Vec::new());
+        };
+
+        let missing = mem::take(&mut watched.missing);
+        let created = watched.watch_dirs(missing)?;
+
+        Ok(created.iter().flat_map(|dir| files_in(dir)).collect())
+    }
+}
+
+fn files_in(dir: &Path) -> impl Iterator<Item = PathBuf> {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+}
+
+fn handle_events(rx: mpsc::Receiver<PathBuf>, profile_id: i64, app: AppHandle) {
+    // exits once the watcher, and with it the sender, is dropped
+    while let Ok(path) = rx.recv() {
+        let mut paths = HashSet::from([path]);
+
+        while let Ok(path) = rx.recv_timeout(DEBOUNCE) {
+            paths.insert(path);
+        }
+
+        match app.config_watcher().watch_created(profile_id) {
+            Ok(files) => paths.extend(files),
+            Err(err) => warn!("failed to watch new config directories: {:#}", err),
+        }
+
+        trace!("config paths changed: {:?}", paths);
+
+        if let Err(err) = refresh(paths, profile_id, &app) {
+            warn!("failed to refresh config files: {:#}", err);
+        }
+    }
+}
+
+fn refresh(paths: HashSet<PathBuf>, profile_id: i64, app: &AppHandle) -> Result<()> {
+    let mut manager = app.lock_manager();
+    let (_, profile) = manager.profile_by_id_mut(profile_id)?;
+
+    let changes = profile.refresh_config_paths(&paths.into_iter().collect_vec());
+
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    debug!(
+        changed = changes.changed.len(),
+        removed = changes.removed.len(),
+        "config files changed on disk"
+    );
+
+    let changed = profile
+        .config_cache
+        .to_frontend()
+        .into_iter()
+        .filter(|file| changes.changed.contains(&file.relative_path))
+        .collect();
+
+    app.emit_buffered(
+        "config_changed",
+        &ConfigChangedEvent {
+            profile_id,
+            changed,
+            removed: changes.removed,
+        },
+    );
+
+    Ok(())
+}
@@ -106,6 +106,7 @@ fn read_manager_data(prefs: &Prefs) -> Result<SaveData> {
                 sync_data: None,
                 custom_args: String::new(),
                 ignored_package_updates: None,
+                install_rules: Vec::new(),
             });
 
             if data.active_profile_index == index {
@@ -118,6 +119,7 @@ fn read_manager_data(prefs: &Prefs) -> Result<SaveData> {
             slug: game.slug.to_string(),
             favorite: data.favorite,
             active_profile_id,
+            install_rules: Vec::new(),
         });
     }
 
@@ -212,6 +214,7 @@ impl From<legacy::ThunderstoreMod> for ThunderstoreMod {
         ThunderstoreMod {
             ident: legacy.ident,
             id: legacy.id.into(),
+            rules: None,
         }
     }
 }
@@ -239,6 +242,7 @@ impl From<legacy::LocalMod> for LocalMod {
             file_size: legacy.file_size,
             readme: None,
             changelog: None,
+            source_url: None,
         }
     }
 }
@@ -1,9 +1,11 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     env, iter,
+    path::Path,
     sync::{Mutex, MutexGuard},
 };
 
+use chrono::{DateTime, Utc};
 use eyre::{Context, Result, eyre};
 use include_dir::include_dir;
 use rusqlite::{OptionalExtension, params, types::Type as SqliteType};
@@ -13,8 +15,10 @@ use tracing::{info, trace};
 use uuid::Uuid;
 
 use crate::{
+    config::history::{ConfigChange, EntryChange},
     prefs::Prefs,
     profile::{self, ManagedGame, ModManager, Profile, sync::auth::AuthCredentials},
+    thunderstore::watchlist::{PackageChange, WatchedPackage, WatchlistChange},
     util,
 };
 
@@ -54,6 +58,15 @@ pub fn init() -> Result<(Db, bool)> {
     Ok((Db(Mutex::new(conn)), existed))
 }
 
+#[cfg(test)]
+pub fn in_memory() -> Result<Db> {
+    let mut conn = rusqlite::Connection::open_in_memory().context("failed to connect")?;
+
+    run_migrations(&mut conn).context("failed to run migrations")?;
+
+    Ok(Db(Mutex::new(conn)))
+}
+
 fn trace_stmt(stmt: &str) {
     trace!("{stmt}");
 }
@@ -96,6 +109,33 @@ where
     }
 }
 
+fn map_config_change(row: &rusqlite::Row) -> rusqlite::Result<ConfigChange> {
+    Ok(ConfigChange {
+        id: row.get(0)?,
+        file: row.get::<_, String>(1)?.into(),
+        change: EntryChange {
+            section: row.get(2)?,
+            entry: row.get(3)?,
+            old_value: map_json_option_row(row, 4)?,
+            new_value: map_json_row(row, 5)?,
+        },
+        changed_at: DateTime::from_timestamp_millis(row.get(6)?).unwrap_or_default(),
+    })
+}
+
+fn map_watchlist_change(row: &rusqlite::Row) -> rusqlite::Result<WatchlistChange> {
+    Ok(WatchlistChange {
+        id: row.get(0)?,
+        change: PackageChange {
+            full_name: row.get(1)?,
+            kind: map_json_row(row, 2)?,
+            version: row.get(3)?,
+            changelog: row.get(4)?,
+        },
+        detected_at: DateTime::from_timestamp_millis(row.get(5)?).unwrap_or_default(),
+    })
+}
+
 pub struct ManagerData {
     pub id: i64,
     pub active_game_slug: Option<String>,
@@ -107,6 +147,7 @@ pub struct ManagedGameData {
     pub slug: String,
     pub favorite: bool,
     pub active_profile_id: i64,
+    pub install_rules: Vec<profile::install::rules::InstallRule>,
 }
 
 pub struct ProfileData {
@@ -120,6 +161,30 @@ pub struct ProfileData {
     pub sync_data: Option<profile::sync::SyncProfileData>,
     pub custom_args: String,
     pub ignored_package_updates: Option<HashSet<Uuid>>,
+    pub install_rules: Vec<profile::install::rules::InstallRule>,
+}
+
+pub struct CacheUsageData {
+    pub game_slug: Option<String>,
+    pub last_used: DateTime<Utc>,
+}
+
+/// A file in a cached package version, stored in the cache's object store.
+#[derive(Debug)]
+pub struct CacheObjectRef {
+    /// Path of the file relative to the version directory.
+    pub path: String,
+    /// Hex-encoded blake3 hash of the file's contents.
+    pub hash: String,
+    pub size: u64,
+}
+
+pub struct CacheObjectStats {
+    pub object_count: u64,
+    /// Combined size of every object in the store.
+    pub stored_size: u64,
+    /// Combined size of every file in the cache, as if none were deduplicated.
+    pub linked_size: u64,
 }
 
 pub struct SaveData {
@@ -198,13 +263,16 @@ impl Db {
             });
 
         let games = conn
-            .prepare("SELECT id, slug, favorite, active_profile_id FROM managed_games")?
+            .prepare(
+                "SELECT id, slug, favorite, active_profile_id, install_rules FROM managed_games",
+            )?
             .query_map((), |row| {
                 Ok(ManagedGameData {
                     id: row.get(0)?,
                     slug: row.get(1)?,
                     favorite: row.get(2)?,
                     active_profile_id: row.get(3)?,
+                    install_rules: map_json_row(row, 4)?,
                 })
             })?
             .collect::<rusqlite::Result<Vec<_>>>()
@@ -212,7 +280,7 @@ impl Db {
 
         let mut profiles = conn
             .prepare(
-                "SELECT id, name, path, game_slug, mods, modpack, ignored_updates, sync_data, custom_args, ignored_package_updates FROM profiles",
+                "SELECT id, name, path, game_slug, mods, modpack, ignored_updates, sync_data, custom_args, ignored_package_updates, install_rules FROM profiles",
             )?
             .query_map((), |row| {
                 let mut mods : Vec<profile::ProfileMod> = map_json_row(row, 4)?;
@@ -236,6 +304,7 @@ impl Db {
                     sync_data: map_json_option_row(row, 7)?,
                     custom_args,
                     ignored_package_updates: map_json_option_row(row, 9)?,
+                    install_rules: map_json_row(row, 10)?,
                 })
             })?
             .collect::<rusqlite::Result<Vec<_>>>()
@@ -274,10 +343,255 @@ impl Db {
             tx.prepare("DELETE FROM profiles WHERE id = ?")?
                 .execute([id])?;
 
+            tx.prepare("DELETE FROM config_history WHERE profile_id = ?")?
+                .execute([id])?;
+
+            Ok(())
+        })
+    }
+
+    pub fn insert_config_changes(
+        &self,
+        profile_id: i64,
+        file: &Path,
+        changes: &[EntryChange],
+    ) -> Result<()> {
+        let changed_at = Utc::now().timestamp_millis();
+
+        self.with_transaction(|tx| {
+            let mut stmt = tx.prepare(
+                "INSERT INTO config_history
+                    (profile_id, file, section, entry, old_value, new_value, changed_at)
+                    VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )?;
+
+            for change in changes {
+                stmt.execute(params![
+                    profile_id,
+                    file.to_string_lossy(),
+                    change.section,
+                    change.entry,
+                    change
+                        .old_value
+                        .as_ref()
+                        .map(serde_json::to_string)
+                        .transpose()?,
+                    serde_json::to_string(&change.new_value)?,
+                    changed_at
+                ])?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Returns the config history of a file, optionally filtered to a single entry, newest first.
+    pub fn config_history(
+        &self,
+        profile_id: i64,
+        file: &Path,
+        entry: Option<(&str, &str)>,
+        limit: usize,
+    ) -> Result<Vec<ConfigChange>> {
+        let (section, entry) = entry.unzip();
+
+        let changes = self
+            .conn()
+            .prepare(
+                "SELECT id, file, section, entry, old_value, new_value, changed_at
+                FROM config_history
+                WHERE profile_id = ?1 AND file = ?2
+                    AND (?3 IS NULL OR section = ?3)
+                    AND (?4 IS NULL OR entry = ?4)
+                ORDER BY id DESC
+                LIMIT ?5",
+            )?
+            .query_map(
+                params![profile_id, file.to_string_lossy(), section, entry, limit],
+                map_config_change,
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read config history")?;
+
+        Ok(changes)
+    }
+
+    pub fn config_change(&self, profile_id: i64, id: i64) -> Result<ConfigChange> {
+        self.conn()
+            .prepare(
+                "SELECT id, file, section, entry, old_value, new_value, changed_at
+                FROM config_history
+                WHERE profile_id = ? AND id = ?",
+            )?
+            .query_row(params![profile_id, id], map_config_change)
+            .optional()?
+            .ok_or_else(|| eyre!("config change {} not found", id))
+    }
+
+    /// Returns all changes made to a file after the given time, oldest first.
+    pub fn config_changes_since(
+        &self,
+        profile_id: i64,
+        file: &Path,
+        time: DateTime<Utc>,
+    ) -> Result<Vec<ConfigChange>> {
+        let changes = self
+            .conn()
+            .prepare(
+                "SELECT id, file, section, entry, old_value, new_value, changed_at
+                FROM config_history
+                WHERE profile_id = ? AND file = ? AND changed_at > ?
+                ORDER BY id ASC",
+            )?
+            .query_map(
+                params![profile_id, file.to_string_lossy(), time.timestamp_millis()],
+                map_config_change,
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read config history")?;
+
+        Ok(changes)
+    }
+
+    /// Returns the packages on a game's watchlist, oldest first.
+    pub fn watchlist(&self, game_slug: &str) -> Result<Vec<WatchedPackage>> {
+        let packages = self
+            .conn()
+            .prepare(
+                "SELECT full_name, last_version, is_deprecated, added_at
+                FROM watched_packages
+                WHERE game_slug = ?
+                ORDER BY added_at ASC",
+            )?
+            .query_map([game_slug], |row| {
+                Ok(WatchedPackage {
+                    full_name: row.get(0)?,
+                    last_version: row.get(1)?,
+                    is_deprecated: row.get(2)?,
+                    added_at: DateTime::from_timestamp_millis(row.get(3)?).unwrap_or_default(),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read watchlist")?;
+
+        Ok(packages)
+    }
+
+    /// Adds a package to a game's watchlist. Does nothing if it's already watched.
+    pub fn watch_package(&self, game_slug: &str, package: &WatchedPackage) -> Result<()> {
+        self.conn()
+            .prepare(
+                "INSERT OR IGNORE INTO watched_packages
+                    (game_slug, full_name, last_version, is_deprecated, added_at)
+                    VALUES (?, ?, ?, ?, ?)",
+            )?
+            .execute(params![
+                game_slug,
+                package.full_name,
+                package.last_version,
+                package.is_deprecated,
+                package.added_at.timestamp_millis()
+            ])?;
+
+        Ok(())
+    }
+
+    /// Removes a package from a game's watchlist, along with its recorded changes.
+    pub fn unwatch_package(&self, game_slug: &str, full_name: &str) -> Result<()> {
+        self.with_transaction(|tx| {
+            tx.execute(
+                "DELETE FROM watched_packages WHERE game_slug = ? AND full_name = ?",
+                params![game_slug, full_name],
+            )?;
+
+            tx.execute(
+                "DELETE FROM watchlist_changes WHERE game_slug = ? AND full_name = ?",
+                params![game_slug, full_name],
+            )?;
+
+            Ok(())
+        })
+    }
+
+    /// Updates the last seen state of watched packages.
+    pub fn update_watched_packages(
+        &self,
+        game_slug: &str,
+        packages: &[WatchedPackage],
+    ) -> Result<()> {
+        self.with_transaction(|tx| {
+            let mut stmt = tx.prepare(
+                "UPDATE watched_packages SET last_version = ?, is_deprecated = ?
+                WHERE game_slug = ? AND full_name = ?",
+            )?;
+
+            for package in packages {
+                stmt.execute(params![
+                    package.last_version,
+                    package.is_deprecated,
+                    game_slug,
+                    package.full_name
+                ])?;
+            }
+
+            Ok(())
+        })
+    }
+
+    pub fn insert_watchlist_changes(
+        &self,
+        game_slug: &str,
+        changes: &[PackageChange],
+    ) -> Result<()> {
+        let detected_at = Utc::now().timestamp_millis();
+
+        self.with_transaction(|tx| {
+            let mut stmt = tx.prepare(
+                "INSERT INTO watchlist_changes
+                    (game_slug, full_name, kind, version, changelog, detected_at)
+                    VALUES (?, ?, ?, ?, ?, ?)",
+            )?;
+
+            for change in changes {
+                stmt.execute(params![
+                    game_slug,
+                    change.full_name,
+                    serde_json::to_string(&change.kind)?,
+                    change.version,
+                    change.changelog,
+                    detected_at
+                ])?;
+            }
+
             Ok(())
         })
     }
 
+    /// Returns the watchlist changes of a game that haven't been marked as seen, oldest first.
+    pub fn unseen_watchlist_changes(&self, game_slug: &str) -> Result<Vec<WatchlistChange>> {
+        let changes = self
+            .conn()
+            .prepare(
+                "SELECT id, full_name, kind, version, changelog, detected_at
+                FROM watchlist_changes
+                WHERE game_slug = ? AND seen = 0
+                ORDER BY id ASC",
+            )?
+            .query_map([game_slug], map_watchlist_change)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read watchlist changes")?;
+
+        Ok(changes)
+    }
+
+    pub fn mark_watchlist_seen(&self, game_slug: &str) -> Result<()> {
+        self.conn()
+            .prepare("UPDATE watchlist_changes SET seen = 1 WHERE game_slug = ? AND seen = 0")?
+            .execute([game_slug])?;
+
+        Ok(())
+    }
+
     pub fn save_all(&self, manager: &ModManager) -> Result<()> {
         self.with_transaction(|tx| {
             self._save_manager(tx, manager)?;
@@ -316,16 +630,19 @@ impl Db {
         games: impl Iterator<Item = &'a ManagedGame>,
     ) -> Result<()> {
         let mut stmt = tx.prepare(
-            "INSERT OR REPLACE INTO managed_games (id, slug, favorite, active_profile_id)
-                VALUES (?, ?, ?, ?)",
+            "INSERT OR REPLACE INTO managed_games (id, slug, favorite, active_profile_id, install_rules)
+                VALUES (?, ?, ?, ?, ?)",
         )?;
 
         for game in games {
+            let install_rules = serde_json::to_string(&game.install_rules)?;
+
             stmt.execute(params![
                 game.id,
                 game.game.slug,
                 game.favorite,
-                game.active_profile_id
+                game.active_profile_id,
+                install_rules
             ])?;
         }
 
@@ -343,8 +660,8 @@ impl Db {
     ) -> Result<()> {
         let mut stmt = tx.prepare(
             "INSERT OR REPLACE INTO profiles 
-                (id, name, path, game_slug, mods, modpack, ignored_updates, sync_data, custom_args, ignored_package_updates) 
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                (id, name, path, game_slug, mods, modpack, ignored_updates, sync_data, custom_args, ignored_package_updates, install_rules) 
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )?;
 
         for profile in profiles {
@@ -361,6 +678,7 @@ impl Db {
                 .map(serde_json::to_string)
                 .transpose()?;
             let ignored_package_updates = serde_json::to_string(&profile.ignored_package_updates)?;
+            let install_rules = serde_json::to_string(&profile.install_rules.profile)?;
 
             stmt.execute(params![
                 profile.id,
@@ -372,13 +690,226 @@ impl Db {
                 ignored_updates,
                 sync_data,
                 profile.custom_args,
-                ignored_package_updates
+                ignored_package_updates,
+                install_rules
             ])?;
         }
 
         Ok(())
     }
 
+    /// Marks a cached package version as used now.
+    pub fn touch_cache_entry(&self, full_name: &str, version: &str, game_slug: &str) -> Result<()> {
+        self.conn()
+            .prepare(
+                "INSERT OR REPLACE INTO cache_usage (full_name, version, game_slug, last_used)
+                VALUES (?, ?, ?, ?)",
+            )?
+            .execute(params![
+                full_name,
+                version,
+                game_slug,
+                Utc::now().timestamp_millis()
+            ])?;
+
+        Ok(())
+    }
+
+    /// Returns the game and last use time of every cached package version
+    /// that has been used since the usage was first tracked.
+    pub fn cache_usage(&self) -> Result<HashMap<(String, String), CacheUsageData>> {
+        let usage = self
+            .conn()
+            .prepare("SELECT full_name, version, game_slug, last_used FROM cache_usage")?
+            .query_map((), |row| {
+                let key = (row.get(0)?, row.get(1)?);
+                let data = CacheUsageData {
+                    game_slug: row.get(2)?,
+                    last_used: DateTime::from_timestamp_millis(row.get(3)?).unwrap_or_default(),
+                };
+
+                Ok((key, data))
+            })?
+            .collect::<rusqlite::Result<_>>()
+            .context("failed to read cache usage")?;
+
+        Ok(usage)
+    }
+
+    pub fn delete_cache_usage<'a>(
+        &self,
+        entries: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<()> {
+        self.with_transaction(|tx| {
+            let mut stmt =
+                tx.prepare("DELETE FROM cache_usage WHERE full_name = ? AND version = ?")?;
+
+            for (full_name, version) in entries {
+                stmt.execute(params![full_name, version])?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Replaces the files recorded for a cached package version.
+    pub fn set_cache_object_refs(
+        &self,
+        full_name: &str,
+        version: &str,
+        refs: &[CacheObjectRef],
+    ) -> Result<()> {
+        self.with_transaction(|tx| {
+            tx.prepare("DELETE FROM cache_object_refs WHERE full_name = ? AND version = ?")?
+                .execute(params![full_name, version])?;
+
+            let mut insert_object =
+                tx.prepare("INSERT OR IGNORE INTO cache_objects (hash, size) VALUES (?, ?)")?;
+            let mut insert_ref = tx.prepare(
+                "INSERT OR REPLACE INTO cache_object_refs (full_name, version, path, hash)
+                VALUES (?, ?, ?, ?)",
+            )?;
+
+            for object_ref in refs {
+                insert_object.execute(params![object_ref.hash, object_ref.size])?;
+                insert_ref.execute(params![
+                    full_name,
+                    version,
+                    object_ref.path,
+                    object_ref.hash
+                ])?;
+            }
+
+            Ok(())
+        })
+    }
+
+    pub fn delete_cache_object_refs<'a>(
+        &self,
+        entries: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<()> {
+        self.with_transaction(|tx| {
+            let mut stmt =
+                tx.prepare("DELETE FROM cache_object_refs WHERE full_name = ? AND version = ?")?;
+
+            for (full_name, version) in entries {
+                stmt.execute(params![full_name, version])?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Returns every package version that has its files recorded in the object store.
+    pub fn cache_object_versions(&self) -> Result<HashSet<(String, String)>> {
+        let versions = self
+            .conn()
+            .prepare("SELECT DISTINCT full_name, version FROM cache_object_refs")?
+            .query_map((), |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()
+            .context("failed to read cached versions")?;
+
+        Ok(versions)
+    }
+
+    /// Returns the files of every cached package version that's in the object store.
+    pub fn cache_object_refs(&self) -> Result<HashMap<(String, String), Vec<CacheObjectRef>>> {
+        let mut versions: HashMap<_, Vec<_>> = HashMap::new();
+
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT r.full_name, r.version, r.path, r.hash, o.size FROM cache_object_refs r
+            JOIN cache_objects o ON o.hash = r.hash",
+        )?;
+        let rows = stmt.query_map((), |row| {
+            let key = (row.get(0)?, row.get(1)?);
+            let object_ref = CacheObjectRef {
+                path: row.get(2)?,
+                hash: row.get(3)?,
+                size: row.get(4)?,
+            };
+
+            Ok((key, object_ref))
+        })?;
+
+        for row in rows {
+            let (key, object) = row.context("failed to read cache objects")?;
+            versions.entry(key).or_default().push(object);
+        }
+
+        Ok(versions)
+    }
+
+    /// Returns the hash of every object in the object store.
+    pub fn cache_object_hashes(&self) -> Result<HashSet<String>> {
+        let hashes = self
+            .conn()
+            .prepare("SELECT hash FROM cache_objects")?
+            .query_map((), |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()
+            .context("failed to read cache objects")?;
+
+        Ok(hashes)
+    }
+
+    /// Deletes and returns the hashes of objects that no cached version refers to.
+    pub fn take_unreferenced_cache_objects(&self) -> Result<Vec<String>> {
+        let mut hashes = Vec::new();
+
+        self.with_transaction(|tx| {
+            hashes = tx
+                .prepare(
+                    "SELECT hash FROM cache_objects
+                    WHERE hash NOT IN (SELECT hash FROM cache_object_refs)",
+                )?
+                .query_map((), |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+
+            let mut stmt = tx.prepare("DELETE FROM cache_objects WHERE hash = ?")?;
+            for hash in &hashes {
+                stmt.execute([hash])?;
+            }
+
+            Ok(())
+        })?;
+
+        Ok(hashes)
+    }
+
+    pub fn cache_object_stats(&self) -> Result<CacheObjectStats> {
+        let conn = self.conn();
+
+        let (object_count, stored_size) = conn
+            .prepare("SELECT COUNT(*), COALESCE(SUM(size), 0) FROM cache_objects")?
+            .query_row((), |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        let linked_size = conn
+            .prepare(
+                "SELECT COALESCE(SUM(o.size), 0) FROM cache_object_refs r
+                JOIN cache_objects o ON o.hash = r.hash",
+            )?
+            .query_row((), |row| row.get(0))?;
+
+        Ok(CacheObjectStats {
+            object_count,
+            stored_size,
+            linked_size,
+        })
+    }
+
+    /// Forgets all usage and object data about the cache, for when it's cleared entirely.
+    pub fn clear_cache_data(&self) -> Result<()> {
+        self.with_transaction(|tx| {
+            tx.execute_batch(
+                "DELETE FROM cache_usage;
+                DELETE FROM cache_object_refs;
+                DELETE FROM cache_objects;",
+            )?;
+
+            Ok(())
+        })
+    }
+
     pub fn save_prefs(&self, prefs: &Prefs) -> Result<()> {
         self.with_transaction(|tx| {
             let json = serde_json::to_string(prefs).context("failed to serialize to json")?;
@@ -105,7 +105,11 @@ impl ModLoader<'_> {
 }
 
 impl ModLoader<'static> {
-    pub fn installer_for(&'static self, package_name: &str) -> Box<dyn PackageInstaller> {
+    pub fn installer_for<'a>(
+        &'static self,
+        package_name: &str,
+        rules: &'a rules::InstallRules,
+    ) -> Box<dyn PackageInstaller + 'a> {
         match (self.is_loader_package(package_name), &self.kind) {
             (true, ModLoaderKind::BepInEx { .. }) => Box::new(BepinexInstaller),
             (false, ModLoaderKind::BepInEx { extra_subdirs, .. }) => {
@@ -119,6 +123,7 @@ impl ModLoader<'static> {
 
                 Box::new(
                     SubdirInstaller::new(SUBDIRS)
+                        .with_rules(rules)
                         .with_default(0)
                         .with_extras(extra_subdirs),
                 )
@@ -137,6 +142,7 @@ impl ModLoader<'static> {
 
                 Box::new(
                     SubdirInstaller::new(SUBDIRS)
+                        .with_rules(rules)
                         .with_default(1)
                         .with_extras(extra_subdirs),
                 )
@@ -168,6 +174,7 @@ impl ModLoader<'static> {
 
                 Box::new(
                     SubdirInstaller::new(SUBDIRS)
+                        .with_rules(rules)
                         .with_default(2)
                         .with_extras(extra_subdirs)
                         .with_ignored_files(IGNORED),
@@ -200,7 +207,11 @@ impl ModLoader<'static> {
                 const SUBDIRS: &[Subdir] = &[Subdir::tracked("mods", "R2Northstar/mods")];
                 const IGNORED: &[&str] = &["manifest.json", "icon.png", "README.md", "LICENSE"];
 
-                Box::new(SubdirInstaller::new(SUBDIRS).with_ignored_files(IGNORED))
+                Box::new(
+                    SubdirInstaller::new(SUBDIRS)
+                        .with_rules(rules)
+                        .with_ignored_files(IGNORED),
+                )
             }
 
             (true, ModLoaderKind::Shimloader {}) => Box::new(ShimloaderInstaller),
@@ -212,7 +223,11 @@ impl ModLoader<'static> {
                     Subdir::untracked("cfg", "shimloader/cfg").mutable(),
                 ];
 
-                Box::new(SubdirInstaller::new(SUBDIRS).with_default(0))
+                Box::new(
+                    SubdirInstaller::new(SUBDIRS)
+                        .with_rules(rules)
+                        .with_default(0),
+                )
             }
 
             (true, ModLoaderKind::ReturnOfModding { files }) => {
@@ -225,7 +240,11 @@ impl ModLoader<'static> {
                     Subdir::separated("config", "ReturnOfModding/config").mutable(),
                 ];
 
-                Box::new(SubdirInstaller::new(SUBDIRS).with_default(0))
+                Box::new(
+                    SubdirInstaller::new(SUBDIRS)
+                        .with_rules(rules)
+                        .with_default(0),
+                )
             }
 
             (true, ModLoaderKind::Lovely {}) => {
@@ -236,7 +255,11 @@ impl ModLoader<'static> {
             (false, ModLoaderKind::Lovely {}) => {
                 const SUBDIRS: &[Subdir] = &[Subdir::separated("", "mods")];
 
-                Box::new(SubdirInstaller::new(SUBDIRS).with_default(0))
+                Box::new(
+                    SubdirInstaller::new(SUBDIRS)
+                        .with_rules(rules)
+                        .with_default(0),
+                )
             }
         }
     }
@@ -19,6 +19,10 @@ mod state;
 mod thunderstore;
 mod util;
 
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub use thunderstore::bench;
+
 fn setup(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
     info!(
         version = env!("CARGO_PKG_VERSION"),
@@ -112,6 +116,9 @@ pub fn run() {
             logger::log_err,
             state::is_first_run,
             thunderstore::commands::query_thunderstore,
+            thunderstore::commands::check_mod_query,
+            thunderstore::commands::get_package_dependants,
+            thunderstore::commands::get_fetch_stats,
             thunderstore::commands::stop_querying_thunderstore,
             thunderstore::commands::get_markdown,
             thunderstore::commands::set_api_token,
@@ -119,6 +126,14 @@ pub fn run() {
             thunderstore::commands::clear_api_token,
             thunderstore::commands::trigger_mod_fetch,
             thunderstore::commands::get_categories,
+            thunderstore::commands::get_repositories,
+            thunderstore::commands::set_repository,
+            thunderstore::commands::remove_repository,
+            thunderstore::commands::get_watchlist,
+            thunderstore::commands::watch_package,
+            thunderstore::commands::unwatch_package,
+            thunderstore::commands::get_watchlist_changes,
+            thunderstore::commands::mark_watchlist_seen,
             prefs::commands::get_prefs,
             prefs::commands::set_prefs,
             prefs::commands::zoom_window,
@@ -152,6 +167,7 @@ pub fn run() {
             profile::commands::forget_profile,
             profile::commands::toggle_hidden_mod,
             profile::commands::get_hidden_mods,
+            profile::commands::check_profile_health,
             profile::launch::commands::launch_game,
             profile::launch::commands::get_launch_args,
             profile::launch::commands::open_game_dir,
@@ -162,17 +178,33 @@ pub fn run() {
             profile::install::commands::has_pending_installations,
             profile::install::commands::is_installing,
             profile::install::commands::clear_download_cache,
+            profile::install::commands::get_cache_usage,
+            profile::install::commands::get_cache_store_stats,
+            profile::install::commands::get_install_rules,
+            profile::install::commands::set_game_install_rules,
+            profile::install::commands::set_profile_install_rules,
+            profile::install::commands::reinstall_affected_mods,
+            profile::install::commands::plan_install,
+            profile::install::commands::verify_cache,
+            profile::install::commands::verify_profile,
+            profile::install::commands::repair_profile,
             profile::install::commands::get_download_size,
             profile::update::commands::change_mod_version,
             profile::update::commands::update_mods,
             profile::update::commands::ignore_update,
             profile::update::commands::ignore_package_updates,
+            profile::update::commands::get_update_changelog,
+            profile::update::commands::review_updates,
             profile::import::commands::import_profile,
+            profile::import::commands::plan_import_profile,
             profile::import::commands::read_profile_code,
             profile::import::commands::read_profile_file,
             profile::import::commands::read_profile_base64,
             profile::import::commands::import_local_mod,
             profile::import::commands::import_local_mod_base64,
+            profile::import::commands::import_remote_mod,
+            profile::import::commands::link_local_mod,
+            profile::import::commands::sync_linked_mod,
             profile::import::commands::get_r2modman_info,
             profile::import::commands::import_r2modman,
             profile::export::commands::export_code,
@@ -192,17 +224,25 @@ pub fn run() {
             profile::sync::commands::push_sync_profile,
             profile::sync::commands::clone_sync_profile,
             profile::sync::commands::pull_sync_profile,
+            profile::sync::commands::plan_pull_sync_profile,
             profile::sync::commands::fetch_sync_profile,
             profile::sync::commands::get_owned_sync_profiles,
             profile::sync::commands::login,
             profile::sync::commands::logout,
             profile::sync::commands::get_user,
             config::commands::get_config_files,
+            config::commands::search_config,
+            config::commands::validate_profile_config,
             config::commands::set_config_entry,
             config::commands::reset_config_entry,
             config::commands::reset_config_file,
+            config::commands::get_config_history,
+            config::commands::undo_config_change,
+            config::commands::revert_config_file,
             config::commands::open_config_file,
             config::commands::delete_config_file,
+            config::commands::find_orphaned_config,
+            config::commands::resolve_orphaned_config,
         ])
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_deep_link::init())
@@ -14,9 +14,9 @@ use crate::{
     db::{self, Db},
     game::{self, Game, platform::Platform},
     logger,
-    profile::launch::LaunchMode,
+    profile::{self, install::FileInstallMethod, launch::LaunchMode},
     state::ManagerExt,
-    thunderstore::Backend,
+    thunderstore::{self, Backend, CustomRepository},
     util::{
         self,
         error::IoResultExt,
@@ -189,6 +189,12 @@ pub struct Prefs {
     pub pull_before_launch: bool,
     pub language: String,
     pub backend_skip_confirm: bool,
+    /// Maximum size of the download cache in bytes, `None` for no limit.
+    pub cache_size_limit: Option<u64>,
+    /// How files that don't need to be modified are installed from the cache.
+    pub install_method: FileInstallMethod,
+    /// Package repositories added by the user, which are used alongside the built-in backends.
+    pub repositories: Vec<CustomRepository>,
 
     pub game_prefs: HashMap<String, GamePrefs>,
 }
@@ -217,6 +223,7 @@ impl From<Backend> for Backends {
         match value {
             Backend::Thunderstore => Backends::Thunderstore,
             Backend::Hexium => Backends::Hexium,
+            Backend::Custom(_) => Backends::All,
         }
     }
 }
@@ -252,6 +259,9 @@ impl Default for Prefs {
             zoom_factor: 1.0,
             language: "en".to_string(),
             backend_skip_confirm: false,
+            cache_size_limit: None,
+            install_method: FileInstallMethod::default(),
+            repositories: Vec::new(),
 
             game_prefs: HashMap::new(),
         }
@@ -271,6 +281,8 @@ impl Prefs {
         let window = app.get_webview_window("main").unwrap();
         window.zoom(self.zoom_factor as f64).ok();
 
+        thunderstore::set_repositories(&self.repositories);
+
         self.save(db)?;
 
         Ok(())
@@ -321,6 +333,12 @@ impl Prefs {
         self.fetch_mods_automatically = value.fetch_mods_automatically;
         self.pull_before_launch = value.pull_before_launch;
         self.backend_skip_confirm = value.backend_skip_confirm;
+        self.install_method = value.install_method;
+
+        if self.cache_size_limit != value.cache_size_limit {
+            self.cache_size_limit = value.cache_size_limit;
+            profile::install::cache::gc::spawn_eviction(app);
+        }
 
         self.save(app.db()).context("failed save prefs")
     }
@@ -362,19 +380,60 @@ impl Prefs {
         Ok(())
     }
 
+    /// Adds `repository`, or replaces the one with the same id.
+    pub fn set_repository(&mut self, mut repository: CustomRepository, db: &Db) -> Result<()> {
+        repository.validate()?;
+
+        match self
+            .repositories
+            .iter_mut()
+            .find(|existing| existing.id == repository.id)
+        {
+            Some(existing) => *existing = repository,
+            None => self.repositories.push(repository),
+        }
+
+        thunderstore::set_repositories(&self.repositories);
+        self.save(db)
+    }
+
+    pub fn remove_repository(&mut self, id: &str, db: &Db) -> Result<()> {
+        let len = self.repositories.len();
+        self.repositories.retain(|repository| repository.id != id);
+        ensure!(
+            self.repositories.len() < len,
+            "repository '{}' not found",
+            id
+        );
+
+        thunderstore::set_repositories(&self.repositories);
+        self.save(db)
+    }
+
     pub fn cache_dir(&self) -> PathBuf {
         self.data_dir.join("cache")
     }
 
-    pub fn enabled_backends(&self, game: Game) -> Backends {
-        match game.backends.as_slice() {
+    /// The backends to fetch packages for `game` from, including custom repositories.
+    pub fn enabled_backends(&self, game: Game) -> Vec<Backend> {
+        let built_in = match game.backends.as_slice() {
             [backend] => (*backend).into(),
             _ => self
                 .game_prefs
                 .get(&*game.slug)
                 .map(|p| p.backend)
                 .unwrap_or_default(),
-        }
+        };
+
+        built_in
+            .iter()
+            .chain(
+                self.repositories
+                    .iter()
+                    .filter(|repository| repository.serves(game))
+                    .map(CustomRepository::backend),
+            )
+            .collect()
     }
 }
 
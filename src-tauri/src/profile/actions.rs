@@ -11,7 +11,10 @@ use tauri::{AppHandle, Listener};
 use tracing::{debug, info, trace};
 use uuid::Uuid;
 
-use super::{Dependant, ManagedGame, Profile, ProfileMod, import, install::PackageInstaller};
+use super::{
+    Dependant, ManagedGame, Profile, ProfileMod, import,
+    install::{PackageInstaller, rules::InstallRules},
+};
 use crate::{
     config::ConfigCache,
     db::Db,
@@ -207,8 +210,26 @@ impl Profile {
         }
     }
 
-    fn installer_for(&self, profile_mod: &ProfileMod) -> Box<dyn PackageInstaller> {
-        self.game.mod_loader.installer_for(&profile_mod.full_name())
+    /// Creates an installer for one of the profile's mods.
+    ///
+    /// Thunderstore mods follow the install rules they were installed with,
+    /// so their files are found even if the profile's rules have changed since.
+    pub(super) fn installer_for<'a>(
+        &'a self,
+        profile_mod: &'a ProfileMod,
+    ) -> Box<dyn PackageInstaller + 'a> {
+        static NO_RULES: InstallRules = InstallRules {
+            game: Vec::new(),
+            profile: Vec::new(),
+        };
+
+        match profile_mod.as_thunderstore() {
+            Some((ts_mod, _)) => self.game.mod_loader.installer_for(
+                ts_mod.ident.full_name(),
+                ts_mod.rules.as_ref().unwrap_or(&NO_RULES),
+            ),
+            None => self.package_installer(&profile_mod.full_name()),
+        }
     }
 
     fn reorder_mod(&mut self, uuid: Uuid, delta: i32) -> Result<()> {
@@ -330,6 +351,10 @@ impl ManagedGame {
             custom_args: String::new(),
             missing: false,
             ignored_package_updates: HashSet::new(),
+            install_rules: InstallRules {
+                game: self.install_rules.clone(),
+                profile: Vec::new(),
+            },
         };
 
         let index = self.target_profile_index(&profile.name);
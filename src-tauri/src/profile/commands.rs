@@ -8,7 +8,7 @@ use tauri::{AppHandle, command};
 use tracing::warn;
 use uuid::Uuid;
 
-use super::{Dependant, Profile, actions::ActionResult};
+use super::{Dependant, Profile, actions::ActionResult, health::HealthReport};
 use crate::{
     game::{self, Game, platform::Platform},
     profile::FrontendManagedGame,
@@ -226,13 +226,27 @@ pub fn create_profile(name: String, override_path: Option<PathBuf>, app: AppHand
 
 #[command]
 pub fn delete_profile(id: i64, app: AppHandle) -> Result<()> {
-    let mut manager = app.lock_manager();
-    let game = manager.active_game_mut();
+    let has_linked = {
+        let mut manager = app.lock_manager();
+        let game = manager.active_game_mut();
 
-    game.delete_profile(id, false, app.db())?;
-    game.save(&app)?;
+        let has_linked = game
+            .profile(id)?
+            .mods
+            .iter()
+            .any(|profile_mod| profile_mod.kind.as_linked().is_some());
 
-    game.update_window_title(&app)?;
+        game.delete_profile(id, false, app.db())?;
+        game.save(&app)?;
+
+        game.update_window_title(&app)?;
+
+        has_linked
+    };
+
+    if has_linked {
+        app.linked_mod_watcher().refresh(&app)?;
+    }
 
     Ok(())
 }
@@ -269,19 +283,37 @@ pub fn duplicate_profile(name: String, app: AppHandle) -> Result<()> {
 
 #[command]
 pub fn remove_mod(uuid: Uuid, app: AppHandle) -> Result<ActionResult> {
-    mod_action_command(app, |profile, thunderstore| {
+    let has_linked = has_linked(app.lock_manager().active_profile(), &[uuid]);
+
+    let response = mod_action_command(&app, |profile, thunderstore| {
         profile.remove_mod(uuid, thunderstore)
-    })
+    })?;
+
+    if has_linked && matches!(response, ActionResult::Done) {
+        app.linked_mod_watcher().refresh(&app)?;
+    }
+
+    Ok(response)
 }
 
 #[command]
 pub fn toggle_mod(uuid: Uuid, app: AppHandle) -> Result<ActionResult> {
-    mod_action_command(app, |profile, thunderstore| {
+    mod_action_command(&app, |profile, thunderstore| {
         profile.toggle_mod(uuid, thunderstore)
     })
 }
 
-fn mod_action_command<F>(app: AppHandle, action: F) -> Result<ActionResult>
+/// Whether any of the given mods is linked, in which case the
+/// linked mod watcher needs a refresh after removing them.
+fn has_linked(profile: &Profile, uuids: &[Uuid]) -> bool {
+    uuids.iter().any(|uuid| {
+        profile
+            .get_mod(*uuid)
+            .is_ok_and(|profile_mod| profile_mod.kind.as_linked().is_some())
+    })
+}
+
+fn mod_action_command<F>(app: &AppHandle, action: F) -> Result<ActionResult>
 where
     F: FnOnce(&mut Profile, &Thunderstore) -> eyre::Result<ActionResult>,
 {
@@ -292,7 +324,7 @@ where
     let response = action(profile, &thunderstore)?;
 
     if let ActionResult::Done = response {
-        profile.save(&app, true)?;
+        profile.save(app, true)?;
     }
 
     Ok(response)
@@ -300,14 +332,22 @@ where
 
 #[command]
 pub fn force_remove_mods(uuids: Vec<Uuid>, app: AppHandle) -> Result<()> {
-    let mut manager = app.lock_manager();
+    let has_linked = has_linked(app.lock_manager().active_profile(), &uuids);
 
-    let profile = manager.active_profile_mut();
-    for package_uuid in uuids {
-        profile.force_remove_mod(package_uuid)?;
+    {
+        let mut manager = app.lock_manager();
+
+        let profile = manager.active_profile_mut();
+        for package_uuid in uuids {
+            profile.force_remove_mod(package_uuid)?;
+        }
+
+        profile.save(&app, true)?;
     }
 
-    profile.save(&app, true)?;
+    if has_linked {
+        app.linked_mod_watcher().refresh(&app)?;
+    }
 
     Ok(())
 }
@@ -348,12 +388,18 @@ pub fn remove_disabled_mods(app: AppHandle) -> Result<usize> {
         .collect_vec();
 
     let len = uuids.len();
+    let has_linked = has_linked(profile, &uuids);
 
     for uuid in uuids {
         profile.force_remove_mod(uuid)?;
     }
 
     profile.save(&app, true)?;
+    drop(manager);
+
+    if has_linked {
+        app.linked_mod_watcher().refresh(&app)?;
+    }
 
     Ok(len)
 }
@@ -547,3 +593,11 @@ pub fn get_hidden_mods(app: AppHandle) -> Result<Vec<Dependant>> {
 
     Ok(hidden_mods)
 }
+
+/// Checks the active profile for deprecated, removed and delisted mods.
+#[command]
+pub async fn check_profile_health(app: AppHandle) -> Result<HealthReport> {
+    let report = super::health::check(&app).await?;
+
+    Ok(report)
+}
@@ -266,8 +266,12 @@ fn generate_diff(old: &[BorrowedMod<'_>], new: &[BorrowedMod<'_>], game: Game) -
     changelog
 }
 
-fn markdown_link(url: impl Display, text: impl Display) -> String {
-    format!("[{text}]({url})")
+/// Links to `url`, or just writes `text` if the url couldn't be built.
+fn markdown_link(url: Result<String>, text: impl Display) -> String {
+    match url {
+        Ok(url) => format!("[{text}]({url})"),
+        Err(_) => text.to_string(),
+    }
 }
 
 fn package_link(package: &PackageListing, game: Game) -> String {
@@ -202,6 +202,7 @@ pub fn copy_debug_info(app: AppHandle) -> Result<()> {
                 let ty = match &profile_mod.kind {
                     ProfileModKind::Thunderstore(_) => "thunderstore",
                     ProfileModKind::Local(_) => "local",
+                    ProfileModKind::Linked(_) => "linked",
                 };
 
                 format!("{} [{}]", profile_mod.ident(), ty)
@@ -63,7 +63,7 @@ impl R2Mod {
         // Prefer backend, otherwise fallback to generic lookup
         let borrowed_mod = thunderstore
             .backend(self.source)
-            .find_ident(&self.version_ident())
+            .and_then(|backend| backend.find_ident(&self.version_ident()))
             .or_else(|_| thunderstore.find_ident(&self.version_ident()))?;
 
         Ok(ModInstall::new(borrowed_mod).with_state(self.enabled))
@@ -158,11 +158,9 @@ async fn export_code(app: &AppHandle) -> Result<ExportCode> {
         let mut base64 = String::from(PROFILE_DATA_PREFIX);
         base64.push_str(&BASE64_STANDARD.encode(data.get_ref()));
 
-        let backend = if profile.has_hexium_exclusive_mods(&app.lock_thunderstore()) {
-            Backend::Hexium
-        } else {
-            Backend::Thunderstore
-        };
+        let backend = profile
+            .exclusive_backend(&app.lock_thunderstore())
+            .unwrap_or(Backend::Thunderstore);
 
         (backend, base64)
     };
@@ -173,7 +171,7 @@ async fn export_code(app: &AppHandle) -> Result<ExportCode> {
 
     let response = app
         .http()
-        .post(backend.profile_export())
+        .post(backend.profile_export()?)
         .header("Content-Type", "application/octet-stream")
         .body(base64)
         .send()
@@ -227,7 +225,7 @@ pub(super) fn find_config<'a>(
 
     static EXCLUDE_SET: LazyLock<GlobSet> = LazyLock::new(|| {
         GlobSetBuilder::new()
-            .add(Glob::new("{dotnet,_state,snapshots,MelonLoader}/*").unwrap())
+            .add(Glob::new("{dotnet,_state,snapshots,config_archive,MelonLoader}/*").unwrap())
             .add(Glob::new("GDWeave/{GDWeave.log,core/*,mods/*}").unwrap())
             .add(Glob::new("mods.yml").unwrap())
             .add(
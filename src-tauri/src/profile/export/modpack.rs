@@ -172,10 +172,10 @@ fn base_request(
     tail: impl Display,
     backend: Backend,
     token: impl Display,
-) -> reqwest_middleware::RequestBuilder {
-    let url = format!("{}/{tail}/", backend.modpack_upload_base_url());
+) -> Result<reqwest_middleware::RequestBuilder> {
+    let url = format!("{}/{tail}/", backend.modpack_upload_base_url()?);
 
-    app.http().post(url).bearer_auth(token)
+    Ok(app.http().post(url).bearer_auth(token))
 }
 
 pub async fn publish(
@@ -250,7 +250,7 @@ async fn initiate_upload(
         name, size
     );
 
-    let response = base_request(app, "usermedia/initiate-upload", backend, token)
+    let response = base_request(app, "usermedia/initiate-upload", backend, token)?
         .json(&UserMediaInitiateUploadParams {
             filename: name,
             file_size_bytes: size,
@@ -303,7 +303,7 @@ async fn abort_upload(app: &AppHandle, uuid: &Uuid, backend: Backend, token: &st
         format!("usermedia/{uuid}/abort-upload"),
         backend,
         token,
-    )
+    )?
     .json(&uuid)
     .send()
     .await?
@@ -326,7 +326,7 @@ async fn finish_upload(
         format!("usermedia/{uuid}/finish-upload"),
         backend,
         token,
-    )
+    )?
     .json(&UserMediaFinishUploadParams { parts })
     .send()
     .await?
@@ -353,7 +353,7 @@ async fn submit_package(
 
     debug!("submitting package");
 
-    let response = base_request(app, "submission/submit", args.backend, token)
+    let response = base_request(app, "submission/submit", args.backend, token)?
         .json(&metadata)
         .send()
         .await?;
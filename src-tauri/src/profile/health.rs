@@ -0,0 +1,322 @@
+//! Finds installed mods that are deprecated or no longer available, which would
+//! otherwise be skipped silently wherever the mod index is used.
+
+use std::collections::HashSet;
+
+use eyre::Result;
+use futures_util::future;
+use serde::Serialize;
+use tauri::AppHandle;
+use tracing::warn;
+use uuid::Uuid;
+
+use super::Profile;
+use crate::{
+    state::ManagerExt,
+    thunderstore::{
+        self, BorrowedMod, ModId, PackageListing, Thunderstore,
+        cache::{self, MarkdownKind},
+    },
+};
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HealthIssueKind {
+    /// The package is marked as deprecated by its author.
+    Deprecated,
+    /// The package doesn't exist in its repository anymore.
+    Removed,
+    /// The installed version was removed from the package, or made inactive.
+    VersionDelisted,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReplacementReason {
+    /// The package's readme links to the replacement.
+    LinkedInReadme,
+    /// The package's description mentions the replacement.
+    MentionedInDescription,
+    /// A package with the same name is still available, possibly in another repository.
+    SameName,
+    /// The latest available version of the same package.
+    LatestVersion,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Replacement {
+    pub full_name: String,
+    pub id: ModId,
+    pub reason: ReplacementReason,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthIssue {
+    pub uuid: Uuid,
+    pub full_name: String,
+    pub version: String,
+    pub kind: HealthIssueKind,
+    /// Packages that could be installed instead, most likely first.
+    pub replacements: Vec<Replacement>,
+}
+
+impl HealthIssue {
+    fn add_replacements<'a>(
+        &mut self,
+        names: impl IntoIterator<Item = &'a str>,
+        reason: ReplacementReason,
+        thunderstore: &Thunderstore,
+    ) {
+        for name in names {
+            if self
+                .replacements
+                .iter()
+                .any(|replacement| replacement.full_name == name)
+            {
+                continue;
+            }
+
+            // this also skips the deprecated package itself
+            let Ok(package) = thunderstore.find_package(name) else {
+                continue;
+            };
+
+            if package.is_deprecated {
+                continue;
+            }
+
+            self.replacements.push(Replacement {
+                full_name: package.full_name().to_owned(),
+                id: BorrowedMod::latest(package).into(),
+                reason,
+            });
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    pub checked: usize,
+    pub issues: Vec<HealthIssue>,
+    /// Mods from repositories that aren't enabled or haven't been fetched, so they couldn't be checked.
+    pub unchecked: Vec<String>,
+}
+
+impl Profile {
+    /// Checks every installed Thunderstore mod against the mod index.
+    ///
+    /// Also returns the indices of the deprecated packages' issues, along with the
+    /// id of the package, since their readmes may point to a successor.
+    fn health_report(&self, thunderstore: &Thunderstore) -> (HealthReport, Vec<(usize, ModId)>) {
+        let mut report = HealthReport {
+            checked: 0,
+            issues: Vec::new(),
+            unchecked: Vec::new(),
+        };
+        let mut readmes = Vec::new();
+
+        for (ts_mod, _) in self
+            .mods
+            .iter()
+            .filter_map(|profile_mod| profile_mod.as_thunderstore())
+        {
+            let ident = &ts_mod.ident;
+
+            let backend = match thunderstore.backend(ts_mod.id.backend) {
+                Ok(backend) if backend.packages_fetched() => backend,
+                _ => {
+                    report.unchecked.push(ident.to_string());
+                    continue;
+                }
+            };
+
+            report.checked += 1;
+
+            let new_issue = |kind| HealthIssue {
+                uuid: ts_mod.id.package_uuid,
+                full_name: ident.full_name().to_owned(),
+                version: ident.version().to_owned(),
+                kind,
+                replacements: Vec::new(),
+            };
+
+            let Ok(package) = backend.get_package(ts_mod.id.package_uuid) else {
+                // the package might have been reuploaded, or be available in another repository
+                let mut issue = new_issue(HealthIssueKind::Removed);
+                issue.add_replacements(
+                    [ident.full_name()],
+                    ReplacementReason::SameName,
+                    thunderstore,
+                );
+
+                report.issues.push(issue);
+                continue;
+            };
+
+            let is_listed = package
+                .get_version(ts_mod.id.version_uuid)
+                .is_some_and(|version| version.is_active);
+
+            if !is_listed {
+                let mut issue = new_issue(HealthIssueKind::VersionDelisted);
+
+                if let Some(version) = latest_active(package) {
+                    issue.replacements.push(Replacement {
+                        full_name: package.full_name().to_owned(),
+                        id: BorrowedMod { package, version }.into(),
+                        reason: ReplacementReason::LatestVersion,
+                    });
+                }
+
+                report.issues.push(issue);
+            }
+
+            if package.is_deprecated {
+                let mut issue = new_issue(HealthIssueKind::Deprecated);
+                issue.add_replacements(
+                    mentioned_packages(&package.latest().description),
+                    ReplacementReason::MentionedInDescription,
+                    thunderstore,
+                );
+
+                readmes.push((report.issues.len(), BorrowedMod::latest(package).into()));
+                report.issues.push(issue);
+            }
+        }
+
+        (report, readmes)
+    }
+}
+
+/// Checks the active profile for deprecated and removed packages.
+pub async fn check(app: &AppHandle) -> Result<HealthReport> {
+    thunderstore::wait_for_fetch(app).await;
+
+    let (mut report, readmes) = {
+        let manager = app.lock_manager();
+        let thunderstore = app.lock_thunderstore();
+
+        manager.active_profile().health_report(&thunderstore)
+    };
+
+    let markdowns = future::join_all(
+        readmes
+            .iter()
+            .map(|(_, mod_id)| cache::get_markdown(MarkdownKind::Readme, mod_id.clone(), app)),
+    )
+    .await;
+
+    let thunderstore = app.lock_thunderstore();
+
+    for ((index, _), markdown) in readmes.into_iter().zip(markdowns) {
+        let issue = &mut report.issues[index];
+
+        match markdown {
+            Ok(Some(readme)) => issue.add_replacements(
+                linked_packages(&readme).iter().map(String::as_str),
+                ReplacementReason::LinkedInReadme,
+                &thunderstore,
+            ),
+            Ok(None) => (),
+            Err(err) => warn!("failed to get readme of {}: {:#}", issue.full_name, err),
+        }
+    }
+
+    Ok(report)
+}
+
+fn latest_active(package: &PackageListing) -> Option<&thunderstore::PackageVersion> {
+    let mut active = package.versions.iter().filter(|version| version.is_active);
+
+    // prefer a release over a prerelease, like when updating
+    active
+        .clone()
+        .find(|version| version.parsed_version().pre.is_empty())
+        .or_else(|| active.next())
+}
+
+/// Finds the packages that Thunderstore links in `markdown`, formatted as `Owner-Name`.
+///
+/// Both `thunderstore.io/c/{community}/p/{owner}/{name}` and the older
+/// `thunderstore.io/package/{owner}/{name}` links are recognized.
+fn linked_packages(markdown: &str) -> Vec<String> {
+    let mut names = HashSet::new();
+
+    markdown
+        .match_indices("thunderstore.io/")
+        .filter_map(|(index, pattern)| {
+            let path = &markdown[index + pattern.len()..];
+            let end = path
+                .find(|char: char| !(char.is_alphanumeric() || "/_-.".contains(char)))
+                .unwrap_or(path.len());
+
+            let segments = path[..end].split('/').collect::<Vec<_>>();
+            let (owner, name) = match segments.as_slice() {
+                ["c", _, "p", owner, name, ..] => (*owner, *name),
+                ["package", owner, name, ..] => (*owner, *name),
+                _ => return None,
+            };
+
+            (is_name(owner) && is_name(name)).then(|| format!("{owner}-{name}"))
+        })
+        .filter(|full_name| names.insert(full_name.clone()))
+        .collect()
+}
+
+/// Finds words in `text` that look like `Owner-Name` package names, including
+/// `Owner-Name-1.0.0` version identifiers, which are returned without the version.
+///
+/// All lowercase words like "re-upload" are skipped, since they're most likely just prose.
+fn mentioned_packages(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|char: char| !(char.is_alphanumeric() || "_-.".contains(char)))
+        .map(|word| strip_version(word.trim_matches('.')))
+        .filter(|word| {
+            word.split_once('-')
+                .is_some_and(|(owner, name)| is_name(owner) && is_name(name))
+                && !word.chars().all(|char| char.is_lowercase() || char == '-')
+        })
+}
+
+/// Removes a trailing `-<version>` from `word`, if it has one.
+fn strip_version(word: &str) -> &str {
+    match word.rsplit_once('-') {
+        Some((rest, version)) if semver::Version::parse(version).is_ok() => rest,
+        _ => word,
+    }
+}
+
+fn is_name(str: &str) -> bool {
+    !str.is_empty()
+        && str
+            .chars()
+            .all(|char| char.is_alphanumeric() || char == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_linked_packages() {
+        let readme = "# Deprecated
+Use [NewMod](https://thunderstore.io/c/lethal-company/p/Author/NewMod/) instead.
+Also see <https://thunderstore.io/package/Other/Thing_2> and
+https://thunderstore.io/c/lethal-company/p/Author/NewMod/versions/.
+Not a package: https://thunderstore.io/c/lethal-company/";
+
+        assert_eq!(linked_packages(readme), ["Author-NewMod", "Other-Thing_2"]);
+    }
+
+    #[test]
+    fn find_mentioned_packages() {
+        let description = "DEPRECATED: merged into Author-BetterMod, a re-upload of Old-Mod-1.0.0.";
+
+        let mentioned = mentioned_packages(description).collect::<Vec<_>>();
+
+        assert_eq!(mentioned, ["Author-BetterMod", "Old-Mod"]);
+        assert!(!mentioned.contains(&"re-upload"));
+    }
+}
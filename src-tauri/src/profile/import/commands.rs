@@ -6,7 +6,10 @@ use tauri::{AppHandle, command};
 use uuid::Uuid;
 
 use crate::{
-    profile::{import::ImportOptions, install::InstallOptions},
+    profile::{
+        import::ImportOptions,
+        install::{InstallOptions, plan::InstallPlan},
+    },
     state::ManagerExt,
     thunderstore::{self, VersionIdent},
     util::cmd::Result,
@@ -59,6 +62,17 @@ pub async fn import_profile(
     Ok(())
 }
 
+#[command]
+pub fn plan_import_profile(
+    data: ImportData,
+    options: ImportOptions,
+    app: AppHandle,
+) -> Result<InstallPlan> {
+    let plan = super::plan_import(&data, &options, &app)?;
+
+    Ok(plan)
+}
+
 #[command]
 pub async fn read_profile_code(key: &str, app: AppHandle) -> Result<FrontendImportData> {
     let key = Uuid::parse_str(key).map_err(|_| anyhow!("invalid code format"))?;
@@ -98,10 +112,47 @@ pub async fn import_local_mod(path: PathBuf, app: AppHandle) -> Result<()> {
 }
 
 #[command]
-pub async fn import_local_mod_base64(base64: String, app: AppHandle) -> Result<()> {
+pub async fn import_local_mod_base64(
+    base64: String,
+    file_name: Option<String>,
+    app: AppHandle,
+) -> Result<()> {
     thunderstore::wait_for_fetch(&app).await;
 
-    super::import_local_mod_base64(base64, &app, InstallOptions::default()).await?;
+    super::import_local_mod_base64(
+        base64,
+        file_name.as_deref(),
+        &app,
+        InstallOptions::default(),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[command]
+pub async fn import_remote_mod(source: String, app: AppHandle) -> Result<()> {
+    thunderstore::wait_for_fetch(&app).await;
+
+    super::import_remote_mod(source, &app, InstallOptions::default()).await?;
+
+    Ok(())
+}
+
+#[command]
+pub async fn link_local_mod(path: PathBuf, app: AppHandle) -> Result<()> {
+    thunderstore::wait_for_fetch(&app).await;
+
+    super::link_local_mod(path, &app).await?;
+
+    Ok(())
+}
+
+#[command]
+pub fn sync_linked_mod(uuid: Uuid, app: AppHandle) -> Result<()> {
+    let profile_id = app.lock_manager().active_profile().id;
+
+    super::sync_linked_mod(profile_id, uuid, &app)?;
 
     Ok(())
 }
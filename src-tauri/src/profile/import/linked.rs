@@ -0,0 +1,378 @@
+//! Linked local mods are installed from a directory outside of the profile, such as the
+//! build output of a plugin, and reinstalled whenever the files in it change.
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Mutex, mpsc},
+    thread,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use eyre::{Context, OptionExt, Result, ensure};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tauri::AppHandle;
+use tracing::{debug, info, trace, warn};
+use uuid::Uuid;
+use walkdir::WalkDir;
+
+use super::local::{self, LocalModKind};
+use crate::{
+    prefs::Prefs,
+    profile::{
+        LinkedMod, LocalMod, ModManager, Profile, ProfileMod, ProfileModKind, install::PackageZip,
+    },
+    state::ManagerExt,
+    util::error::IoResultExt,
+};
+
+/// How long to wait for more events before syncing.
+///
+/// Builds write several files, often more than once, which shows up as a burst of events.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Adds the directory at `source` to the active profile as a linked mod.
+///
+/// Replaces any local mod with the same name.
+pub async fn link_local_mod(source: PathBuf, app: &AppHandle) -> Result<()> {
+    ensure!(source.is_dir(), "path is not a directory");
+
+    let fingerprint = fingerprint(&source)?;
+    let (local_mod, archive) = read_source(&source)?;
+
+    local::install_dependencies(&local_mod, app, Default::default()).await?;
+
+    {
+        let mut manager = app.lock_manager();
+        let prefs = app.lock_prefs();
+
+        let profile = manager.active_profile_mut();
+
+        local::remove_existing(&local_mod.name, profile)?;
+
+        let linked = install(local_mod, archive, source, fingerprint, profile, &prefs)?;
+        info!(
+            "linked {} from {}",
+            linked.local.name,
+            linked.source.display()
+        );
+
+        profile.mods.push(ProfileMod::new_linked(linked));
+        profile.save(app, true)?;
+    }
+
+    app.linked_mod_watcher().refresh(app)
+}
+
+/// Reinstalls a linked mod from its source directory.
+pub fn sync(profile_id: i64, uuid: Uuid, app: &AppHandle) -> Result<()> {
+    let source = {
+        let manager = app.lock_manager();
+        let (_, profile) = manager.profile_by_id(profile_id)?;
+
+        profile
+            .get_mod(uuid)?
+            .kind
+            .as_linked()
+            .ok_or_eyre("mod is not linked")?
+            .source
+            .clone()
+    };
+
+    ensure!(
+        source.is_dir(),
+        "source directory {} no longer exists",
+        source.display()
+    );
+
+    // read the source before locking, since it can be large
+    let fingerprint = fingerprint(&source)?;
+    let (mut local_mod, archive) = read_source(&source)?;
+    local_mod.uuid = uuid;
+
+    let mut manager = app.lock_manager();
+    let prefs = app.lock_prefs();
+
+    let (_, profile) = manager.profile_by_id_mut(profile_id)?;
+    let index = profile.index_of(uuid)?;
+    let profile_mod = &profile.mods[index];
+
+    ensure!(profile_mod.kind.as_linked().is_some(), "mod is not linked");
+
+    profile
+        .package_installer(&profile_mod.full_name())
+        .uninstall(profile_mod, profile)
+        .context("failed to remove old files")?;
+
+    let linked = install(local_mod, archive, source, fingerprint, profile, &prefs)?;
+
+    let profile_mod = &mut profile.mods[index];
+    profile_mod.kind = ProfileModKind::Linked(Box::new(linked));
+
+    if !profile_mod.enabled {
+        let profile_mod = &profile.mods[index];
+        profile
+            .package_installer(&profile_mod.full_name())
+            .toggle(false, profile_mod, profile)?;
+    }
+
+    debug!("synced linked mod {}", profile.mods[index].full_name());
+
+    profile.save(app, true)
+}
+
+/// Reads the mod's manifest and files from its source directory.
+///
+/// The manifest is read on every sync, since it may have changed since the last one.
+fn read_source(source: &Path) -> Result<(LocalMod, PackageZip)> {
+    let (local_mod, archive) = local::read_local_mod(source, Some(LocalModKind::Directory))?;
+    let archive = archive.expect("directories should always be read into an archive");
+
+    Ok((local_mod, archive))
+}
+
+/// Identifies the state of a source directory by the paths, sizes and
+/// modification times of its files, which is much cheaper than reading them.
+fn fingerprint(source: &Path) -> Result<String> {
+    let mut hasher = blake3::Hasher::new();
+
+    for entry in WalkDir::new(source).sort_by_file_name() {
+        let entry = entry.context("failed to read directory")?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let metadata = entry.metadata().context("failed to read file metadata")?;
+        let modified = metadata
+            .modified()
+            .fs_context("reading modification time", entry.path())?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        let relative_path = entry
+            .path()
+            .strip_prefix(source)
+            .expect("WalkDir should only return full paths inside of the root");
+
+        hasher.update(relative_path.to_string_lossy().as_bytes());
+        hasher.update(&metadata.len().to_le_bytes());
+        hasher.update(&modified.as_nanos().to_le_bytes());
+    }
+
+    Ok(hasher.finalize().to_hex()[..16].to_owned())
+}
+
+/// Whether a linked mod's source directory is unchanged since its last sync.
+fn is_synced(profile_id: i64, uuid: Uuid, source: &Path, app: &AppHandle) -> bool {
+    let Ok(current) = fingerprint(source) else {
+        return false;
+    };
+
+    let manager = app.lock_manager();
+
+    manager
+        .profile_by_id(profile_id)
+        .ok()
+        .and_then(|(_, profile)| profile.get_mod(uuid).ok())
+        .and_then(|profile_mod| profile_mod.kind.as_linked())
+        .is_some_and(|linked| linked.fingerprint.as_ref() == Some(&current))
+}
+
+/// Installs the files read from a linked mod's source directory into `profile`.
+fn install(
+    mut local_mod: LocalMod,
+    archive: PackageZip,
+    source: PathBuf,
+    fingerprint: String,
+    profile: &Profile,
+    prefs: &Prefs,
+) -> Result<LinkedMod> {
+    // keep the extracted files around, so they can be symlinked
+    let extract_dir = staging_dir(prefs, local_mod.uuid);
+    if extract_dir.exists() {
+        fs::remove_dir_all(&extract_dir).fs_context("clearing staging directory", &extract_dir)?;
+    }
+
+    let mod_dir = local::install_package(
+        archive,
+        profile,
+        &local_mod.name,
+        &extract_dir,
+        prefs.install_method,
+    )
+    .context("install error")?;
+
+    if let Some(mod_dir) = mod_dir {
+        local::read_metadata(&mut local_mod, &mod_dir)?;
+    }
+
+    Ok(LinkedMod {
+        local: local_mod,
+        source,
+        fingerprint: Some(fingerprint),
+    })
+}
+
+fn staging_root(prefs: &Prefs) -> PathBuf {
+    prefs.data_dir.join("linked")
+}
+
+fn staging_dir(prefs: &Prefs, uuid: Uuid) -> PathBuf {
+    staging_root(prefs).join(uuid.to_string())
+}
+
+/// Finds every linked mod across all profiles, as (profile id, mod uuid, source directory).
+fn linked_mods(manager: &ModManager) -> Vec<(i64, Uuid, PathBuf)> {
+    manager
+        .games
+        .values()
+        .flat_map(|game| &game.profiles)
+        .flat_map(|profile| {
+            profile.mods.iter().filter_map(|profile_mod| {
+                profile_mod
+                    .kind
+                    .as_linked()
+                    .map(|linked| (profile.id, linked.local.uuid, linked.source.clone()))
+            })
+        })
+        .collect()
+}
+
+/// Watches the source directories of all linked mods and syncs them when they change.
+#[derive(Default)]
+pub struct LinkedModWatcher(Mutex<Option<RecommendedWatcher>>);
+
+impl LinkedModWatcher {
+    /// Syncs the linked mods whose sources have changed while Gale
+    /// was closed, then starts watching them.
+    pub fn start(&self, app: &AppHandle) {
+        let linked = linked_mods(&app.lock_manager());
+
+        for (profile_id, uuid, source) in linked {
+            if is_synced(profile_id, uuid, &source, app) {
+                trace!("linked mod at {} is up to date", source.display());
+                continue;
+            }
+
+            if let Err(err) = sync(profile_id, uuid, app) {
+                warn!(
+                    "failed to sync linked mod at {}: {:#}",
+                    source.display(),
+                    err
+                );
+            }
+        }
+
+        if let Err(err) = self.refresh(app) {
+            warn!("failed to watch linked mods: {:#}", err);
+        }
+    }
+
+    /// Starts watching the current set of linked mods, replacing the previous watcher.
+    ///
+    /// Also deletes the staging directories of mods that have been removed.
+    pub fn refresh(&self, app: &AppHandle) -> Result<()> {
+        let linked = linked_mods(&app.lock_manager());
+
+        let mut watcher = self.0.lock().unwrap();
+
+        // drop the old watcher first, which also stops its event thread
+        *watcher = None;
+
+        remove_unused_staging(&linked, &app.lock_prefs());
+
+        if linked.is_empty() {
+            return Ok(());
+        }
+
+        let (tx, rx) = mpsc::channel();
+
+        let mut new_watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+                Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+                    for path in event.paths {
+                        tx.send(path).ok();
+                    }
+                }
+                Ok(_) => (),
+                Err(err) => warn!("linked mod watcher error: {:#}", err),
+            })
+            .context("failed to create linked mod watcher")?;
+
+        for (_, _, source) in &linked {
+            if let Err(err) = new_watcher.watch(source, RecursiveMode::Recursive) {
+                warn!("failed to watch {}: {:#}", source.display(), err);
+            }
+        }
+
+        let app = app.to_owned();
+        thread::spawn(move || handle_events(rx, app));
+
+        debug!("watching {} linked mods", linked.len());
+
+        *watcher = Some(new_watcher);
+
+        Ok(())
+    }
+}
+
+fn remove_unused_staging(linked: &[(i64, Uuid, PathBuf)], prefs: &Prefs) {
+    let root = staging_root(prefs);
+    let Ok(entries) = root.read_dir() else {
+        return;
+    };
+
+    let used: HashSet<String> = linked.iter().map(|(_, uuid, _)| uuid.to_string()).collect();
+
+    for entry in entries.filter_map(Result::ok) {
+        if used.contains(&*entry.file_name().to_string_lossy()) {
+            continue;
+        }
+
+        if let Err(err) = fs::remove_dir_all(entry.path()) {
+            warn!(
+                "failed to remove staging directory {}: {}",
+                entry.path().display(),
+                err
+            );
+        }
+    }
+}
+
+fn handle_events(rx: mpsc::Receiver<PathBuf>, app: AppHandle) {
+    // exits once the watcher, and with it the sender, is dropped
+    while let Ok(path) = rx.recv() {
+        let mut paths = HashSet::from([path]);
+
+        while let Ok(path) = rx.recv_timeout(DEBOUNCE) {
+            paths.insert(path);
+        }
+
+        trace!("linked mod paths changed: {:?}", paths);
+
+        let changed = linked_mods(&app.lock_manager())
+            .into_iter()
+            .filter(|(_, _, source)| paths.iter().any(|path| path.starts_with(source)))
+            .collect::<Vec<_>>();
+
+        if changed.is_empty() {
+            // the mod was probably removed from its profile
+            if let Err(err) = app.linked_mod_watcher().refresh(&app) {
+                warn!("failed to watch linked mods: {:#}", err);
+            }
+            continue;
+        }
+
+        for (profile_id, uuid, source) in changed {
+            match sync(profile_id, uuid, &app) {
+                Ok(()) => info!("synced linked mod from {}", source.display()),
+                Err(err) => warn!(
+                    "failed to sync linked mod at {}: {:#}",
+                    source.display(),
+                    err
+                ),
+            }
+        }
+    }
+}
@@ -1,27 +1,38 @@
 use std::{
     fs,
-    io::{Cursor, Read, Write},
+    io::{Cursor, Read, Seek, Write},
     path::{Path, PathBuf},
 };
 
 use base64::{Engine, prelude::BASE64_STANDARD};
-use eyre::{Context, Result, bail, ensure};
+use eyre::{Context, OptionExt, Result, bail, ensure};
+use flate2::read::GzDecoder;
+use sevenz_rust2::{ArchiveReader, Password};
 use tauri::AppHandle;
 use tempfile::NamedTempFile;
 use uuid::Uuid;
-use zip::ZipArchive;
+use walkdir::WalkDir;
+use xz2::read::XzDecoder;
+use zip::{CompressionMethod, ZipArchive, ZipWriter, write::SimpleFileOptions};
 
 use crate::{
-    game::mod_loader::{ModLoader, ModLoaderKind},
+    game::mod_loader::ModLoaderKind,
     prefs::Prefs,
-    profile::{LocalMod, Profile, ProfileMod, install::InstallOptions},
+    profile::{
+        LocalMod, Profile, ProfileMod,
+        install::{FileInstallMethod, InstallOptions, ModInstall, PackageZip, plan::InstallPlan},
+    },
     state::ManagerExt,
     thunderstore::PackageManifest,
-    util::{self, fs::PathExt},
+    util::{self, error::IoResultExt, fs::PathExt},
 };
 
+/// Imports a mod from base64-encoded file contents.
+///
+/// `file_name` is used to tell the archive format, defaulting to zip.
 pub async fn import_local_mod_base64(
     base64: String,
+    file_name: Option<&str>,
     app: &AppHandle,
     options: InstallOptions,
 ) -> Result<()> {
@@ -30,13 +41,11 @@ pub async fn import_local_mod_base64(
     let mut file = NamedTempFile::new().context("failed to create temp file")?;
     file.write_all(&data).context("failed to write temp file")?;
 
-    import_local_mod(
-        file.path().to_owned(),
-        Some(LocalModKind::Zip),
-        app,
-        options,
-    )
-    .await
+    let kind = file_name
+        .and_then(LocalModKind::from_file_name)
+        .unwrap_or(LocalModKind::Zip);
+
+    import_local_mod(file.path().to_owned(), Some(kind), app, options).await
 }
 
 pub async fn import_local_mod(
@@ -45,28 +54,21 @@ pub async fn import_local_mod(
     app: &AppHandle,
     options: InstallOptions,
 ) -> Result<()> {
-    let (mut local_mod, kind) = read_local_mod(&path, override_kind)?;
-
-    if let Some(deps) = &local_mod.dependencies {
-        let (profile_id, mods) = {
-            let manager = app.lock_manager();
-            let profile = manager.active_profile();
-
-            let mods = app
-                .lock_thunderstore()
-                .dependencies(deps)
-                .filter(|dep| !profile.has_mod(dep.package.uuid))
-                .map(|borrowed| borrowed.into())
-                .collect::<Vec<_>>();
+    import_local_mod_from(path, override_kind, None, app, options).await
+}
 
-            (profile.id, mods)
-        };
+/// Imports a local mod, recording where it was downloaded from.
+pub(super) async fn import_local_mod_from(
+    path: PathBuf,
+    override_kind: Option<LocalModKind>,
+    source_url: Option<String>,
+    app: &AppHandle,
+    options: InstallOptions,
+) -> Result<()> {
+    let (mut local_mod, archive) = read_local_mod(&path, override_kind)?;
+    local_mod.source_url = source_url;
 
-        app.install_queue()
-            .install(mods, profile_id, options, app)
-            .await
-            .context("failed to install dependencies")?;
-    }
+    install_dependencies(&local_mod, app, options).await?;
 
     let prefs = app.lock_prefs();
     let mut manager = app.lock_manager();
@@ -74,30 +76,18 @@ pub async fn import_local_mod(
     let mod_loader = manager.active_mod_loader();
     let profile = manager.active_profile_mut();
 
-    let existing = profile
-        .local_mods()
-        .find(|(LocalMod { name, .. }, _)| *name == local_mod.name);
-
-    let existing = existing.map(|(LocalMod { uuid, .. }, _)| *uuid);
+    remove_existing(&local_mod.name, profile)?;
 
-    if let Some(uuid) = existing {
-        profile
-            .force_remove_mod(uuid)
-            .context("failed to remove existing version")?;
-    }
-
-    match kind {
-        LocalModKind::Zip => {
-            let mod_dir = install_from_zip(&path, profile, &local_mod.name, mod_loader, &prefs)
+    match archive {
+        Some(archive) => {
+            let mod_dir = install_from_archive(archive, profile, &local_mod.name, &prefs)
                 .context("install error")?;
 
             if let Some(mod_dir) = mod_dir {
-                local_mod.icon = mod_dir.join("icon.png").exists_or_none();
-                local_mod.readme = read_text_file(&mod_dir, "README.md")?;
-                local_mod.changelog = read_text_file(&mod_dir, "CHANGELOG.md")?;
+                read_metadata(&mut local_mod, &mod_dir)?;
             }
         }
-        LocalModKind::Dll => match mod_loader.kind {
+        None => match mod_loader.kind {
             ModLoaderKind::BepInEx { .. } => {
                 let target: PathBuf = ["BepInEx", "plugins", &local_mod.name, &local_mod.name]
                     .iter()
@@ -121,32 +111,153 @@ pub async fn import_local_mod(
     Ok(())
 }
 
-#[derive(PartialEq, Eq)]
+/// Installs the dependencies of `local_mod` that are missing from the active profile.
+pub(super) async fn install_dependencies(
+    local_mod: &LocalMod,
+    app: &AppHandle,
+    options: InstallOptions,
+) -> Result<()> {
+    let Some(deps) = &local_mod.dependencies else {
+        return Ok(());
+    };
+
+    let (profile_id, mods) = {
+        let manager = app.lock_manager();
+        let profile = manager.active_profile();
+
+        let mods = app
+            .lock_thunderstore()
+            .dependencies(deps)
+            .filter(|dep| !profile.has_mod(dep.package.uuid))
+            .map(|borrowed| borrowed.into())
+            .collect::<Vec<_>>();
+
+        (profile.id, mods)
+    };
+
+    app.install_queue()
+        .install(mods, profile_id, options, app)
+        .await
+        .context("failed to install dependencies")
+}
+
+/// Removes the local mod called `name` from the profile, if there is one.
+pub(super) fn remove_existing(name: &str, profile: &mut Profile) -> Result<()> {
+    let existing = profile
+        .local_mods()
+        .find(|(local_mod, _)| local_mod.name == name)
+        .map(|(LocalMod { uuid, .. }, _)| *uuid);
+
+    if let Some(uuid) = existing {
+        profile
+            .force_remove_mod(uuid)
+            .context("failed to remove existing version")?;
+    }
+
+    Ok(())
+}
+
+/// Shows which dependencies importing the mod at `path` would install, without changing anything.
+pub fn plan_local_mod(path: &Path, app: &AppHandle) -> Result<InstallPlan> {
+    let (local_mod, _) = read_local_mod(path, None)?;
+
+    let manager = app.lock_manager();
+    let thunderstore = app.lock_thunderstore();
+    let prefs = app.lock_prefs();
+
+    let profile = manager.active_profile();
+
+    let deps = thunderstore
+        .dependencies(local_mod.dependencies.iter().flatten())
+        .filter(|dep| !profile.has_mod(dep.package.uuid))
+        .map(ModInstall::from)
+        .collect();
+
+    profile.plan_install(deps, &thunderstore, &prefs)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LocalModKind {
     Zip,
+    SevenZip,
+    TarGz,
+    TarXz,
+    /// An unpacked package, like the contents of a zip.
+    Directory,
     Dll,
 }
 
-fn read_local_mod(
+impl LocalModKind {
+    const EXTENSIONS: &[(&str, LocalModKind)] = &[
+        (".zip", LocalModKind::Zip),
+        (".7z", LocalModKind::SevenZip),
+        (".tar.gz", LocalModKind::TarGz),
+        (".tgz", LocalModKind::TarGz),
+        (".tar.xz", LocalModKind::TarXz),
+        (".txz", LocalModKind::TarXz),
+        (".dll", LocalModKind::Dll),
+    ];
+
+    fn detect(path: &Path) -> Option<Self> {
+        match path.is_dir() {
+            true => Some(LocalModKind::Directory),
+            false => Self::from_file_name(&util::fs::file_name_owned(path)),
+        }
+    }
+
+    pub(super) fn from_file_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+
+        Self::EXTENSIONS
+            .iter()
+            .find(|(ext, _)| name.ends_with(ext))
+            .map(|(_, kind)| *kind)
+    }
+}
+
+/// Returns the file name of `path` without its extension, including
+/// double extensions like `.tar.gz`.
+///
+/// Directories keep their full name, since dots in names like `netstandard2.1`
+/// aren't extensions.
+fn file_stem(path: &Path) -> String {
+    let name = util::fs::file_name_owned(path);
+    if path.is_dir() {
+        return name;
+    }
+
+    let lowercase = name.to_lowercase();
+
+    LocalModKind::EXTENSIONS
+        .iter()
+        .find(|(ext, _)| lowercase.ends_with(ext))
+        .map(|(ext, _)| name[..name.len() - ext.len()].to_owned())
+        .unwrap_or_else(|| util::fs::file_name_owned(path.with_extension("")))
+}
+
+pub(super) fn read_local_mod(
     path: &Path,
     override_kind: Option<LocalModKind>,
-) -> Result<(LocalMod, LocalModKind)> {
-    ensure!(path.is_file(), "path is not a file");
-
-    let kind = match (override_kind, path.extension().and_then(|ext| ext.to_str())) {
-        (Some(kind), _) => kind,
-        (_, Some("dll")) => LocalModKind::Dll,
-        (_, Some("zip")) => LocalModKind::Zip,
-        _ => bail!("unsupported file type"),
+) -> Result<(LocalMod, Option<PackageZip>)> {
+    ensure!(path.exists(), "path does not exist");
+
+    let kind = match override_kind {
+        Some(kind) => kind,
+        None => LocalModKind::detect(path).ok_or_eyre("unsupported file type")?,
     };
 
-    let manifest = match kind {
-        LocalModKind::Zip => read_zip_manifest(path)?,
-        LocalModKind::Dll => None,
+    let mut archive = open_archive(path, kind)?;
+
+    let manifest = match &mut archive {
+        Some(archive) => read_manifest(archive)?,
+        None => None,
     };
 
     let uuid = Uuid::new_v4();
-    let file_size = path.metadata()?.len();
+    let file_size = match kind {
+        LocalModKind::Directory => util::fs::get_directory_size(path),
+        _ => path.metadata()?.len(),
+    };
 
     let local_mod = match manifest {
         Some(manifest) => LocalMod {
@@ -162,18 +273,168 @@ fn read_local_mod(
         None => LocalMod {
             uuid,
             file_size,
-            name: util::fs::file_name_owned(path.with_extension("")),
+            name: file_stem(path),
             ..Default::default()
         },
     };
 
-    Ok((local_mod, kind))
+    Ok((local_mod, archive))
+}
+
+/// Reads the package at `path` into a zip archive, so it can be installed like any other package.
+///
+/// Returns [`None`] for dlls, which aren't packages.
+fn open_archive(path: &Path, kind: LocalModKind) -> Result<Option<PackageZip>> {
+    let mut repacker = Repacker::default();
+
+    match kind {
+        LocalModKind::Zip => {
+            let reader = fs::read(path)
+                .map(Cursor::new)
+                .context("failed to read file")?;
+
+            return ZipArchive::new(reader)
+                .context("failed to read archive")
+                .map(Some);
+        }
+        LocalModKind::Dll => return Ok(None),
+        LocalModKind::Directory => {
+            for entry in WalkDir::new(path) {
+                let entry = entry.context("failed to read directory")?;
+                if !entry.file_type().is_file() {
+                    continue;
+                }
+
+                let relative_path = entry
+                    .path()
+                    .strip_prefix(path)
+                    .expect("WalkDir should only return full paths inside of the root");
+
+                let data = fs::read(entry.path()).fs_context("reading file", entry.path())?;
+                repacker.add(relative_path, data);
+            }
+        }
+        LocalModKind::TarGz => {
+            let file = fs::File::open(path).fs_context("opening archive", path)?;
+            repacker.add_tar(GzDecoder::new(file))?;
+        }
+        LocalModKind::TarXz => {
+            let file = fs::File::open(path).fs_context("opening archive", path)?;
+            repacker.add_tar(XzDecoder::new(file))?;
+        }
+        LocalModKind::SevenZip => {
+            ArchiveReader::open(path, Password::empty())
+                .and_then(|mut archive| {
+                    archive.for_each_entries(|entry, reader| {
+                        if !entry.is_directory() {
+                            // the size comes from the archive's header, so it can't be trusted
+                            let mut data = Vec::new();
+                            reader.read_to_end(&mut data)?;
+                            repacker.add(Path::new(entry.name()), data);
+                        }
+
+                        Ok(true)
+                    })
+                })
+                .context("failed to read 7z archive")?;
+        }
+    }
+
+    repacker.finish().map(Some)
 }
 
-fn read_zip_manifest(path: &Path) -> Result<Option<PackageManifest>> {
-    let mut zip = util::fs::open_zip(path).context("failed to open zip archive")?;
+/// Collects the files of a non-zip package and writes them into an in-memory zip.
+#[derive(Default)]
+struct Repacker {
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl Repacker {
+    fn add(&mut self, relative_path: &Path, data: Vec<u8>) {
+        let path = relative_path.to_string_lossy().replace('\\', "/");
+        self.files.push((path, data));
+    }
+
+    fn add_tar(&mut self, reader: impl Read) -> Result<()> {
+        let mut archive = tar::Archive::new(reader);
+
+        for entry in archive.entries().context("failed to read tar archive")? {
+            let mut entry = entry.context("failed to read tar archive")?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+
+            let path = entry
+                .path()
+                .context("invalid path in archive")?
+                .into_owned();
+
+            // the size comes from the archive's header, so it can't be trusted
+            let mut data = Vec::new();
+            entry
+                .read_to_end(&mut data)
+                .context("failed to read tar archive")?;
+
+            self.add(&path, data);
+        }
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<PackageZip> {
+        self.strip_top_level_dir();
+
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        // the archive is only read back once, so don't bother compressing it
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+        for (path, data) in &self.files {
+            zip.start_file(path.as_str(), options)?;
+            zip.write_all(data)?;
+        }
+
+        let reader = Cursor::new(zip.finish()?.into_inner());
+        ZipArchive::new(reader).context("failed to read repacked archive")
+    }
+
+    /// Archives are often made by compressing the package's folder instead of its
+    /// contents, in which case the folder is removed so the manifest ends up at the root.
+    fn strip_top_level_dir(&mut self) {
+        let has_root_manifest = self.files.iter().any(|(path, _)| path == "manifest.json");
+        if has_root_manifest {
+            return;
+        }
+
+        let Some(top_level) = self
+            .files
+            .first()
+            .and_then(|(path, _)| path.split_once('/'))
+            .map(|(dir, _)| format!("{dir}/"))
+        else {
+            return;
+        };
+
+        let all_nested = self
+            .files
+            .iter()
+            .all(|(path, _)| path.starts_with(&top_level));
+        let has_nested_manifest = self
+            .files
+            .iter()
+            .any(|(path, _)| path.strip_prefix(&top_level) == Some("manifest.json"));
+
+        if all_nested && has_nested_manifest {
+            for (path, _) in &mut self.files {
+                path.drain(..top_level.len());
+            }
+        }
+    }
+}
 
-    let manifest = zip.by_name("manifest.json");
+pub fn read_manifest<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+) -> Result<Option<PackageManifest>> {
+    let manifest = archive.by_name("manifest.json");
 
     match manifest {
         Ok(mut file) => {
@@ -194,33 +455,56 @@ fn read_zip_manifest(path: &Path) -> Result<Option<PackageManifest>> {
     }
 }
 
-fn install_from_zip(
-    src: &Path,
+fn install_from_archive(
+    archive: PackageZip,
     profile: &Profile,
     package_name: &str,
-    mod_loader: &'static ModLoader,
     prefs: &Prefs,
 ) -> Result<Option<PathBuf>> {
     // dont use tempdir since we need the files on the same drive as the destination
     // for hard linking to work
 
     let temp_path = prefs.data_dir.join("temp").join("extract");
-    fs::create_dir_all(&temp_path).context("failed to create temporary directory")?;
 
-    let reader = fs::read(src)
-        .map(Cursor::new)
-        .context("failed to read file")?;
-    let archive = ZipArchive::new(reader).context("failed to read archive")?;
+    // the extracted files are deleted afterwards, so they can't be symlinked
+    let link_method = match prefs.install_method {
+        FileInstallMethod::Symlink => FileInstallMethod::Link,
+        method => method,
+    };
 
-    let mut installer = mod_loader.installer_for(package_name);
-    installer.extract(archive, package_name, temp_path.clone())?;
-    installer.install(&temp_path, package_name, profile)?;
+    let mod_dir = install_package(archive, profile, package_name, &temp_path, link_method)?;
 
     fs::remove_dir_all(temp_path).context("failed to remove temporary directory")?;
 
-    let directory = installer.mod_dir(package_name, profile);
+    Ok(mod_dir)
+}
+
+/// Extracts `archive` into `extract_dir` and installs it from there.
+///
+/// Returns the mod's own directory in the profile, if the installer has one.
+pub(super) fn install_package(
+    archive: PackageZip,
+    profile: &Profile,
+    package_name: &str,
+    extract_dir: &Path,
+    link_method: FileInstallMethod,
+) -> Result<Option<PathBuf>> {
+    fs::create_dir_all(extract_dir).context("failed to create temporary directory")?;
+
+    let mut installer = profile.package_installer(package_name);
+    installer.extract(archive, package_name, extract_dir.to_path_buf())?;
+    installer.install(extract_dir, package_name, profile, link_method)?;
+
+    Ok(installer.mod_dir(package_name, profile))
+}
 
-    Ok(directory)
+/// Fills in the icon, readme and changelog of `local_mod` from its installed files.
+pub(super) fn read_metadata(local_mod: &mut LocalMod, mod_dir: &Path) -> Result<()> {
+    local_mod.icon = mod_dir.join("icon.png").exists_or_none();
+    local_mod.readme = read_text_file(mod_dir, "README.md")?;
+    local_mod.changelog = read_text_file(mod_dir, "CHANGELOG.md")?;
+
+    Ok(())
 }
 
 fn read_text_file(mod_dir: &Path, file: &str) -> Result<Option<String>> {
@@ -230,3 +514,75 @@ fn read_text_file(mod_dir: &Path, file: &str) -> Result<Option<String>> {
         .map(|path| fs::read_to_string(path).with_context(|| format!("failed to read {file}")))
         .transpose()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kind_from_file_name() {
+        let kind = LocalModKind::from_file_name;
+
+        assert_eq!(kind("Mod.zip"), Some(LocalModKind::Zip));
+        assert_eq!(kind("Mod.7Z"), Some(LocalModKind::SevenZip));
+        assert_eq!(kind("Mod-1.0.tar.gz"), Some(LocalModKind::TarGz));
+        assert_eq!(kind("Mod.tgz"), Some(LocalModKind::TarGz));
+        assert_eq!(kind("Mod.tar.xz"), Some(LocalModKind::TarXz));
+        assert_eq!(kind("Plugin.DLL"), Some(LocalModKind::Dll));
+
+        assert_eq!(kind("Mod.rar"), None);
+        assert_eq!(kind("zip"), None);
+    }
+
+    #[test]
+    fn stem_of_files_and_dirs() {
+        let temp = tempfile::tempdir().unwrap();
+
+        let dir = temp.path().join("netstandard2.1");
+        fs::create_dir(&dir).unwrap();
+        assert_eq!(file_stem(&dir), "netstandard2.1");
+
+        assert_eq!(file_stem(&temp.path().join("My.Mod.zip")), "My.Mod");
+        assert_eq!(file_stem(&temp.path().join("Mod-1.0.TAR.GZ")), "Mod-1.0");
+        assert_eq!(file_stem(&temp.path().join("Mod.txt")), "Mod");
+    }
+
+    fn repacker(paths: &[&str]) -> Repacker {
+        Repacker {
+            files: paths
+                .iter()
+                .map(|path| (path.to_string(), Vec::new()))
+                .collect(),
+        }
+    }
+
+    fn paths(repacker: &Repacker) -> Vec<&str> {
+        repacker
+            .files
+            .iter()
+            .map(|(path, _)| path.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn strip_packaged_folder() {
+        let mut files = repacker(&["Mod/manifest.json", "Mod/plugins/Mod.dll"]);
+        files.strip_top_level_dir();
+        assert_eq!(paths(&files), ["manifest.json", "plugins/Mod.dll"]);
+    }
+
+    #[test]
+    fn keep_folder_without_nested_manifest() {
+        let mut files = repacker(&["plugins/Mod.dll", "plugins/Other.dll"]);
+        files.strip_top_level_dir();
+        assert_eq!(paths(&files), ["plugins/Mod.dll", "plugins/Other.dll"]);
+
+        let mut files = repacker(&["manifest.json", "Mod/manifest.json"]);
+        files.strip_top_level_dir();
+        assert_eq!(paths(&files), ["manifest.json", "Mod/manifest.json"]);
+
+        let mut files = repacker(&["Mod/manifest.json", "README.md"]);
+        files.strip_top_level_dir();
+        assert_eq!(paths(&files), ["Mod/manifest.json", "README.md"]);
+    }
+}
@@ -23,19 +23,26 @@ use crate::{
     prefs::Backends,
     profile::{
         export::{PROFILE_DATA_PREFIX, ProfileManifest},
-        install::{InstallOptions, ModInstall},
+        install::{
+            InstallOptions, ModInstall,
+            plan::{self, InstallPlan},
+        },
     },
     state::ManagerExt,
-    thunderstore::{Backend, ModId, Thunderstore},
+    thunderstore::{Backend, CustomRepository, ModId, Thunderstore},
     util::{self, error::IoResultExt},
 };
 
 pub mod commands;
+mod linked;
 mod local;
 mod r2modman;
+mod remote;
 
 use super::Profile;
-pub use local::{import_local_mod, import_local_mod_base64};
+pub use linked::{LinkedModWatcher, link_local_mod, sync as sync_linked_mod};
+pub use local::{import_local_mod, import_local_mod_base64, plan_local_mod, read_manifest};
+pub use remote::import_remote_mod;
 
 pub fn read_file_at_path(path: PathBuf, thunderstore: &Thunderstore) -> Result<ImportData> {
     let file = File::open(&path).fs_context("opening file", &path)?;
@@ -76,7 +83,7 @@ pub(super) fn read_file(
         // then try falling back to checking any other backend and update the source as needed
         if thunderstore
             .backend(r2mod.source)
-            .find_ident(&r2mod.version_ident())
+            .and_then(|backend| backend.find_ident(&r2mod.version_ident()))
             .is_err()
         {
             if let Ok(package) = thunderstore.find_ident(&r2mod.version_ident()) {
@@ -101,9 +108,20 @@ fn read_base64(base64: &str, thunderstore: &Thunderstore) -> Result<ImportData>
 }
 
 pub async fn read_code(key: Uuid, app: &AppHandle) -> Result<ImportData> {
+    let backends = Backends::All
+        .iter()
+        .chain(
+            app.lock_prefs()
+                .repositories
+                .iter()
+                .filter(|repository| repository.is_remote())
+                .map(CustomRepository::backend),
+        )
+        .collect_vec();
+
     let response = future::join_all(
-        Backends::All
-            .iter()
+        backends
+            .into_iter()
             .map(async |backend| read_code_from_backend(backend, key, app).await),
     )
     .await
@@ -118,9 +136,8 @@ pub async fn read_code(key: Uuid, app: &AppHandle) -> Result<ImportData> {
 }
 
 async fn read_code_from_backend(backend: Backend, key: Uuid, app: &AppHandle) -> Result<String> {
-    let response = app
-        .http()
-        .get(backend.profile_import(&key.to_string()))
+    let response = backend
+        .authorize(app.http().get(backend.profile_import(&key.to_string())?))
         .send()
         .await?
         .error_for_status()
@@ -197,6 +214,33 @@ pub(super) async fn import_profile(
     result
 }
 
+/// Shows what importing `data` would do, without changing anything.
+pub fn plan_import(
+    data: &ImportData,
+    options: &ImportOptions,
+    app: &AppHandle,
+) -> Result<InstallPlan> {
+    let manager = app.lock_manager();
+    let thunderstore = app.lock_thunderstore();
+    let prefs = app.lock_prefs();
+
+    let installs = data
+        .manifest
+        .mods
+        .iter()
+        .map(|r2_mod| r2_mod.into_install(&thunderstore))
+        .collect::<Result<Vec<_>>>()?;
+
+    let game = manager.active_game();
+
+    let plan = match game.find_profile_index(&data.manifest.name) {
+        Some(index) => game.profiles[index].plan_import(installs, options.merge, &prefs),
+        None => plan::plan_new_profile(installs, &prefs),
+    };
+
+    Ok(plan)
+}
+
 fn prepare_import(
     options: &ImportOptions,
     manifest: ProfileManifest,
@@ -0,0 +1,295 @@
+//! Installs local mods from direct download links and GitHub releases,
+//! for mods that aren't published on Thunderstore.
+
+use std::{fs, io::Write, path::PathBuf};
+
+use eyre::{Context, OptionExt, Result, bail, ensure};
+use futures_util::StreamExt;
+use http_cache_reqwest::CacheMode;
+use reqwest::Url;
+use serde::Deserialize;
+use tauri::AppHandle;
+use tracing::{debug, info};
+
+use super::local::{self, LocalModKind};
+use crate::{profile::install::InstallOptions, state::ManagerExt, util::error::IoResultExt};
+
+const GITHUB_PREFIX: &str = "github:";
+
+/// Name of the directory inside the cache directory where downloaded files are kept.
+///
+/// Like the object store, it's skipped when indexing the cached packages, so it's
+/// only emptied when the whole cache is cleared.
+const DOWNLOADS_DIR: &str = ".downloads";
+
+/// Where to download a mod from.
+#[derive(Debug, PartialEq, Eq)]
+enum RemoteSource {
+    Url(Url),
+    /// A release asset, written as `github:owner/repo@tag`.
+    ///
+    /// If the tag is omitted, the latest release is used.
+    GitHub {
+        owner: String,
+        repo: String,
+        tag: Option<String>,
+    },
+}
+
+impl RemoteSource {
+    fn parse(source: &str) -> Result<Self> {
+        let source = source.trim();
+
+        if let Some(reference) = source.strip_prefix(GITHUB_PREFIX) {
+            let (repo, tag) = match reference.split_once('@') {
+                Some((repo, tag)) => (repo, Some(tag.to_owned())),
+                None => (reference, None),
+            };
+
+            let (owner, repo) = repo
+                .split_once('/')
+                .ok_or_eyre("expected github:owner/repo@tag")?;
+
+            let is_valid = !owner.is_empty()
+                && !repo.is_empty()
+                && tag.as_ref().is_none_or(|tag| !tag.is_empty());
+
+            ensure!(is_valid, "expected github:owner/repo@tag");
+
+            return Ok(RemoteSource::GitHub {
+                owner: owner.to_owned(),
+                repo: repo.to_owned(),
+                tag,
+            });
+        }
+
+        let url = Url::parse(source).context("invalid url")?;
+        ensure!(
+            matches!(url.scheme(), "http" | "https"),
+            "only http and https urls are supported"
+        );
+
+        Ok(RemoteSource::Url(url))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubRelease {
+    assets: Vec<GitHubAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubAsset {
+    id: u64,
+    name: String,
+    browser_download_url: Url,
+}
+
+/// A file to download.
+struct Download {
+    url: Url,
+    file_name: String,
+    /// Identifies the file in the download cache.
+    cache_key: String,
+}
+
+impl Download {
+    fn from_url(url: Url) -> Result<Self> {
+        let file_name = url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .and_then(|name| {
+                percent_encoding::percent_decode_str(name)
+                    .decode_utf8()
+                    .ok()
+            })
+            .filter(|name| !name.is_empty() && !name.contains(['/', '\\']))
+            .map(|name| name.into_owned())
+            .ok_or_eyre("url does not point to a file")?;
+
+        Ok(Download {
+            cache_key: url.to_string(),
+            url,
+            file_name,
+        })
+    }
+
+    /// Returns where the file is kept in the download cache.
+    fn cache_path(&self, app: &AppHandle) -> PathBuf {
+        let mut path = app.lock_prefs().cache_dir();
+
+        path.push(DOWNLOADS_DIR);
+        path.push(&blake3::hash(self.cache_key.as_bytes()).to_hex()[..16]);
+        path.push(&self.file_name);
+
+        path
+    }
+
+    /// Downloads the file, unless it's already in the download cache.
+    async fn fetch(&self, app: &AppHandle) -> Result<PathBuf> {
+        let path = self.cache_path(app);
+
+        if path.exists() {
+            debug!("using cached download of {}", self.url);
+            return Ok(path);
+        }
+
+        info!("downloading {} from {}", self.file_name, self.url);
+
+        let mut stream = app
+            .http()
+            .get(self.url.clone())
+            .with_extension(CacheMode::NoStore)
+            .send()
+            .await
+            .context("failed to send request")?
+            .error_for_status()
+            .context("download failed")?
+            .bytes_stream();
+
+        let dir = path.parent().unwrap();
+        fs::create_dir_all(dir).fs_context("creating download directory", dir)?;
+
+        // only move the file into place once it's complete, so interrupted downloads aren't reused
+        let mut temp_file =
+            tempfile::NamedTempFile::new_in(dir).fs_context("creating download file", dir)?;
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.context("failed to download file")?;
+            temp_file
+                .write_all(&chunk)
+                .fs_context("writing downloaded file", temp_file.path())?;
+        }
+
+        temp_file
+            .persist(&path)
+            .map_err(|err| err.error)
+            .fs_context("saving downloaded file", &path)?;
+
+        Ok(path)
+    }
+}
+
+/// Finds the mod asset in a GitHub release.
+async fn resolve_github(
+    owner: &str,
+    repo: &str,
+    tag: Option<&str>,
+    app: &AppHandle,
+) -> Result<Download> {
+    let url = match tag {
+        Some(tag) => format!("https://api.github.com/repos/{owner}/{repo}/releases/tags/{tag}"),
+        None => format!("https://api.github.com/repos/{owner}/{repo}/releases/latest"),
+    };
+
+    let release: GitHubRelease = app
+        .http()
+        .get(url)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .context("failed to send request")?
+        .error_for_status()
+        .context("failed to find release")?
+        .json()
+        .await
+        .context("failed to parse release")?;
+
+    // prefer packages over loose dlls, since they can have a manifest
+    let asset = release
+        .assets
+        .iter()
+        .filter(|asset| LocalModKind::from_file_name(&asset.name).is_some())
+        .min_by_key(|asset| LocalModKind::from_file_name(&asset.name) == Some(LocalModKind::Dll))
+        .ok_or_eyre("release has no mod assets")?;
+
+    Ok(Download {
+        url: asset.browser_download_url.clone(),
+        file_name: asset.name.clone(),
+        // the url of an asset stays the same if it's replaced, but its id doesn't
+        cache_key: format!("github:{}", asset.id),
+    })
+}
+
+/// Downloads a mod from an http(s) url or GitHub release and imports it into the active profile.
+///
+/// `source` is recorded on the imported mod, so it can be checked for updates later.
+pub async fn import_remote_mod(
+    source: String,
+    app: &AppHandle,
+    options: InstallOptions,
+) -> Result<()> {
+    let download = match RemoteSource::parse(&source)? {
+        RemoteSource::Url(url) => Download::from_url(url)?,
+        RemoteSource::GitHub { owner, repo, tag } => {
+            resolve_github(&owner, &repo, tag.as_deref(), app).await?
+        }
+    };
+
+    let Some(kind) = LocalModKind::from_file_name(&download.file_name) else {
+        bail!("unsupported file type: {}", download.file_name);
+    };
+
+    // the file keeps its original name, which is used as the mod's name if it has no manifest
+    let path = download.fetch(app).await?;
+
+    local::import_local_mod_from(path, Some(kind), Some(source), app, options).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_github() {
+        assert_eq!(
+            RemoteSource::parse("github:Owner/Repo@v1.2.0").unwrap(),
+            RemoteSource::GitHub {
+                owner: "Owner".into(),
+                repo: "Repo".into(),
+                tag: Some("v1.2.0".into()),
+            }
+        );
+
+        assert_eq!(
+            RemoteSource::parse("github:Owner/Repo").unwrap(),
+            RemoteSource::GitHub {
+                owner: "Owner".into(),
+                repo: "Repo".into(),
+                tag: None,
+            }
+        );
+
+        assert!(RemoteSource::parse("github:Owner").is_err());
+        assert!(RemoteSource::parse("github:Owner/Repo@").is_err());
+    }
+
+    #[test]
+    fn parse_url() {
+        assert!(matches!(
+            RemoteSource::parse("https://example.com/Mod.zip"),
+            Ok(RemoteSource::Url(_))
+        ));
+
+        assert!(RemoteSource::parse("ftp://example.com/Mod.zip").is_err());
+        assert!(RemoteSource::parse("not a url").is_err());
+    }
+
+    #[test]
+    fn decode_file_name() {
+        let file_name =
+            |url: &str| Download::from_url(Url::parse(url).unwrap()).map(|d| d.file_name);
+
+        assert_eq!(
+            file_name("https://example.com/files/My%20Mod%201.0.zip").unwrap(),
+            "My Mod 1.0.zip"
+        );
+        assert_eq!(
+            file_name("https://example.com/Mod.dll?raw=true").unwrap(),
+            "Mod.dll"
+        );
+
+        assert!(file_name("https://example.com/files/").is_err());
+        assert!(file_name("https://example.com/..%2FMod.zip").is_err());
+    }
+}
@@ -0,0 +1,435 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use itertools::Itertools;
+use rayon::prelude::*;
+use serde::Serialize;
+use tauri::AppHandle;
+use tracing::{debug, info, warn};
+
+use super::manifest::Manifest;
+use crate::{
+    db::CacheObjectRef,
+    state::ManagerExt,
+    util::{self, error::IoResultExt},
+};
+
+/// A single cached package version.
+#[derive(Debug)]
+pub struct CacheEntry {
+    pub full_name: String,
+    /// Name of the version's directory, see [`super::dir_name`].
+    pub version: String,
+    pub path: PathBuf,
+    /// Combined size of the version's files, including the ones shared with other versions.
+    pub size: u64,
+    /// The version's files in the object store.
+    ///
+    /// Empty if the version hasn't been moved into the store.
+    pub objects: Vec<CacheObjectRef>,
+    pub last_used: DateTime<Utc>,
+    /// The game the entry was last installed for, if known.
+    pub last_game: Option<String>,
+    /// Slugs of the games with profiles that have this version installed.
+    pub referenced_by: BTreeSet<&'static str>,
+}
+
+impl CacheEntry {
+    pub fn is_referenced(&self) -> bool {
+        !self.referenced_by.is_empty()
+    }
+}
+
+/// Returns the disk space taken up by `entries`, counting objects shared between them once.
+fn disk_size<'a>(entries: impl IntoIterator<Item = &'a CacheEntry>) -> u64 {
+    let mut objects = HashMap::new();
+    let mut size = 0;
+
+    for entry in entries {
+        if entry.objects.is_empty() {
+            size += entry.size;
+        }
+
+        objects.extend(
+            entry
+                .objects
+                .iter()
+                .map(|object| (&object.hash, object.size)),
+        );
+    }
+
+    size + objects.values().sum::<u64>()
+}
+
+/// Indexes every package version in the cache directory, across all games.
+pub fn index(app: &AppHandle) -> Result<Vec<CacheEntry>> {
+    let cache_dir = app.lock_prefs().cache_dir();
+
+    let mut referenced: HashMap<(String, String), BTreeSet<&'static str>> = HashMap::new();
+
+    {
+        let manager = app.lock_manager();
+
+        for (game, managed_game) in &manager.games {
+            for profile in &managed_game.profiles {
+                for (ts_mod, _) in profile.thunderstore_mods() {
+                    let key = (
+                        ts_mod.ident.full_name().to_owned(),
+                        super::dir_name(
+                            ts_mod.ident.version(),
+                            ts_mod.rules_fingerprint().as_deref(),
+                        ),
+                    );
+
+                    referenced.entry(key).or_default().insert(&game.slug);
+                }
+            }
+        }
+    }
+
+    let mut usage = app.db().cache_usage()?;
+    let mut objects = app.db().cache_object_refs()?;
+
+    if !cache_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let versions = cache_dir
+        .read_dir()
+        .fs_context("reading cache directory", &cache_dir)?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .flat_map(|package| {
+            let full_name = util::fs::file_name_owned(package.path());
+
+            package
+                .path()
+                .read_dir()
+                .into_iter()
+                .flatten()
+                .filter_map(Result::ok)
+                .filter(|entry| entry.path().is_dir())
+                .map(move |version| (full_name.clone(), version.path()))
+        })
+        .collect_vec();
+
+    let versions = versions
+        .into_iter()
+        .map(|(full_name, path)| {
+            let key = (full_name, util::fs::file_name_owned(&path));
+            let objects = objects.remove(&key).unwrap_or_default();
+            (key, path, objects)
+        })
+        .collect_vec();
+
+    // versions in the object store are sized by their objects, since
+    // walking the hard links would count shared files once per version
+    let sizes = versions
+        .par_iter()
+        .map(|(_, path, objects)| match objects.is_empty() {
+            true => util::fs::get_directory_size(path),
+            false => objects.iter().map(|object| object.size).sum(),
+        })
+        .collect::<Vec<_>>();
+
+    let entries = versions
+        .into_iter()
+        .zip(sizes)
+        .map(|((key, path, objects), size)| {
+            let usage = usage.remove(&key);
+            let referenced_by = referenced.remove(&key).unwrap_or_default();
+            let (full_name, version) = key;
+
+            let last_used = usage
+                .as_ref()
+                .map(|usage| usage.last_used)
+                .unwrap_or_else(|| modified_time(&path));
+
+            CacheEntry {
+                full_name,
+                version,
+                path,
+                size,
+                objects,
+                last_used,
+                last_game: usage.and_then(|usage| usage.game_slug),
+                referenced_by,
+            }
+        })
+        .collect();
+
+    Ok(entries)
+}
+
+fn modified_time(path: &Path) -> DateTime<Utc> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+        .into()
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheUsage {
+    pub total_size: u64,
+    pub unreferenced_size: u64,
+    pub size_limit: Option<u64>,
+    pub games: Vec<GameCacheUsage>,
+    pub packages: Vec<PackageCacheUsage>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameCacheUsage {
+    /// `None` for versions that can't be attributed to any game.
+    pub slug: Option<String>,
+    /// Versions shared between games are counted towards each of them.
+    pub size: u64,
+    pub version_count: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageCacheUsage {
+    pub full_name: String,
+    pub size: u64,
+    pub versions: Vec<VersionCacheUsage>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionCacheUsage {
+    pub version: String,
+    pub size: u64,
+    pub last_used: DateTime<Utc>,
+    pub referenced_by: Vec<&'static str>,
+}
+
+pub fn usage(app: &AppHandle) -> Result<CacheUsage> {
+    let entries = index(app)?;
+
+    let mut games: HashMap<Option<String>, (u64, usize)> = HashMap::new();
+
+    for entry in &entries {
+        let slugs = match entry.is_referenced() {
+            true => entry
+                .referenced_by
+                .iter()
+                .map(|slug| Some(slug.to_string()))
+                .collect_vec(),
+            false => vec![entry.last_game.clone()],
+        };
+
+        for slug in slugs {
+            let (size, count) = games.entry(slug).or_default();
+            *size += entry.size;
+            *count += 1;
+        }
+    }
+
+    let games = games
+        .into_iter()
+        .map(|(slug, (size, version_count))| GameCacheUsage {
+            slug,
+            size,
+            version_count,
+        })
+        .sorted_by_key(|game| std::cmp::Reverse(game.size))
+        .collect();
+
+    let total_size = disk_size(&entries);
+    // the space that would be freed by removing every unreferenced version
+    let unreferenced_size =
+        total_size - disk_size(entries.iter().filter(|entry| entry.is_referenced()));
+
+    let packages = entries
+        .into_iter()
+        .into_group_map_by(|entry| entry.full_name.clone())
+        .into_iter()
+        .map(|(full_name, versions)| PackageCacheUsage {
+            full_name,
+            size: versions.iter().map(|entry| entry.size).sum(),
+            versions: versions
+                .into_iter()
+                .map(|entry| VersionCacheUsage {
+                    version: entry.version,
+                    size: entry.size,
+                    last_used: entry.last_used,
+                    referenced_by: entry.referenced_by.into_iter().collect(),
+                })
+                .collect(),
+        })
+        .sorted_by_key(|package| std::cmp::Reverse(package.size))
+        .collect();
+
+    Ok(CacheUsage {
+        total_size,
+        unreferenced_size,
+        size_limit: app.lock_prefs().cache_size_limit,
+        games,
+        packages,
+    })
+}
+
+/// Removes the given entries from the cache, along with their package
+/// directories if they end up empty.
+pub fn remove(entries: &[CacheEntry], app: &AppHandle) -> Result<()> {
+    for entry in entries {
+        super::manifest::remove_version(&entry.path)?;
+
+        if let Some(parent) = entry.path.parent() {
+            // only succeeds if the directory is empty
+            fs::remove_dir(parent).ok();
+        }
+    }
+
+    let versions = entries
+        .iter()
+        .map(|entry| (entry.full_name.as_str(), entry.version.as_str()));
+
+    app.db().delete_cache_usage(versions.clone())?;
+    super::store::remove(versions, app)?;
+
+    info!("removed {} mods from cache", entries.len());
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheVerifyReport {
+    pub checked: usize,
+    /// Identifiers of the versions that were corrupt and got removed.
+    pub removed: Vec<String>,
+    pub freed: u64,
+}
+
+/// Checks every cached version against its manifest and removes the ones that don't match,
+/// so that they get downloaded again the next time they're installed.
+pub fn verify(app: &AppHandle) -> Result<CacheVerifyReport> {
+    let entries = index(app)?;
+    let checked = entries.len();
+
+    let corrupt = entries
+        .into_par_iter()
+        .filter(|entry| {
+            let intact = Manifest::read(&entry.path)
+                .and_then(|manifest| manifest.verify(&entry.path))
+                .unwrap_or(false);
+
+            if !intact {
+                warn!("{}-{} is corrupt", entry.full_name, entry.version);
+            }
+
+            !intact
+        })
+        .collect::<Vec<_>>();
+
+    remove(&corrupt, app)?;
+
+    Ok(CacheVerifyReport {
+        checked,
+        removed: corrupt
+            .iter()
+            .map(|entry| format!("{}-{}", entry.full_name, entry.version))
+            .collect(),
+        freed: corrupt.iter().map(|entry| entry.size).sum(),
+    })
+}
+
+/// Evicts the least recently used unreferenced versions until the cache fits within `limit`.
+///
+/// Versions installed in any profile are never evicted, so the cache may still exceed the limit.
+/// The size of the cache is measured in disk space, so files shared with other versions
+/// only count as freed once every version using them is evicted.
+///
+/// Returns the number of bytes freed.
+pub fn evict(limit: u64, app: &AppHandle) -> Result<u64> {
+    let entries = index(app)?;
+
+    let mut total = disk_size(&entries);
+    if total <= limit {
+        return Ok(0);
+    }
+
+    let mut object_refs: HashMap<String, usize> = HashMap::new();
+    for object in entries.iter().flat_map(|entry| &entry.objects) {
+        *object_refs.entry(object.hash.clone()).or_default() += 1;
+    }
+
+    let candidates = entries
+        .into_iter()
+        .filter(|entry| !entry.is_referenced())
+        .sorted_by_key(|entry| entry.last_used);
+
+    let mut to_remove = Vec::new();
+    let mut freed = 0;
+
+    for entry in candidates {
+        if total <= limit {
+            break;
+        }
+
+        let size = match entry.objects.is_empty() {
+            true => entry.size,
+            false => entry
+                .objects
+                .iter()
+                .filter(|object| {
+                    let refs = object_refs
+                        .get_mut(&object.hash)
+                        .expect("object should be counted");
+                    *refs -= 1;
+                    *refs == 0
+                })
+                .map(|object| object.size)
+                .sum(),
+        };
+
+        total -= size;
+        freed += size;
+        to_remove.push(entry);
+    }
+
+    debug!(
+        count = to_remove.len(),
+        freed, limit, "evicting mods from cache"
+    );
+
+    remove(&to_remove, app)?;
+
+    Ok(freed)
+}
+
+/// Enforces the cache size limit from the prefs in the background, if one is set.
+pub fn spawn_eviction(app: &AppHandle) {
+    let app = app.to_owned();
+    tauri::async_runtime::spawn_blocking(move || {
+        let Some(limit) = app.lock_prefs().cache_size_limit else {
+            return;
+        };
+
+        // keep mods from being installed while evicting, since
+        // they could be installed from one of the evicted versions
+        let Some(_pause) = app.install_queue().try_pause() else {
+            debug!("skipping cache eviction while installing");
+            return;
+        };
+
+        match evict(limit, &app) {
+            Ok(0) => (),
+            Ok(freed) => info!(
+                "freed {} from the download cache",
+                humansize::format_size(freed, humansize::BINARY)
+            ),
+            Err(err) => warn!("failed to evict mods from cache: {:#}", err),
+        }
+    });
+}
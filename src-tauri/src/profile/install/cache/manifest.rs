@@ -0,0 +1,176 @@
+//! Each cached version has a manifest file next to its directory, listing the
+//! version's files along with their checksums.
+//!
+//! The manifest is written once the version has been fully extracted, so it
+//! doubles as a completion marker: directories without one are left over from
+//! an interrupted extraction and must not be installed from. While a version is
+//! being extracted, an `.extracting` marker sits next to it, which tells those
+//! leftovers apart from versions cached before manifests were introduced.
+
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use eyre::{Context, Result};
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::util::{self, error::IoResultExt, fs::JsonStyle};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    /// Keyed by [`key`].
+    pub files: BTreeMap<String, ManifestFile>,
+    /// Fingerprint of the install rules the version was extracted with, if any.
+    ///
+    /// See [`crate::profile::install::rules::InstallRules::fingerprint`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rules: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestFile {
+    /// Hex-encoded blake3 hash of the file's contents.
+    pub hash: String,
+    pub size: u64,
+}
+
+/// Returns the path of the manifest for the version at `version_dir`.
+pub fn path(version_dir: &Path) -> PathBuf {
+    let mut path = OsString::from(version_dir.as_os_str());
+    path.push(".manifest.json");
+    path.into()
+}
+
+fn extracting_path(version_dir: &Path) -> PathBuf {
+    let mut path = OsString::from(version_dir.as_os_str());
+    path.push(".extracting");
+    path.into()
+}
+
+/// Marks the version at `version_dir` as being extracted, until its manifest is written.
+pub fn begin_extract(version_dir: &Path) -> Result<()> {
+    let path = extracting_path(version_dir);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).fs_context("creating cache directory", parent)?;
+    }
+
+    fs::write(&path, []).fs_context("writing extraction marker", &path)?;
+
+    Ok(())
+}
+
+/// Whether the version at `version_dir` was being extracted, but never finished.
+pub fn is_interrupted(version_dir: &Path) -> bool {
+    extracting_path(version_dir).exists() && !path(version_dir).exists()
+}
+
+/// Converts a path relative to the version directory into a manifest key,
+/// which always uses forward slashes.
+pub fn key(relative_path: &Path) -> String {
+    relative_path.to_string_lossy().replace('\\', "/")
+}
+
+impl Manifest {
+    /// Hashes every file in `version_dir`.
+    pub fn create(version_dir: &Path) -> Result<Self> {
+        let mut files = BTreeMap::new();
+
+        for entry in WalkDir::new(version_dir) {
+            let entry = entry.context("failed to read cached mod")?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let relative_path = entry
+                .path()
+                .strip_prefix(version_dir)
+                .expect("WalkDir should only return full paths inside of the root");
+
+            let hash = util::fs::checksum(entry.path())
+                .fs_context("hashing file", entry.path())?
+                .to_hex()
+                .to_string();
+            let size = entry
+                .metadata()
+                .context("failed to read file metadata")?
+                .len();
+
+            files.insert(key(relative_path), ManifestFile { hash, size });
+        }
+
+        Ok(Self { files, rules: None })
+    }
+
+    pub fn read(version_dir: &Path) -> Result<Self> {
+        util::fs::read_json(path(version_dir))
+    }
+
+    /// Writes the manifest, which marks the version as complete.
+    pub fn write(&self, version_dir: &Path) -> Result<()> {
+        let path = path(version_dir);
+
+        // write to a temporary file first, so a half-written manifest is never mistaken for a valid one
+        let mut temp_path = path.clone().into_os_string();
+        temp_path.push(".tmp");
+
+        util::fs::write_json(&temp_path, self, JsonStyle::Compact)?;
+        fs::rename(&temp_path, &path).fs_context("writing cache manifest", &path)?;
+
+        remove_file(&extracting_path(version_dir))
+    }
+
+    /// Cheaply checks whether every file in the manifest exists with the right size.
+    fn matches_sizes(&self, version_dir: &Path) -> bool {
+        self.files.iter().all(|(key, file)| {
+            fs::metadata(version_dir.join(key)).is_ok_and(|metadata| metadata.len() == file.size)
+        })
+    }
+
+    /// Hashes every file in `version_dir` and compares them against the manifest,
+    /// also making sure there are no files that aren't in the manifest.
+    pub fn verify(&self, version_dir: &Path) -> Result<bool> {
+        let actual = Self::create(version_dir)?;
+
+        let matches = actual.files.len() == self.files.len()
+            && actual.files.iter().all(|(key, file)| {
+                self.files
+                    .get(key)
+                    .is_some_and(|expected| expected.hash == file.hash)
+            });
+
+        Ok(matches)
+    }
+}
+
+/// Whether the version at `version_dir` was completely extracted and is safe to install from.
+pub fn is_complete(version_dir: &Path) -> bool {
+    Manifest::read(version_dir).is_ok_and(|manifest| manifest.matches_sizes(version_dir))
+}
+
+/// Removes a cached version along with its manifest.
+pub fn remove_version(version_dir: &Path) -> Result<()> {
+    // remove the manifest first, so the version is never mistaken as complete
+    remove_file(&path(version_dir))?;
+
+    if version_dir.exists() {
+        fs::remove_dir_all(version_dir).fs_context("removing cached mod", version_dir)?;
+    }
+
+    remove_file(&extracting_path(version_dir))
+}
+
+fn remove_file(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => {
+            Err(err).fs_context("removing cache file", path)
+        }
+        _ => Ok(()),
+    }
+}
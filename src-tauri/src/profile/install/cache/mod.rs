@@ -0,0 +1,58 @@
+use std::{fs, path::PathBuf};
+
+use eyre::{Context, Result};
+use tauri::AppHandle;
+
+use crate::{prefs::Prefs, thunderstore::VersionIdent};
+
+pub mod gc;
+pub mod manifest;
+pub mod store;
+
+/// Returns the directory of a cached version, as extracted under the install rules
+/// with the given fingerprint.
+///
+/// See [`dir_name`].
+pub(super) fn path(ident: &VersionIdent, rules: Option<&str>, prefs: &Prefs) -> PathBuf {
+    let mut path = prefs.cache_dir();
+
+    path.push(ident.full_name());
+    path.push(dir_name(ident.version(), rules));
+
+    path
+}
+
+/// Returns the name of a cached version's directory.
+///
+/// Versions extracted under install rules may be laid out differently, so they're
+/// kept apart from the plain extraction in a `<version>+<fingerprint>` directory.
+/// Cache entries in the database are keyed by this name instead of the version.
+pub(super) fn dir_name(version: &str, rules: Option<&str>) -> String {
+    match rules {
+        Some(fingerprint) => format!("{version}+{fingerprint}"),
+        None => version.to_owned(),
+    }
+}
+
+pub(super) fn clear(path: PathBuf) -> Result<()> {
+    if path.exists() {
+        fs::remove_dir_all(&path).context("failed to delete cache directory")?;
+        fs::create_dir_all(path).context("failed to recreate cache directory")?;
+    }
+
+    Ok(())
+}
+
+/// Returns every cached version that isn't installed in any profile, across all games.
+pub(super) fn prepare_soft_clear(app: &AppHandle) -> Result<Vec<gc::CacheEntry>> {
+    let entries = gc::index(app)?
+        .into_iter()
+        .filter(|entry| !entry.is_referenced())
+        .collect();
+
+    Ok(entries)
+}
+
+pub(super) fn do_soft_clear(entries: Vec<gc::CacheEntry>, app: &AppHandle) -> Result<()> {
+    gc::remove(&entries, app)
+}
@@ -0,0 +1,311 @@
+//! Content-addressed storage for the download cache.
+//!
+//! Every file in a cached version is hashed and stored once in the object store,
+//! with the version directories made up of hard links into it. This way files
+//! shared between versions (or packages) only take up disk space once.
+
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use eyre::Result;
+use serde::Serialize;
+use tauri::AppHandle;
+use tracing::{debug, info, warn};
+use walkdir::WalkDir;
+
+use super::manifest::{self, Manifest};
+use crate::{
+    db::CacheObjectRef,
+    state::ManagerExt,
+    util::{self, error::IoResultExt},
+};
+
+/// Name of the object store directory inside the cache directory.
+///
+/// Package names can't start with a dot, so this never collides with a cached package.
+pub const OBJECTS_DIR: &str = ".objects";
+
+pub fn objects_dir(cache_dir: &Path) -> PathBuf {
+    cache_dir.join(OBJECTS_DIR)
+}
+
+fn object_path(objects_dir: &Path, hash: &str) -> PathBuf {
+    objects_dir.join(&hash[..2]).join(hash)
+}
+
+/// Moves the files of a cached version into the object store,
+/// replacing them with hard links to the stored objects.
+pub fn insert(
+    version_dir: &Path,
+    manifest: &Manifest,
+    full_name: &str,
+    version: &str,
+    app: &AppHandle,
+) -> Result<()> {
+    let objects_dir = objects_dir(&app.lock_prefs().cache_dir());
+
+    let refs = manifest
+        .files
+        .iter()
+        .map(|(key, file)| CacheObjectRef {
+            path: key.clone(),
+            hash: file.hash.clone(),
+            size: file.size,
+        })
+        .collect::<Vec<_>>();
+
+    // record the refs before creating any objects, so they're
+    // pruned along with the version if linking fails midway
+    app.db().set_cache_object_refs(full_name, version, &refs)?;
+
+    for object_ref in &refs {
+        link_object(
+            &version_dir.join(&object_ref.path),
+            &object_path(&objects_dir, &object_ref.hash),
+        )?;
+    }
+
+    debug!(full_name, version, files = refs.len(), "stored cached mod");
+
+    Ok(())
+}
+
+fn link_object(path: &Path, object: &Path) -> Result<()> {
+    if !object.exists() {
+        let parent = object.parent().unwrap();
+        fs::create_dir_all(parent).fs_context("creating object directory", parent)?;
+
+        match fs::hard_link(path, object) {
+            Ok(()) => return Ok(()),
+            // another file with the same contents got there first
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => (),
+            Err(err) => return Err(err).fs_context("storing object", object),
+        }
+    }
+
+    // link to a temporary path first, so the file is never missing if this fails midway
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".link");
+    let temp_path = PathBuf::from(temp_path);
+
+    fs::hard_link(object, &temp_path).fs_context("linking object", &temp_path)?;
+    fs::rename(&temp_path, path)
+        .inspect_err(|_| {
+            fs::remove_file(&temp_path).ok();
+        })
+        .fs_context("replacing file with object", path)?;
+
+    Ok(())
+}
+
+/// Forgets the files of the given versions and deletes the objects that are no longer used.
+pub fn remove<'a>(
+    versions: impl IntoIterator<Item = (&'a str, &'a str)>,
+    app: &AppHandle,
+) -> Result<()> {
+    app.db().delete_cache_object_refs(versions)?;
+    prune(app)
+}
+
+/// Deletes every object that isn't referred to by any cached version.
+pub fn prune(app: &AppHandle) -> Result<()> {
+    let objects_dir = objects_dir(&app.lock_prefs().cache_dir());
+    let hashes = app.db().take_unreferenced_cache_objects()?;
+
+    for hash in &hashes {
+        let path = object_path(&objects_dir, hash);
+
+        match fs::remove_file(&path) {
+            Ok(()) => {
+                // only succeeds if the directory is empty
+                fs::remove_dir(path.parent().unwrap()).ok();
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+            Err(err) => warn!("failed to delete cache object {}: {}", hash, err),
+        }
+    }
+
+    if !hashes.is_empty() {
+        debug!(count = hashes.len(), "pruned cache objects");
+    }
+
+    Ok(())
+}
+
+/// Moves every cached version that isn't in the object store yet into it,
+/// and forgets about versions that have been deleted from disk.
+///
+/// This brings caches from before the object store and manifests were introduced up to date,
+/// and deletes versions whose extraction was interrupted.
+pub fn migrate(app: &AppHandle) -> Result<()> {
+    let cache_dir = app.lock_prefs().cache_dir();
+
+    if !cache_dir.exists() {
+        return Ok(());
+    }
+
+    let mut stored = app.db().cache_object_versions()?;
+    let mut on_disk = HashSet::new();
+    let mut migrated = 0;
+
+    let packages = cache_dir
+        .read_dir()
+        .fs_context("reading cache directory", &cache_dir)?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'));
+
+    for package in packages {
+        let full_name = util::fs::file_name_owned(package.path());
+
+        let versions = package
+            .path()
+            .read_dir()
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().is_dir());
+
+        for version in versions {
+            let key = (full_name.clone(), util::fs::file_name_owned(version.path()));
+
+            if manifest::is_interrupted(&version.path()) {
+                match manifest::remove_version(&version.path()) {
+                    Ok(()) => debug!("removed interrupted extraction of {}-{}", key.0, key.1),
+                    Err(err) => warn!(
+                        "failed to remove interrupted extraction of {}-{}: {:#}",
+                        key.0, key.1, err
+                    ),
+                }
+
+                continue;
+            }
+
+            if !stored.contains(&key) || !manifest::path(&version.path()).exists() {
+                match migrate_version(&version.path(), &key.0, &key.1, app) {
+                    Ok(()) => migrated += 1,
+                    Err(err) => warn!(
+                        "failed to move {}-{} into the object store: {:#}",
+                        key.0, key.1, err
+                    ),
+                }
+            }
+
+            on_disk.insert(key);
+        }
+    }
+
+    stored.retain(|key| !on_disk.contains(key));
+    remove(
+        stored
+            .iter()
+            .map(|(full_name, version)| (full_name.as_str(), version.as_str())),
+        app,
+    )?;
+
+    if migrated > 0 {
+        info!("moved {} cached mods into the object store", migrated);
+    }
+
+    sweep(&cache_dir, app)
+}
+
+/// Deletes objects that aren't known to the database at all, which
+/// can be left behind if the app is closed while storing a version.
+fn sweep(cache_dir: &Path, app: &AppHandle) -> Result<()> {
+    let objects_dir = objects_dir(cache_dir);
+    if !objects_dir.exists() {
+        return Ok(());
+    }
+
+    let known = app.db().cache_object_hashes()?;
+    let mut count = 0;
+
+    for entry in WalkDir::new(&objects_dir)
+        .min_depth(2)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+    {
+        if known.contains(entry.file_name().to_string_lossy().as_ref()) {
+            continue;
+        }
+
+        match fs::remove_file(entry.path()) {
+            Ok(()) => count += 1,
+            Err(err) => warn!(
+                "failed to delete cache object {}: {}",
+                entry.path().display(),
+                err
+            ),
+        }
+    }
+
+    if count > 0 {
+        debug!(count, "swept unknown cache objects");
+    }
+
+    Ok(())
+}
+
+fn migrate_version(
+    version_dir: &Path,
+    full_name: &str,
+    version: &str,
+    app: &AppHandle,
+) -> Result<()> {
+    let manifest = match Manifest::read(version_dir) {
+        Ok(manifest) => manifest,
+        Err(_) => {
+            // interrupted extractions are marked and removed before this,
+            // so this is a version from before manifests were introduced
+            let manifest = Manifest::create(version_dir)?;
+            manifest.write(version_dir)?;
+            manifest
+        }
+    };
+
+    insert(version_dir, &manifest, full_name, version, app)
+}
+
+/// Migrates the cache in the background, then enforces the cache size limit.
+pub fn spawn_migration(app: &AppHandle) {
+    let app = app.to_owned();
+    tauri::async_runtime::spawn_blocking(move || {
+        match app.install_queue().try_pause() {
+            Some(_pause) => {
+                if let Err(err) = migrate(&app) {
+                    warn!("failed to migrate download cache: {:#}", err);
+                }
+            }
+            None => debug!("skipping cache migration while installing"),
+        }
+
+        super::gc::spawn_eviction(&app);
+    });
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoreStats {
+    pub object_count: u64,
+    /// Disk space taken up by the object store.
+    pub stored_size: u64,
+    /// Disk space the cached files would take up without deduplication.
+    pub linked_size: u64,
+    pub saved_size: u64,
+}
+
+pub fn stats(app: &AppHandle) -> Result<StoreStats> {
+    let stats = app.db().cache_object_stats()?;
+
+    Ok(StoreStats {
+        object_count: stats.object_count,
+        stored_size: stats.stored_size,
+        linked_size: stats.linked_size,
+        saved_size: stats.linked_size.saturating_sub(stats.stored_size),
+    })
+}
@@ -1,8 +1,18 @@
+use eyre::eyre;
 use itertools::Itertools;
 use tauri::{AppHandle, command};
 use uuid::Uuid;
 
-use super::{InstallOptions, ModInstall};
+use super::{
+    InstallOptions, ModInstall,
+    cache::{
+        gc::{self, CacheUsage, CacheVerifyReport},
+        store::StoreStats,
+    },
+    plan::InstallPlan,
+    rules::{self, InstallRule, InstallRules},
+    verify::{self, IntegrityReport, RepairSummary},
+};
 use crate::{
     profile::install::InstallResultExt,
     state::ManagerExt,
@@ -78,11 +88,12 @@ pub fn is_installing(package_uuid: Uuid, app: AppHandle) -> Result<bool> {
 #[command]
 pub async fn clear_download_cache(soft: bool, app: AppHandle) -> Result<u64> {
     if soft {
-        let paths = super::cache::prepare_soft_clear(app)?;
+        let entries = super::cache::prepare_soft_clear(&app)?;
 
-        let size = paths.iter().map(util::fs::get_directory_size).sum();
+        let size = entries.iter().map(|entry| entry.size).sum();
 
-        tauri::async_runtime::spawn_blocking(|| super::cache::do_soft_clear(paths)).await??;
+        tauri::async_runtime::spawn_blocking(move || super::cache::do_soft_clear(entries, &app))
+            .await??;
 
         Ok(size)
     } else {
@@ -91,11 +102,128 @@ pub async fn clear_download_cache(soft: bool, app: AppHandle) -> Result<u64> {
         let size = util::fs::get_directory_size(&path);
 
         tauri::async_runtime::spawn_blocking(|| super::cache::clear(path)).await??;
+        app.db().clear_cache_data()?;
 
         Ok(size)
     }
 }
 
+#[command]
+pub async fn get_cache_usage(app: AppHandle) -> Result<CacheUsage> {
+    let usage = tauri::async_runtime::spawn_blocking(move || gc::usage(&app)).await??;
+
+    Ok(usage)
+}
+
+#[command]
+pub fn get_cache_store_stats(app: AppHandle) -> Result<StoreStats> {
+    let stats = super::cache::store::stats(&app)?;
+
+    Ok(stats)
+}
+
+#[command]
+pub async fn verify_cache(app: AppHandle) -> Result<CacheVerifyReport> {
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        // corrupt versions are removed, so keep mods from being installed from them meanwhile
+        let Some(_pause) = app.install_queue().try_pause() else {
+            return Err(eyre!(
+                "cannot verify the cache while mods are being installed"
+            ));
+        };
+
+        gc::verify(&app)
+    })
+    .await??;
+
+    Ok(report)
+}
+
+#[command]
+pub async fn verify_profile(app: AppHandle) -> Result<IntegrityReport> {
+    let snapshot = {
+        let manager = app.lock_manager();
+        let prefs = app.lock_prefs();
+
+        manager.active_profile().verify_snapshot(&prefs)
+    };
+
+    let report = tauri::async_runtime::spawn_blocking(move || snapshot.verify()).await??;
+
+    Ok(report)
+}
+
+#[command]
+pub async fn repair_profile(app: AppHandle) -> Result<RepairSummary> {
+    let profile_id = app.lock_manager().active_profile().id;
+    let summary = verify::repair(profile_id, &app).await?;
+
+    Ok(summary)
+}
+
+#[command]
+pub fn get_install_rules(app: AppHandle) -> Result<InstallRules> {
+    let rules = app.lock_manager().active_profile().install_rules.clone();
+
+    Ok(rules)
+}
+
+#[command]
+pub fn set_game_install_rules(rules: Vec<InstallRule>, app: AppHandle) -> Result<()> {
+    rules::validate(&rules)?;
+
+    let mut manager = app.lock_manager();
+    let game = manager.active_game_mut();
+
+    for profile in &mut game.profiles {
+        profile.install_rules.game = rules.clone();
+    }
+
+    game.install_rules = rules;
+    game.save(&app)?;
+
+    Ok(())
+}
+
+#[command]
+pub fn set_profile_install_rules(rules: Vec<InstallRule>, app: AppHandle) -> Result<()> {
+    rules::validate(&rules)?;
+
+    let mut manager = app.lock_manager();
+    let profile = manager.active_profile_mut();
+
+    profile.install_rules.profile = rules;
+    profile.save(&app, true)?;
+
+    Ok(())
+}
+
+#[command]
+pub async fn reinstall_affected_mods(app: AppHandle) -> Result<usize> {
+    let profile_id = app.lock_manager().active_profile().id;
+    let count = rules::reinstall_affected(profile_id, &app).await?;
+
+    Ok(count)
+}
+
+#[command]
+pub fn plan_install(mods: Vec<ModId>, app: AppHandle) -> Result<InstallPlan> {
+    let manager = app.lock_manager();
+    let thunderstore = app.lock_thunderstore();
+    let prefs = app.lock_prefs();
+
+    let installs = mods
+        .into_iter()
+        .map(|id| ModInstall::try_from_id(id, &thunderstore))
+        .collect::<eyre::Result<Vec<_>>>()?;
+
+    let plan = manager
+        .active_profile()
+        .plan_install(installs, &thunderstore, &prefs)?;
+
+    Ok(plan)
+}
+
 #[command]
 pub fn get_download_size(mod_ref: ModId, app: AppHandle) -> Result<u64> {
     let prefs = app.lock_prefs();
@@ -6,6 +6,7 @@ use std::{
 };
 
 use eyre::{Context, Result};
+use serde::{Deserialize, Serialize};
 use tracing::{trace, warn};
 use walkdir::WalkDir;
 use zip::ZipArchive;
@@ -99,12 +100,55 @@ fn set_unix_mode(file: &zip::read::ZipFile, target_path: &Path) -> io::Result<()
     Ok(())
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum FileInstallMethod {
-    /// Use a hard link.
+    /// Use a hard link, falling back to [`FileInstallMethod::Reflink`]
+    /// if the profile is on a different filesystem than the cache.
+    #[default]
     Link,
+    /// Use a copy-on-write clone where the filesystem supports it (e.g. btrfs, XFS, APFS),
+    /// otherwise copy the file.
+    Reflink,
     /// Copy the file.
     Copy,
+    /// Use a symbolic link to the cached file, falling back to a hard link if that fails.
+    ///
+    /// The profile breaks if the cache is cleared, so this is only used if the user opts in.
+    Symlink,
+}
+
+impl FileInstallMethod {
+    pub(super) fn apply(self, src: &Path, target: &Path) -> io::Result<()> {
+        match self {
+            Self::Link => match fs::hard_link(src, target) {
+                Err(err) if err.kind() == io::ErrorKind::CrossesDevices => {
+                    trace!("cache is on another filesystem, falling back to reflink");
+                    Self::Reflink.apply(src, target)
+                }
+                res => res,
+            },
+            Self::Reflink => reflink_copy::reflink_or_copy(src, target).map(|_| ()),
+            Self::Copy => fs::copy(src, target).map(|_| ()),
+            Self::Symlink => symlink_file(src, target).or_else(|err| {
+                warn!(
+                    "failed to create symlink, falling back to hard link: {}",
+                    err
+                );
+                Self::Link.apply(src, target)
+            }),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn symlink_file(src: &Path, target: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(src, target)
+}
+
+#[cfg(windows)]
+fn symlink_file(src: &Path, target: &Path) -> io::Result<()> {
+    // requires developer mode or admin privileges
+    std::os::windows::fs::symlink_file(src, target)
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -121,7 +165,14 @@ pub enum ConflictResolution {
 ///
 /// `before_install` is called each time a file is encountered,
 /// with the file's relative path and whether the target file already exists.
-pub(super) fn install<F>(src: &Path, profile: &Profile, mut before_install: F) -> Result<()>
+/// Files it wants to link are installed with `link_method` instead, which is
+/// chosen by the user.
+pub(super) fn install<F>(
+    src: &Path,
+    profile: &Profile,
+    link_method: FileInstallMethod,
+    mut before_install: F,
+) -> Result<()>
 where
     F: FnMut(&Path, bool) -> Result<(FileInstallMethod, ConflictResolution)>,
 {
@@ -152,20 +203,23 @@ where
                 format!("failed to create directory {}", relative_path.display())
             })?;
         } else {
-            let target_exists = target.exists();
-            let (method, conflict) = before_install(relative_path, target_exists)?;
+            // don't follow symlinks, since they might be dangling
+            let target_exists = target.symlink_metadata().is_ok();
+            let (mut method, conflict) = before_install(relative_path, target_exists)?;
+
+            if let FileInstallMethod::Link = method {
+                method = link_method;
+            }
 
             if target_exists {
-                match (conflict, method) {
-                    (ConflictResolution::Skip, _) => {
+                match conflict {
+                    ConflictResolution::Skip => {
                         warn!("file already exists, skipping",);
                         continue;
                     }
-                    // fs::copy already overwrites the target, no need to remove it
-                    (ConflictResolution::Overwrite, FileInstallMethod::Copy) => {
-                        trace!("overwriting existing file");
-                    }
-                    (ConflictResolution::Overwrite, FileInstallMethod::Link) => {
+                    // the existing file might be linked to the cache, so writing
+                    // over it in place could modify the cached file as well
+                    ConflictResolution::Overwrite => {
                         trace!("removing existing file");
                         fs::remove_file(&target).with_context(|| {
                             format!(
@@ -178,18 +232,9 @@ where
             }
 
             trace!(?method, "installing file");
-            match method {
-                FileInstallMethod::Link => {
-                    fs::hard_link(entry.path(), target).with_context(|| {
-                        format!("failed to link file at {}", relative_path.display())
-                    })?;
-                }
-                FileInstallMethod::Copy => {
-                    fs::copy(entry.path(), target).with_context(|| {
-                        format!("failed to copy file at {}", relative_path.display())
-                    })?;
-                }
-            }
+            method.apply(entry.path(), &target).with_context(|| {
+                format!("failed to install file at {}", relative_path.display())
+            })?;
         }
     }
 
@@ -53,16 +53,26 @@ impl PackageInstaller for BepinexInstaller {
         })
     }
 
-    fn install(&mut self, src: &Path, _package_name: &str, profile: &Profile) -> Result<()> {
-        install::fs::install(src, profile, |relative_path, _| {
-            if relative_path.extension().is_some_and(|ext| ext == "cfg") {
-                Ok((FileInstallMethod::Copy, ConflictResolution::Skip))
+    fn install(
+        &mut self,
+        src: &Path,
+        _package_name: &str,
+        profile: &Profile,
+        link_method: FileInstallMethod,
+    ) -> Result<()> {
+        install::fs::install(src, profile, link_method, |relative_path, _| {
+            if self.is_mutable(relative_path) {
+                Ok((FileInstallMethod::Reflink, ConflictResolution::Skip))
             } else {
                 Ok((FileInstallMethod::Link, ConflictResolution::Overwrite))
             }
         })
     }
 
+    fn is_mutable(&self, relative_path: &Path) -> bool {
+        relative_path.extension().is_some_and(|ext| ext == "cfg")
+    }
+
     fn toggle(&mut self, enabled: bool, profile_mod: &ProfileMod, profile: &Profile) -> Result<()> {
         for file in scan(profile, &profile_mod.full_name())? {
             install::fs::toggle_file(file, enabled)?;
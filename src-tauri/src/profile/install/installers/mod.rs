@@ -20,7 +20,7 @@ pub use self::{
     extract::{ExtractInstaller, FlattenTopLevel},
     gd_weave::GDWeaveModInstaller,
     shimloader::ShimloaderInstaller,
-    subdir::{Subdir, SubdirInstaller},
+    subdir::{Subdir, SubdirInstaller, SubdirMode},
 };
 
 pub type PackageZip = ZipArchive<Cursor<Vec<u8>>>;
@@ -28,16 +28,35 @@ pub type PackageZip = ZipArchive<Cursor<Vec<u8>>>;
 pub trait PackageInstaller {
     fn extract(&mut self, archive: PackageZip, package_name: &str, dest: PathBuf) -> Result<()>;
 
-    fn install(&mut self, src: &Path, _package_name: &str, profile: &Profile) -> Result<()> {
-        super::fs::install(src, profile, |_, _| {
+    fn install(
+        &mut self,
+        src: &Path,
+        _package_name: &str,
+        profile: &Profile,
+        link_method: FileInstallMethod,
+    ) -> Result<()> {
+        super::fs::install(src, profile, link_method, |_, _| {
             Ok((FileInstallMethod::Link, ConflictResolution::Overwrite))
         })
     }
 
+    /// Whether the file at `relative_path` is expected to be modified after it's installed,
+    /// in which case it isn't linked to the cache or checked against it.
+    fn is_mutable(&self, _relative_path: &Path) -> bool {
+        false
+    }
+
     fn toggle(&mut self, enabled: bool, profile_mod: &ProfileMod, profile: &Profile) -> Result<()>;
     fn uninstall(&mut self, profile_mod: &ProfileMod, profile: &Profile) -> Result<()>;
 
     fn mod_dir(&self, _package_name: &str, _profile: &Profile) -> Option<PathBuf> {
         None
     }
+
+    /// Identifies the user-defined install rules that affect how packages are extracted.
+    ///
+    /// See [`super::rules::InstallRules::fingerprint`].
+    fn rules_fingerprint(&self) -> Option<String> {
+        None
+    }
 }
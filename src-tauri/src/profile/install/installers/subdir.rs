@@ -16,6 +16,7 @@ use crate::{
         install::{
             self,
             fs::{ConflictResolution, FileInstallMethod},
+            rules::{InstallRule, InstallRules},
         },
     },
     util::{self, fs::JsonStyle},
@@ -31,10 +32,12 @@ pub struct SubdirInstaller<'a> {
     default_subdir: Option<usize>,
     /// File paths that the installer should always ignore.
     ignored_files: &'a [&'a str],
+    /// User-defined rules, which take precedence over all other subdirs.
+    rules: Option<&'a InstallRules>,
 }
 
 /// A directory inside the profile where files will be placed into.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Subdir<'a> {
     /// The string which "triggers" the subdir. Must be a single path component.
@@ -122,6 +125,7 @@ impl<'a> SubdirInstaller<'a> {
             default_subdir: None,
             extra_subdirs: DEFAULT_EXTRA,
             ignored_files: DEFAULT_IGNORED,
+            rules: None,
         }
     }
 
@@ -140,12 +144,21 @@ impl<'a> SubdirInstaller<'a> {
         self
     }
 
-    fn subdirs(&'_ self) -> impl Iterator<Item = &'_ Subdir<'_>> {
-        self.extra_subdirs.iter().chain(self.subdirs.iter())
+    pub fn with_rules(mut self, rules: &'a InstallRules) -> Self {
+        self.rules = Some(rules);
+        self
+    }
+
+    fn subdirs(&'_ self) -> impl Iterator<Item = Subdir<'_>> {
+        self.rules
+            .into_iter()
+            .flat_map(|rules| rules.iter().map(InstallRule::as_subdir))
+            .chain(self.extra_subdirs.iter().copied())
+            .chain(self.subdirs.iter().copied())
     }
 
     /// Determines if a path component matches any subdir names/extensions.
-    fn match_subdir(&'_ self, name: &str) -> Option<&'_ Subdir<'_>> {
+    fn match_subdir(&'_ self, name: &str) -> Option<Subdir<'_>> {
         self.subdirs().find(|subdir| {
             util::cmp_ignore_case(subdir.name, name).is_eq()
                 || subdir
@@ -238,7 +251,7 @@ impl<'a> SubdirInstaller<'a> {
                 Some(Component::RootDir | Component::Prefix(_) | Component::CurDir) => continue,
                 // default when the whole path is exhausted
                 None => match self.default_subdir {
-                    Some(index) => break &self.subdirs[index],
+                    Some(index) => break self.subdirs[index],
                     None => return Ok(None),
                 },
             }
@@ -289,7 +302,7 @@ impl<'a> SubdirInstaller<'a> {
             // ex. relative_path: MyFolder/plugins/MyOtherFolder/Plugin.dll
             //    (with flatten): BepInEx/plugins/MyOtherFolder/Plugin.dll
             // (without flatten): BepInEx/plugins/MyFolder/MyOtherFolder/Plugin.dll
-            let components_to_add = self.handle_overlap(components, subdir, flatten);
+            let components_to_add = self.handle_overlap(components, &subdir, flatten);
             target.push(components_to_add);
         }
 
@@ -432,11 +445,17 @@ impl PackageInstaller for SubdirInstaller<'_> {
         })
     }
 
-    fn install(&mut self, src: &Path, package_name: &str, profile: &Profile) -> Result<()> {
+    fn install(
+        &mut self,
+        src: &Path,
+        package_name: &str,
+        profile: &Profile,
+        link_method: FileInstallMethod,
+    ) -> Result<()> {
         let mut state: Option<PackageStateHandle> = None;
         let mut profile_state: Option<ProfileStateHandle> = None;
 
-        install::fs::install(src, profile, |relative_path, exists| {
+        install::fs::install(src, profile, link_method, |relative_path, exists| {
             let Some(subdir) = self
                 .subdirs()
                 .find(|subdir| relative_path.starts_with(subdir.target))
@@ -448,8 +467,10 @@ impl PackageInstaller for SubdirInstaller<'_> {
                 return Ok((FileInstallMethod::Copy, ConflictResolution::Skip));
             };
 
+            // mutable files need their own data, but can still share
+            // blocks with the cache on copy-on-write filesystems
             let method = if subdir.mutable {
-                FileInstallMethod::Copy
+                FileInstallMethod::Reflink
             } else {
                 FileInstallMethod::Link
             };
@@ -497,6 +518,16 @@ impl PackageInstaller for SubdirInstaller<'_> {
         Ok(())
     }
 
+    fn rules_fingerprint(&self) -> Option<String> {
+        self.rules.and_then(InstallRules::fingerprint)
+    }
+
+    fn is_mutable(&self, relative_path: &Path) -> bool {
+        self.subdirs()
+            .find(|subdir| relative_path.starts_with(subdir.target))
+            .is_some_and(|subdir| subdir.mutable)
+    }
+
     fn toggle(&mut self, enabled: bool, profile_mod: &ProfileMod, profile: &Profile) -> Result<()> {
         self.scan_mod(profile_mod, profile, |path| {
             install::fs::toggle_any(path, enabled)
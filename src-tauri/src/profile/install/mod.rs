@@ -38,12 +38,16 @@ use crate::{
     thunderstore::{BorrowedMod, ModId, Thunderstore, VersionIdent},
 };
 
-mod cache;
+pub mod cache;
 pub mod commands;
 mod fs;
 mod installers;
+pub use fs::FileInstallMethod;
 pub use installers::*;
+pub mod plan;
 pub mod queue;
+pub mod rules;
+pub mod verify;
 
 type BeforeInstallHandler =
     Box<dyn Fn(&ModInstall, &mut Profile) -> Result<()> + 'static + Send + Sync>;
@@ -168,7 +172,10 @@ impl ModInstall {
         self.enabled
     }
 
-    fn insert_into(self, profile: &mut Profile) -> Result<()> {
+    /// Adds the mod to `profile`, as installed under the given install rules.
+    ///
+    /// `rules` should be [`None`] if the mod's installer doesn't use them.
+    fn insert_into(self, rules: Option<rules::InstallRules>, profile: &mut Profile) -> Result<()> {
         let ModInstall {
             id,
             ident,
@@ -183,7 +190,7 @@ impl ModInstall {
 
         let profile_mod = ProfileMod::new_at(
             install_time,
-            ProfileModKind::Thunderstore(ThunderstoreMod { ident, id }),
+            ProfileModKind::Thunderstore(ThunderstoreMod { ident, id, rules }),
         );
 
         match index {
@@ -262,7 +269,9 @@ fn total_download_size(
         .missing_deps(borrowed.dependencies(), thunderstore)
         .chain(iter::once(borrowed))
         .filter(|borrowed| {
-            !cache::path(borrowed.ident(), prefs).exists()
+            let rules = profile.rules_fingerprint(borrowed.ident().full_name());
+
+            !cache::manifest::is_complete(&cache::path(borrowed.ident(), rules.as_deref(), prefs))
                 && !queue.has_mod(borrowed.package.uuid, profile.id)
         })
         .map(|borrowed| borrowed.version.file_size)
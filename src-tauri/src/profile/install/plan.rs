@@ -0,0 +1,253 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    iter,
+};
+
+use eyre::{Context, Result};
+use itertools::Itertools;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::{
+    ModInstall,
+    cache::{self, manifest::Manifest},
+};
+use crate::{
+    prefs::Prefs,
+    profile::Profile,
+    thunderstore::{ModId, Thunderstore, VersionIdent},
+};
+
+/// Everything an install, import or sync pull would do, computed without changing anything.
+#[derive(Debug, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallPlan {
+    /// In the order they would be installed in.
+    pub installs: Vec<PlannedInstall>,
+    /// Mods that would be removed without being replaced by another version.
+    pub removals: Vec<PlannedMod>,
+    pub conflicts: Vec<FileConflict>,
+    /// Planned installs that aren't cached, so their files couldn't be checked for conflicts.
+    pub unchecked: Vec<String>,
+    pub download_size: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedMod {
+    pub uuid: Uuid,
+    pub full_name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum InstallReason {
+    Requested,
+    Dependency,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedInstall {
+    #[serde(flatten)]
+    pub package: PlannedMod,
+    pub reason: InstallReason,
+    /// The version currently installed, if the mod would be replaced.
+    pub previous_version: Option<String>,
+    pub cached: bool,
+    /// Zero if the mod is cached.
+    pub download_size: u64,
+}
+
+/// A file that would be installed by multiple mods.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileConflict {
+    pub path: String,
+    /// In installation order, so the last mod's file is the one that ends up in the profile.
+    pub mods: Vec<String>,
+}
+
+impl PlannedMod {
+    fn new(uuid: Uuid, ident: &VersionIdent) -> Self {
+        Self {
+            uuid,
+            full_name: ident.full_name().to_owned(),
+            version: ident.version().to_owned(),
+        }
+    }
+}
+
+impl Profile {
+    /// Plans installing `mods` along with their missing dependencies,
+    /// like [`super::queue::InstallQueue::install_with_deps`] does.
+    pub fn plan_install(
+        &self,
+        mods: Vec<ModInstall>,
+        thunderstore: &Thunderstore,
+        prefs: &Prefs,
+    ) -> Result<InstallPlan> {
+        let requested: HashSet<_> = mods.iter().map(ModInstall::uuid).collect();
+
+        let installs = mods
+            .into_iter()
+            .map(|install| {
+                let borrowed = install.id.borrow(thunderstore)?;
+
+                Ok(iter::once(install).chain(
+                    self.missing_deps(borrowed.dependencies(), thunderstore)
+                        .map(ModInstall::from),
+                ))
+            })
+            .flatten_ok()
+            .collect::<Result<Vec<_>>>()
+            .context("failed to resolve dependencies")?
+            .into_iter()
+            .unique_by(ModInstall::uuid)
+            .rev() // dependencies are installed first
+            .map(|install| {
+                let reason = match requested.contains(&install.uuid()) {
+                    true => InstallReason::Requested,
+                    false => InstallReason::Dependency,
+                };
+
+                (install, reason)
+            })
+            .collect();
+
+        Ok(build(Some(self), installs, Vec::new(), prefs))
+    }
+
+    /// Plans importing `installs` over this profile.
+    ///
+    /// If `merge` is false, all mods not in `installs` are removed.
+    /// Otherwise, only mods that are in `installs` with another version are.
+    pub fn plan_import(
+        &self,
+        installs: Vec<ModInstall>,
+        merge: bool,
+        prefs: &Prefs,
+    ) -> InstallPlan {
+        let current: HashMap<&ModId, &VersionIdent> = self
+            .thunderstore_mods()
+            .map(|(ts_mod, _)| (&ts_mod.id, &ts_mod.ident))
+            .collect();
+
+        let new_ids: HashSet<&ModId> = installs.iter().map(ModInstall::mod_id).collect();
+
+        let removals = current
+            .iter()
+            .filter(|(id, _)| match merge {
+                true => installs.iter().any(|install| {
+                    install.mod_id().package_uuid == id.package_uuid
+                        && install.mod_id().version_uuid != id.version_uuid
+                }),
+                false => !new_ids.contains(*id),
+            })
+            .map(|(id, ident)| PlannedMod::new(id.package_uuid, ident))
+            .collect();
+
+        let installs = installs
+            .into_iter()
+            .filter(|install| !current.contains_key(install.mod_id()))
+            .map(|install| (install, InstallReason::Requested))
+            .collect();
+
+        build(Some(self), installs, removals, prefs)
+    }
+}
+
+/// Plans importing `installs` into a new profile.
+pub fn plan_new_profile(installs: Vec<ModInstall>, prefs: &Prefs) -> InstallPlan {
+    let installs = installs
+        .into_iter()
+        .map(|install| (install, InstallReason::Requested))
+        .collect();
+
+    build(None, installs, Vec::new(), prefs)
+}
+
+fn build(
+    profile: Option<&Profile>,
+    installs: Vec<(ModInstall, InstallReason)>,
+    mut removals: Vec<PlannedMod>,
+    prefs: &Prefs,
+) -> InstallPlan {
+    let current: HashMap<Uuid, &VersionIdent> = profile
+        .into_iter()
+        .flat_map(|profile| profile.thunderstore_mods())
+        .map(|(ts_mod, _)| (ts_mod.id.package_uuid, &ts_mod.ident))
+        .collect();
+
+    let mut plan = InstallPlan::default();
+
+    // the rules each mod is extracted with, which determine its cached files
+    let rules_for = |ident: &VersionIdent| {
+        profile.and_then(|profile| profile.rules_fingerprint(ident.full_name()))
+    };
+
+    for (install, reason) in &installs {
+        let rules = rules_for(&install.ident);
+        let cached =
+            cache::manifest::is_complete(&cache::path(&install.ident, rules.as_deref(), prefs));
+        let download_size = if cached { 0 } else { install.file_size };
+
+        plan.download_size += download_size;
+        plan.installs.push(PlannedInstall {
+            package: PlannedMod::new(install.uuid(), &install.ident),
+            reason: *reason,
+            previous_version: current
+                .get(&install.uuid())
+                .map(|ident| ident.version().to_owned()),
+            cached,
+            download_size,
+        });
+    }
+
+    // mods that are replaced by another version are shown as version changes instead
+    let replaced: HashSet<Uuid> = installs.iter().map(|(install, _)| install.uuid()).collect();
+    removals.retain(|removal| !replaced.contains(&removal.uuid));
+
+    let removed: HashSet<Uuid> = removals.iter().map(|removal| removal.uuid).collect();
+    let remaining = profile
+        .into_iter()
+        .flat_map(|profile| profile.thunderstore_mods())
+        .map(|(ts_mod, _)| ts_mod)
+        .filter(|ts_mod| {
+            !removed.contains(&ts_mod.id.package_uuid)
+                && !replaced.contains(&ts_mod.id.package_uuid)
+        })
+        .map(|ts_mod| (&ts_mod.ident, ts_mod.rules_fingerprint(), false));
+
+    let planned = installs
+        .iter()
+        .map(|(install, _)| (&install.ident, rules_for(&install.ident), true));
+
+    let mut files: BTreeMap<String, Vec<(&VersionIdent, bool)>> = BTreeMap::new();
+
+    for (ident, rules, is_planned) in remaining.chain(planned) {
+        let Ok(manifest) = Manifest::read(&cache::path(ident, rules.as_deref(), prefs)) else {
+            if is_planned {
+                plan.unchecked.push(ident.to_string());
+            }
+            continue;
+        };
+
+        for path in manifest.files.into_keys() {
+            files.entry(path).or_default().push((ident, is_planned));
+        }
+    }
+
+    plan.conflicts = files
+        .into_iter()
+        .filter(|(_, mods)| mods.len() > 1 && mods.iter().any(|(_, is_planned)| *is_planned))
+        .map(|(path, mods)| FileConflict {
+            path,
+            mods: mods.iter().map(|(ident, _)| ident.to_string()).collect(),
+        })
+        .collect();
+
+    plan.removals = removals;
+    plan
+}
@@ -4,6 +4,7 @@ use std::{
     future::Future,
     io::Cursor,
     iter,
+    path::Path,
     sync::{
         Arc, Mutex, MutexGuard,
         atomic::{AtomicBool, Ordering},
@@ -17,17 +18,28 @@ use http_cache_reqwest::CacheMode;
 use itertools::Itertools;
 use serde::Serialize;
 use tauri::AppHandle;
-use tokio::sync::{Notify, futures::Notified, oneshot};
+use tokio::sync::{Notify, RwLock, RwLockWriteGuard, futures::Notified, oneshot};
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 use zip::ZipArchive;
 
-use crate::{logger, state::ManagerExt, thunderstore::VersionIdent, util::error::IoResultExt};
+use crate::{
+    game::Game,
+    logger,
+    state::ManagerExt,
+    thunderstore::{Backend, VersionIdent, directory},
+    util::error::IoResultExt,
+};
 
-use super::{CancelBehavior, InstallError, InstallOptions, InstallResult, ModInstall};
+use super::{
+    CancelBehavior, InstallError, InstallOptions, InstallResult, ModInstall,
+    cache::manifest::{self, Manifest},
+};
 
 pub struct InstallQueue {
     state: Mutex<State>,
+    /// Held for reading while batches are installed, see [`InstallQueue::try_pause`].
+    cache_lock: RwLock<()>,
     /// Notified when a batch is pushed to the queue.
     notify_push: Notify,
     /// Notified when all batches have been completed.
@@ -48,6 +60,7 @@ impl InstallQueue {
 
         let this = Arc::new(Self {
             state: Mutex::new(State::default()),
+            cache_lock: RwLock::new(()),
             notify_push: Notify::new(),
             notify_empty: Notify::new(),
             cancel,
@@ -66,6 +79,14 @@ impl InstallQueue {
         self.cancel.store(true, Ordering::SeqCst);
     }
 
+    /// Holds off installing mods until the returned guard is dropped,
+    /// so that the cache can be modified without pulling files out from under an install.
+    ///
+    /// Returns `None` if mods are currently being installed.
+    pub fn try_pause(&self) -> Option<RwLockWriteGuard<'_, ()>> {
+        self.cache_lock.try_write().ok()
+    }
+
     pub fn lock(&'_ self) -> InstallQueueLock<'_> {
         InstallQueueLock {
             state: self.state.lock().unwrap(),
@@ -289,6 +310,8 @@ async fn handle_queue(queue: Arc<InstallQueue>, app: AppHandle) {
         emit(InstallEvent::Show, &app);
 
         loop {
+            // wait for the cache to be released if it's being cleaned up
+            let _cache = queue.cache_lock.read().await;
             let batch = queue.lock().pop_next();
 
             match batch {
@@ -307,6 +330,8 @@ async fn handle_queue(queue: Arc<InstallQueue>, app: AppHandle) {
             .cache_mods(&app.lock_thunderstore(), &app.lock_prefs())
             .ok();
 
+        super::cache::gc::spawn_eviction(&app);
+
         queue.notify_empty.notify_waiters();
     }
 }
@@ -417,9 +442,25 @@ enum CacheStatus {
 fn try_cache_install(batch: &InstallBatch, index: usize, app: &AppHandle) -> Result<CacheStatus> {
     let install = &batch.mods[index];
 
-    let cache_path = super::cache::path(&install.ident, &app.lock_prefs());
+    let mut manager = app.lock_manager();
+
+    let (game, profile) = manager.profile_by_id_mut(batch.profile_id)?;
+    let package_name = install.ident.full_name();
+
+    // the cached files are laid out according to the install rules they were extracted with
+    let rules = profile.rules_fingerprint(package_name);
+    let cache_path = super::cache::path(&install.ident, rules.as_deref(), &app.lock_prefs());
 
-    if !cache_path.exists() {
+    if !super::cache::manifest::is_complete(&cache_path) {
+        return Ok(CacheStatus::Miss);
+    }
+
+    // versions cached before they were kept apart by rules may still have been extracted with some
+    if Manifest::read(&cache_path)?.rules != rules {
+        debug!(
+            ident = %install.ident,
+            "cached version was extracted with different install rules"
+        );
         return Ok(CacheStatus::Miss);
     }
 
@@ -428,9 +469,6 @@ fn try_cache_install(batch: &InstallBatch, index: usize, app: &AppHandle) -> Res
         app,
     );
 
-    let mut manager = app.lock_manager();
-
-    let (game, profile) = manager.profile_by_id_mut(batch.profile_id)?;
     if let Some(callback) = &batch.options.before_install {
         callback(install, profile)?;
     }
@@ -440,11 +478,16 @@ fn try_cache_install(batch: &InstallBatch, index: usize, app: &AppHandle) -> Res
         "cache hit, installing from cache"
     );
 
-    let package_name = install.ident.full_name();
-    let mut installer = game.mod_loader.installer_for(package_name);
-    installer.install(&cache_path, package_name, profile)?;
+    profile.package_installer(package_name).install(
+        &cache_path,
+        package_name,
+        profile,
+        app.lock_prefs().install_method,
+    )?;
 
-    install.clone().insert_into(profile)?;
+    let install_rules = rules.is_some().then(|| profile.install_rules.clone());
+    install.clone().insert_into(install_rules, profile)?;
+    touch_cache(install, rules.as_deref(), game, app);
 
     profile.save(app, true)?;
 
@@ -470,7 +513,13 @@ async fn download(
         app,
     );
 
-    let url = install.id.backend.download_url(&install.ident);
+    let backend = install.id.backend;
+
+    if let Some(dir) = backend.directory() {
+        return read_from_directory(install, &dir, app).await;
+    }
+
+    let url = backend.download_url(&install.ident)?;
 
     debug!(
         ident = %install.ident,
@@ -487,7 +536,7 @@ async fn download(
     let mut backoff = INITIAL_BACKOFF;
 
     loop {
-        match try_download(&mut response, &url, cancel, options, app).await {
+        match try_download(&mut response, &url, backend, cancel, options, app).await {
             Ok(()) => break Ok(response),
             Err(InstallError::Cancelled) => return Err(InstallError::Cancelled),
             Err(InstallError::Error(err)) => {
@@ -516,18 +565,44 @@ async fn download(
     }
 }
 
+/// Reads a package from a local repository instead of downloading it.
+async fn read_from_directory(
+    install: &ModInstall,
+    dir: &Path,
+    app: &AppHandle,
+) -> InstallResult<Vec<u8>> {
+    let path = directory::package_path(dir, &install.ident);
+
+    // packages can be large, so don't block the async runtime while reading them
+    let bytes = tauri::async_runtime::spawn_blocking(move || {
+        fs::read(&path).fs_context("reading package", &path)
+    })
+    .await
+    .map_err(eyre::Report::from)??;
+
+    emit(
+        InstallEvent::AddProgress {
+            mods: 0,
+            bytes: bytes.len() as i64,
+        },
+        app,
+    );
+
+    Ok(bytes)
+}
+
 async fn try_download(
     buf: &mut Vec<u8>,
     url: &str,
+    backend: Backend,
     cancel: &AtomicBool,
     options: &InstallOptions,
     app: &AppHandle,
 ) -> InstallResult<()> {
     const UPDATE_DELAY: Duration = Duration::from_millis(100);
 
-    let mut stream = app
-        .http()
-        .get(url)
+    let mut stream = backend
+        .authorize(app.http().get(url))
         .with_extension(CacheMode::NoStore)
         .send()
         .await
@@ -579,16 +654,20 @@ fn install_from_download(
     let manager = app.lock_manager();
 
     let install = &batch.mods[index];
-
-    let cache_path = super::cache::path(&install.ident, &app.lock_prefs());
     let package_name = install.ident.full_name();
 
-    let (game, _) = manager.profile_by_id(batch.profile_id)?;
+    let (game, profile) = manager.profile_by_id(batch.profile_id)?;
+    let install_rules = profile.install_rules.clone();
     drop(manager);
 
-    fs::create_dir_all(&cache_path).fs_context("creating mod cache dir", &cache_path)?;
+    let mut installer = game.mod_loader.installer_for(package_name, &install_rules);
+    let rules = installer.rules_fingerprint();
+    let cache_path = super::cache::path(&install.ident, rules.as_deref(), &app.lock_prefs());
 
-    let mut installer = game.mod_loader.installer_for(package_name);
+    // clear out any leftovers from an interrupted extraction
+    manifest::remove_version(&cache_path)?;
+    manifest::begin_extract(&cache_path)?;
+    fs::create_dir_all(&cache_path).fs_context("creating mod cache dir", &cache_path)?;
 
     emit(
         InstallEvent::set_task(&install.ident, InstallTask::Extract),
@@ -597,18 +676,37 @@ fn install_from_download(
 
     let archive = ZipArchive::new(Cursor::new(data)).context("failed to open archive")?;
 
-    installer
+    let mut manifest = installer
         .extract(archive, package_name, cache_path.clone())
+        .context("error while extracting")
+        .and_then(|_| Manifest::create(&cache_path).context("failed to create cache manifest"))
         .inspect_err(|_| {
             // the cached mod is probably in an invalid state, so remove it
-            fs::remove_dir_all(&cache_path).unwrap_or_else(|err| {
+            manifest::remove_version(&cache_path).unwrap_or_else(|err| {
                 warn!(
                     "failed to clean up after failed extraction of {}: {:#}",
                     install.ident, err
                 );
             });
-        })
-        .context("error while extracting")?;
+        })?;
+
+    super::cache::store::insert(
+        &cache_path,
+        &manifest,
+        package_name,
+        &super::cache::dir_name(install.ident.version(), rules.as_deref()),
+        app,
+    )
+    .unwrap_or_else(|err| {
+        warn!(
+            "failed to move {} into the object store: {:#}",
+            install.ident, err
+        )
+    });
+
+    // mark the cached version as complete
+    manifest.rules = rules.clone();
+    manifest.write(&cache_path)?;
 
     check_cancel(cancel, &batch.options)?;
 
@@ -629,8 +727,16 @@ fn install_from_download(
         "installing mod"
     );
 
-    installer.install(&cache_path, package_name, profile)?;
-    install.clone().insert_into(profile)?;
+    installer.install(
+        &cache_path,
+        package_name,
+        profile,
+        app.lock_prefs().install_method,
+    )?;
+    install
+        .clone()
+        .insert_into(rules.is_some().then_some(install_rules.clone()), profile)?;
+    touch_cache(install, rules.as_deref(), game, app);
 
     profile.save(app, true)?;
 
@@ -639,6 +745,16 @@ fn install_from_download(
     Ok(())
 }
 
+fn touch_cache(install: &ModInstall, rules: Option<&str>, game: Game, app: &AppHandle) {
+    app.db()
+        .touch_cache_entry(
+            install.ident.full_name(),
+            &super::cache::dir_name(install.ident.version(), rules),
+            &game.slug,
+        )
+        .unwrap_or_else(|err| warn!("failed to record cache usage: {:#}", err));
+}
+
 /// Events sent to the frontend to keep track of installation progress.
 #[derive(Debug, Serialize, Clone)]
 #[serde(rename_all = "camelCase", tag = "type")]
@@ -0,0 +1,284 @@
+//! User-defined install rules, which extend the mod loader's built-in [`Subdir`]s
+//! for packages with non-standard layouts.
+//!
+//! Rules can be defined for a whole game or a single profile. Since the cache stores
+//! packages as they were extracted, versions extracted under rules are cached separately
+//! for each set of rules (see [`InstallRules::fingerprint`]), and installed mods remember
+//! which rules they were installed with.
+
+use std::path::Path;
+
+use eyre::{Result, ensure};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+use super::{InstallOptions, InstallResultExt, ModInstall, Subdir, SubdirMode};
+use crate::{
+    profile::{Profile, ThunderstoreMod},
+    state::ManagerExt,
+    util,
+};
+
+/// An owned version of [`Subdir`], as defined by the user.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallRule {
+    pub name: String,
+    pub target: String,
+    #[serde(default)]
+    pub mode: SubdirMode,
+    #[serde(default)]
+    pub mutable: bool,
+    #[serde(default)]
+    pub extension: Option<String>,
+}
+
+impl InstallRule {
+    pub fn as_subdir(&self) -> Subdir<'_> {
+        Subdir {
+            name: &self.name,
+            target: &self.target,
+            mode: self.mode,
+            mutable: self.mutable,
+            extension: self.extension.as_deref().filter(|ext| !ext.is_empty()),
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        ensure!(
+            !self.name.is_empty() && !self.name.contains(['/', '\\']),
+            "rule name '{}' must be a single path component",
+            self.name
+        );
+
+        ensure!(
+            !self.target.is_empty()
+                && !self.target.contains('\\')
+                && util::fs::is_enclosed(Path::new(&self.target)),
+            "rule target '{}' must be a relative path inside of the profile, separated by forward slashes",
+            self.target
+        );
+
+        Ok(())
+    }
+}
+
+/// The install rules that apply to a profile.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallRules {
+    /// Rules shared by every profile of the game.
+    ///
+    /// These are stored on the game and copied into each of its profiles.
+    pub game: Vec<InstallRule>,
+    /// Rules for this profile only, which take precedence over the game's.
+    pub profile: Vec<InstallRule>,
+}
+
+impl InstallRules {
+    pub fn iter(&self) -> impl Iterator<Item = &InstallRule> {
+        self.profile.iter().chain(&self.game)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.game.is_empty() && self.profile.is_empty()
+    }
+
+    /// Identifies this set of rules, or [`None`] if there are no rules.
+    ///
+    /// Packages extracted with different fingerprints may have different layouts.
+    pub fn fingerprint(&self) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let json = serde_json::to_vec(&self.iter().collect::<Vec<_>>())
+            .expect("install rules should always be serializable");
+
+        Some(blake3::hash(&json).to_hex()[..16].to_owned())
+    }
+}
+
+pub fn validate(rules: &[InstallRule]) -> Result<()> {
+    rules.iter().try_for_each(InstallRule::validate)
+}
+
+impl ThunderstoreMod {
+    /// Fingerprint of the install rules the mod was installed with.
+    pub fn rules_fingerprint(&self) -> Option<String> {
+        self.rules.as_ref().and_then(InstallRules::fingerprint)
+    }
+}
+
+impl Profile {
+    /// Fingerprint of the install rules that apply to `package_name` in this profile.
+    ///
+    /// This is [`None`] for packages whose installer doesn't use the rules.
+    pub(super) fn rules_fingerprint(&self, package_name: &str) -> Option<String> {
+        self.package_installer(package_name).rules_fingerprint()
+    }
+
+    /// Finds the installed mods that were installed with
+    /// different install rules than the profile currently has.
+    fn mods_with_outdated_rules(&self) -> Vec<usize> {
+        self.mods
+            .iter()
+            .enumerate()
+            .filter_map(|(index, profile_mod)| {
+                let (ts_mod, _) = profile_mod.as_thunderstore()?;
+                let fingerprint = self.rules_fingerprint(ts_mod.ident.full_name());
+
+                (ts_mod.rules_fingerprint() != fingerprint).then_some(index)
+            })
+            .collect()
+    }
+}
+
+/// Reinstalls the mods in a profile that were installed under different install rules,
+/// so their files are placed according to the current ones.
+///
+/// Returns the number of mods that are reinstalled.
+pub async fn reinstall_affected(profile_id: i64, app: &AppHandle) -> Result<usize> {
+    let installs = {
+        let manager = app.lock_manager();
+        let thunderstore = app.lock_thunderstore();
+
+        let (_, profile) = manager.profile_by_id(profile_id)?;
+
+        profile
+            .mods_with_outdated_rules()
+            .into_iter()
+            .filter_map(|index| {
+                let profile_mod = &profile.mods[index];
+                let (ts_mod, enabled) = profile_mod.as_thunderstore()?;
+
+                match ModInstall::try_from_id(ts_mod.id.clone(), &thunderstore) {
+                    Ok(install) => Some(
+                        install
+                            .with_state(enabled)
+                            .with_index(index)
+                            .with_time(profile_mod.install_time),
+                    ),
+                    Err(err) => {
+                        warn!("cannot reinstall {}: {:#}", ts_mod.ident, err);
+                        None
+                    }
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let count = installs.len();
+    info!("reinstalling {} mods after install rules changed", count);
+
+    if !installs.is_empty() {
+        // versions that aren't cached under the current rules yet are downloaded and extracted again
+        app.install_queue()
+            .install(
+                installs,
+                profile_id,
+                InstallOptions::default()
+                    .cancel_individually()
+                    .before_install(Box::new(|install, profile| {
+                        profile.force_remove_mod(install.uuid())
+                    })),
+                app,
+            )
+            .await
+            .ignore_cancel()?;
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        fs,
+    };
+
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        config::ConfigCache,
+        game,
+        profile::{ProfileMod, ProfileModKind},
+        thunderstore::{ModId, VersionIdent},
+    };
+
+    fn rule(target: &str) -> InstallRule {
+        InstallRule {
+            name: "Assets".to_owned(),
+            target: target.to_owned(),
+            mode: SubdirMode::Separate,
+            mutable: false,
+            extension: None,
+        }
+    }
+
+    #[test]
+    fn uninstall_with_installed_rules() {
+        let temp = tempfile::tempdir().unwrap();
+        let cache_path = temp.path().join("cache");
+        let package_name = "Owner-Mod";
+
+        let cached_file = cache_path.join("BepInEx/assets").join(package_name);
+        fs::create_dir_all(&cached_file).unwrap();
+        fs::write(cached_file.join("asset.bundle"), "data").unwrap();
+
+        let mut profile = Profile {
+            id: 1,
+            name: "Default".to_owned(),
+            path: temp.path().join("profile"),
+            mods: Vec::new(),
+            game: game::from_slug("repo").unwrap(),
+            ignored_version_updates: HashSet::new(),
+            ignored_package_updates: HashSet::new(),
+            config_cache: ConfigCache::default(),
+            linked_config: HashMap::new(),
+            modpack: None,
+            sync: None,
+            custom_args: String::new(),
+            missing: false,
+            install_rules: InstallRules {
+                game: Vec::new(),
+                profile: vec![rule("BepInEx/assets")],
+            },
+        };
+
+        profile
+            .package_installer(package_name)
+            .install(&cache_path, package_name, &profile, Default::default())
+            .unwrap();
+
+        let installed_dir = profile.path.join("BepInEx/assets").join(package_name);
+        assert!(installed_dir.join("asset.bundle").exists());
+
+        let uuid = Uuid::new_v4();
+        profile.mods.push(ProfileMod {
+            enabled: true,
+            install_time: Utc::now(),
+            kind: ProfileModKind::Thunderstore(ThunderstoreMod {
+                ident: VersionIdent::new("Owner", "Mod", "1.0.0"),
+                id: ModId {
+                    package_uuid: uuid,
+                    version_uuid: Uuid::new_v4(),
+                    backend: Default::default(),
+                },
+                rules: Some(profile.install_rules.clone()),
+            }),
+        });
+
+        profile.install_rules.profile = vec![rule("BepInEx/other")];
+        assert_eq!(profile.mods_with_outdated_rules(), vec![0]);
+
+        profile.force_remove_mod(uuid).unwrap();
+
+        assert!(!installed_dir.exists());
+        assert!(profile.mods.is_empty());
+    }
+}
@@ -0,0 +1,475 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use eyre::{Context, Result, bail};
+use itertools::Itertools;
+use serde::Serialize;
+use tauri::AppHandle;
+use tracing::{info, warn};
+use uuid::Uuid;
+use walkdir::WalkDir;
+
+use super::{
+    FileInstallMethod, InstallOptions, InstallResultExt, ModInstall, PackageInstaller,
+    cache::{
+        self,
+        manifest::{self, Manifest},
+    },
+    rules::InstallRules,
+};
+use crate::{
+    game::Game,
+    prefs::Prefs,
+    profile::Profile,
+    state::ManagerExt,
+    thunderstore::VersionIdent,
+    util::{self, error::IoResultExt},
+};
+
+/// Problems found with the files of an installed mod.
+///
+/// Paths are relative to the profile directory and exclude the `.old` extension of disabled mods.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModIntegrity {
+    pub uuid: Uuid,
+    pub full_name: String,
+    pub version: String,
+    pub missing: Vec<PathBuf>,
+    pub modified: Vec<PathBuf>,
+    /// Files in the mod's own directory that weren't installed from the package.
+    ///
+    /// These are reported, but never removed, since mods may create files there themselves.
+    pub extra: Vec<PathBuf>,
+    /// Whether the cached files are damaged too, so the mod has to be downloaded again.
+    pub needs_download: bool,
+}
+
+impl ModIntegrity {
+    /// Whether none of the mod's files are missing or modified.
+    ///
+    /// Extra files don't count, since mods may create files in their own directory.
+    fn is_intact(&self) -> bool {
+        self.missing.is_empty() && self.modified.is_empty() && !self.needs_download
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    pub checked: usize,
+    /// Mods with missing or modified files, or extra files in their directory.
+    pub mods: Vec<ModIntegrity>,
+    /// Mods that couldn't be checked because they aren't in the cache.
+    pub unverified: Vec<String>,
+}
+
+/// A file that a mod installed into the profile.
+struct InstalledFile {
+    relative_path: PathBuf,
+    cache_path: PathBuf,
+    target: PathBuf,
+}
+
+/// A copy of the profile's state needed to verify its mods, so the
+/// files can be hashed without holding on to the manager.
+pub struct VerifySnapshot {
+    profile_dir: PathBuf,
+    game: Game,
+    mods: Vec<ModSnapshot>,
+}
+
+struct ModSnapshot {
+    uuid: Uuid,
+    ident: VersionIdent,
+    enabled: bool,
+    cache_path: PathBuf,
+    mod_dir: Option<PathBuf>,
+    /// The install rules the mod was installed with.
+    rules: InstallRules,
+}
+
+impl Profile {
+    pub fn verify_snapshot(&self, prefs: &Prefs) -> VerifySnapshot {
+        let mods = self
+            .mods
+            .iter()
+            // local mods aren't cached
+            .filter_map(|profile_mod| {
+                let (ts_mod, enabled) = profile_mod.as_thunderstore()?;
+
+                Some(ModSnapshot {
+                    uuid: ts_mod.id.package_uuid,
+                    ident: ts_mod.ident.clone(),
+                    enabled,
+                    cache_path: cache::path(
+                        &ts_mod.ident,
+                        ts_mod.rules_fingerprint().as_deref(),
+                        prefs,
+                    ),
+                    mod_dir: self
+                        .installer_for(profile_mod)
+                        .mod_dir(ts_mod.ident.full_name(), self),
+                    rules: ts_mod.rules.clone().unwrap_or_default(),
+                })
+            })
+            .collect();
+
+        VerifySnapshot {
+            profile_dir: self.path.clone(),
+            game: self.game,
+            mods,
+        }
+    }
+}
+
+impl VerifySnapshot {
+    fn installer<'a>(&self, snapshot: &'a ModSnapshot) -> Box<dyn PackageInstaller + 'a> {
+        self.game
+            .mod_loader
+            .installer_for(snapshot.ident.full_name(), &snapshot.rules)
+    }
+
+    /// Compares the files of every installed mod against the mod's cached files.
+    ///
+    /// Files in mutable directories (such as configs) are only checked for existence.
+    /// If multiple mods install the same file, it's only checked against the one that
+    /// was installed last, since it has overwritten the others.
+    pub fn verify(&self) -> Result<IntegrityReport> {
+        let mut unverified = Vec::new();
+        let mut installed = Vec::new();
+
+        for snapshot in &self.mods {
+            // half-extracted versions can't be trusted either
+            if !manifest::is_complete(&snapshot.cache_path) {
+                unverified.push(snapshot.ident.to_string());
+                continue;
+            }
+
+            let files = self.installed_files(&snapshot.cache_path, snapshot.enabled)?;
+            installed.push((snapshot, files));
+        }
+
+        let mut owners = HashMap::new();
+        for (snapshot, files) in &installed {
+            for file in files {
+                owners.insert(file.target.clone(), snapshot.uuid);
+            }
+        }
+
+        let mods = installed
+            .iter()
+            .map(|(snapshot, files)| {
+                let files = files
+                    .iter()
+                    .filter(|file| owners.get(&file.target) == Some(&snapshot.uuid))
+                    .collect_vec();
+
+                self.verify_mod(snapshot, &files)
+            })
+            .filter_ok(|integrity| !integrity.is_intact() || !integrity.extra.is_empty())
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(IntegrityReport {
+            checked: installed.len(),
+            mods,
+            unverified,
+        })
+    }
+
+    fn installed_files(&self, cache_path: &Path, enabled: bool) -> Result<Vec<InstalledFile>> {
+        let mut files = Vec::new();
+
+        for entry in WalkDir::new(cache_path) {
+            let entry = entry.context("failed to read cached mod")?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let relative_path = entry
+                .path()
+                .strip_prefix(cache_path)
+                .expect("WalkDir should only return full paths inside of the root")
+                .to_path_buf();
+
+            let mut target = self.profile_dir.join(&relative_path);
+            if !enabled {
+                target.add_extension("old");
+            }
+
+            files.push(InstalledFile {
+                relative_path,
+                cache_path: entry.into_path(),
+                target,
+            });
+        }
+
+        Ok(files)
+    }
+
+    fn verify_mod(&self, snapshot: &ModSnapshot, files: &[&InstalledFile]) -> Result<ModIntegrity> {
+        let full_name = snapshot.ident.full_name();
+        let installer = self.installer(snapshot);
+
+        // versions extracted before manifests were introduced have none
+        let recorded: HashMap<_, _> = Manifest::read(&snapshot.cache_path)
+            .map(|manifest| {
+                manifest
+                    .files
+                    .into_iter()
+                    .map(|(key, file)| (key, file.hash))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut integrity = ModIntegrity {
+            uuid: snapshot.uuid,
+            full_name: full_name.to_owned(),
+            version: snapshot.ident.version().to_owned(),
+            missing: Vec::new(),
+            modified: Vec::new(),
+            extra: Vec::new(),
+            needs_download: false,
+        };
+
+        for file in files {
+            let recorded_hash = recorded.get(&manifest::key(&file.relative_path));
+
+            // this follows symlinks, so dangling ones count as missing
+            let intact = if !file.target.exists() {
+                integrity.missing.push(file.relative_path.clone());
+                false
+            } else if installer.is_mutable(&file.relative_path) {
+                true
+            } else {
+                let actual = util::fs::checksum(&file.target)
+                    .fs_context("hashing installed file", &file.target)?;
+
+                let expected = match recorded_hash {
+                    Some(hash) => hash.clone(),
+                    None => util::fs::checksum(&file.cache_path)
+                        .fs_context("hashing cached file", &file.cache_path)?
+                        .to_hex()
+                        .to_string(),
+                };
+
+                let intact = actual.to_hex().as_str() == expected;
+                if !intact {
+                    integrity.modified.push(file.relative_path.clone());
+                }
+
+                intact
+            };
+
+            // files that are hard linked to the cache get modified along with it,
+            // in which case it can't be used to repair the profile
+            if !intact
+                && let Some(hash) = recorded_hash
+                && util::fs::checksum(&file.cache_path)
+                    .map_or(true, |cached| cached.to_hex().as_str() != hash)
+            {
+                integrity.needs_download = true;
+            }
+        }
+
+        if let Some(mod_dir) = &snapshot.mod_dir {
+            let expected: HashSet<_> = files.iter().map(|file| &file.target).collect();
+
+            integrity.extra = WalkDir::new(mod_dir)
+                .into_iter()
+                .filter_map(Result::ok)
+                .filter(|entry| !entry.file_type().is_dir())
+                .filter(|entry| !expected.contains(&entry.path().to_path_buf()))
+                .filter_map(|entry| {
+                    entry
+                        .path()
+                        .strip_prefix(&self.profile_dir)
+                        .ok()
+                        .map(Path::to_path_buf)
+                })
+                .collect();
+        }
+
+        Ok(integrity)
+    }
+
+    /// Reinstalls the missing and modified files of the mods in `report` from the cache.
+    ///
+    /// Returns the number of files that were repaired.
+    fn relink_files(
+        &self,
+        report: &IntegrityReport,
+        install_method: FileInstallMethod,
+    ) -> Result<usize> {
+        let mut count = 0;
+
+        for integrity in report
+            .mods
+            .iter()
+            .filter(|integrity| !integrity.needs_download)
+        {
+            let Some(snapshot) = self
+                .mods
+                .iter()
+                .find(|snapshot| snapshot.uuid == integrity.uuid)
+            else {
+                continue;
+            };
+
+            let installer = self.installer(snapshot);
+
+            for relative_path in integrity.missing.iter().chain(&integrity.modified) {
+                let mut target = self.profile_dir.join(relative_path);
+                if !snapshot.enabled {
+                    target.add_extension("old");
+                }
+
+                if target.symlink_metadata().is_ok() {
+                    fs::remove_file(&target).fs_context("removing damaged file", &target)?;
+                } else if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent).fs_context("creating directory", parent)?;
+                }
+
+                let method = match installer.is_mutable(relative_path) {
+                    true => FileInstallMethod::Reflink,
+                    false => install_method,
+                };
+
+                method
+                    .apply(&snapshot.cache_path.join(relative_path), &target)
+                    .fs_context("reinstalling file", &target)?;
+
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairSummary {
+    pub repaired_files: usize,
+    pub redownloaded_mods: usize,
+}
+
+/// Verifies a profile and repairs any missing or modified files, either by reinstalling
+/// them from the cache or by downloading the mod again if the cache is damaged as well.
+pub async fn repair(profile_id: i64, app: &AppHandle) -> Result<RepairSummary> {
+    let (snapshot, install_method) = {
+        let manager = app.lock_manager();
+        let prefs = app.lock_prefs();
+
+        let (_, profile) = manager.profile_by_id(profile_id)?;
+        (profile.verify_snapshot(&prefs), prefs.install_method)
+    };
+
+    // hashing the files may take a while, so don't block the rest of the app meanwhile
+    let (report, repaired_files) = tauri::async_runtime::spawn_blocking(move || {
+        let report = snapshot.verify()?;
+        let repaired_files = snapshot.relink_files(&report, install_method)?;
+
+        Ok::<_, eyre::Report>((report, repaired_files))
+    })
+    .await??;
+
+    let (installs, removed) = {
+        let prefs = app.lock_prefs();
+        let manager = app.lock_manager();
+        let thunderstore = app.lock_thunderstore();
+
+        let (_, profile) = manager.profile_by_id(profile_id)?;
+
+        let mut installs = Vec::new();
+        let mut removed = Vec::new();
+        for integrity in report
+            .mods
+            .iter()
+            .filter(|integrity| integrity.needs_download)
+        {
+            // the mod might have been removed while verifying
+            let Ok(index) = profile.index_of(integrity.uuid) else {
+                continue;
+            };
+
+            let profile_mod = &profile.mods[index];
+            let Some((ts_mod, enabled)) = profile_mod.as_thunderstore() else {
+                continue;
+            };
+
+            let install = match ModInstall::try_from_id(ts_mod.id.clone(), &thunderstore) {
+                Ok(install) => install
+                    .with_state(enabled)
+                    .with_index(index)
+                    .with_time(profile_mod.install_time),
+                Err(err) => {
+                    warn!("cannot redownload {}: {:#}", ts_mod.ident, err);
+                    continue;
+                }
+            };
+
+            let rules = ts_mod.rules_fingerprint();
+            removed.push((
+                cache::path(&ts_mod.ident, rules.as_deref(), &prefs),
+                ts_mod.ident.full_name().to_owned(),
+                cache::dir_name(ts_mod.ident.version(), rules.as_deref()),
+            ));
+            installs.push(install);
+        }
+
+        (installs, removed)
+    };
+
+    // make sure the damaged files aren't reused
+    if !removed.is_empty() {
+        // keep mods from being installed from the versions while they're removed
+        let Some(_pause) = app.install_queue().try_pause() else {
+            bail!("cannot repair the profile while mods are being installed");
+        };
+
+        for (cache_path, _, _) in &removed {
+            manifest::remove_version(cache_path)?;
+        }
+
+        let versions = removed
+            .iter()
+            .map(|(_, full_name, dir_name)| (full_name.as_str(), dir_name.as_str()));
+
+        app.db().delete_cache_usage(versions.clone())?;
+        cache::store::remove(versions, app)?;
+    }
+
+    let redownloaded_mods = installs.len();
+
+    info!(
+        repaired_files,
+        redownloaded_mods, "repairing profile {}", profile_id
+    );
+
+    if !installs.is_empty() {
+        app.install_queue()
+            .install(
+                installs,
+                profile_id,
+                InstallOptions::default()
+                    .cancel_individually()
+                    .before_install(Box::new(|install, profile| {
+                        profile
+                            .force_remove_mod(install.uuid())
+                            .context("failed to remove damaged mod")
+                    })),
+                app,
+            )
+            .await
+            .ignore_cancel()?;
+    }
+
+    Ok(RepairSummary {
+        repaired_files,
+        redownloaded_mods,
+    })
+}
@@ -13,6 +13,10 @@ use tauri::{AppHandle, Manager};
 use tracing::{info, warn};
 use uuid::Uuid;
 
+use self::install::{
+    PackageInstaller,
+    rules::{InstallRule, InstallRules},
+};
 use crate::{
     config::ConfigCache,
     db::{self, Db},
@@ -25,6 +29,7 @@ use crate::{
 
 pub mod commands;
 pub mod export;
+pub mod health;
 pub mod import;
 pub mod install;
 pub mod launch;
@@ -61,6 +66,8 @@ pub struct ManagedGame {
     pub profiles: Vec<Profile>,
     pub favorite: bool,
     pub active_profile_id: i64,
+    /// Install rules shared by every profile, see [`InstallRules::game`].
+    pub install_rules: Vec<InstallRule>,
 }
 
 #[derive(Debug)]
@@ -78,6 +85,7 @@ pub struct Profile {
     pub sync: Option<sync::SyncProfileData>,
     pub custom_args: String,
     pub missing: bool,
+    pub install_rules: InstallRules,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -96,6 +104,8 @@ pub struct ProfileMod {
 #[serde(rename_all = "camelCase", untagged)]
 pub enum ProfileModKind {
     Thunderstore(ThunderstoreMod),
+    // must come before Local, since linked mods would also deserialize as one
+    Linked(Box<LinkedMod>),
     // Box to decrease size of enum, since this variant is rare and much larger
     Local(Box<LocalMod>),
 }
@@ -108,6 +118,14 @@ pub struct ThunderstoreMod {
 
     #[serde(flatten)]
     pub id: ModId,
+
+    /// The install rules the mod was installed with, if any.
+    ///
+    /// The mod is toggled and uninstalled with these, since the profile's rules may
+    /// have changed since. Their fingerprint identifies which of the version's cached
+    /// extractions the mod was installed from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rules: Option<InstallRules>,
 }
 
 impl ProfileMod {
@@ -130,6 +148,10 @@ impl ProfileMod {
         Self::new(ProfileModKind::Local(Box::new(local_mod)))
     }
 
+    fn new_linked(linked: LinkedMod) -> Self {
+        Self::new(ProfileModKind::Linked(Box::new(linked)))
+    }
+
     /// See [`ProfileModKind::uuid`]
     pub fn uuid(&self) -> Uuid {
         self.kind.uuid()
@@ -180,6 +202,7 @@ impl ProfileModKind {
     pub fn uuid(&self) -> Uuid {
         match self {
             ProfileModKind::Local(local_mod) => local_mod.uuid,
+            ProfileModKind::Linked(linked) => linked.local.uuid,
             ProfileModKind::Thunderstore(ts_mod) => ts_mod.id.package_uuid,
         }
     }
@@ -195,6 +218,7 @@ impl ProfileModKind {
         match self {
             ProfileModKind::Thunderstore(ts_mod) => Cow::Borrowed(&ts_mod.ident),
             ProfileModKind::Local(local_mod) => Cow::Owned(local_mod.ident()),
+            ProfileModKind::Linked(linked) => Cow::Owned(linked.local.ident()),
         }
     }
 
@@ -212,9 +236,18 @@ impl ProfileModKind {
         }
     }
 
+    /// Also returns the local part of linked mods.
     pub fn as_local(&self) -> Option<&LocalMod> {
         match self {
             ProfileModKind::Local(local) => Some(local),
+            ProfileModKind::Linked(linked) => Some(&linked.local),
+            _ => None,
+        }
+    }
+
+    pub fn as_linked(&self) -> Option<&LinkedMod> {
+        match self {
+            ProfileModKind::Linked(linked) => Some(linked),
             _ => None,
         }
     }
@@ -225,6 +258,7 @@ impl ProfileModKind {
     ) -> Option<&'a [VersionIdent]> {
         match self {
             ProfileModKind::Local(local_mod) => local_mod.dependencies.as_deref(),
+            ProfileModKind::Linked(linked) => linked.local.dependencies.as_deref(),
             ProfileModKind::Thunderstore(ts_mod) => ts_mod
                 .id
                 .borrow(thunderstore)
@@ -255,6 +289,13 @@ impl Profile {
             && name.chars().all(|c| !FORBIDDEN.contains(&c))
     }
 
+    /// Creates an installer for the given package, which follows the profile's install rules.
+    fn package_installer(&self, package_name: &str) -> Box<dyn PackageInstaller + '_> {
+        self.game
+            .mod_loader
+            .installer_for(package_name, &self.install_rules)
+    }
+
     fn index_of(&self, uuid: Uuid) -> Result<usize> {
         self.mods
             .iter()
@@ -289,14 +330,21 @@ impl Profile {
 
     /// Checks if any mods are hexium-exclusive mods
     fn has_hexium_exclusive_mods(&self, thunderstore: &Thunderstore) -> bool {
-        self.thunderstore_mods().any(|(package, _)| {
-            let ident = &package.ident;
-            package.id.backend == Backend::Hexium
-                && thunderstore
-                    .backend(Backend::Thunderstore)
-                    .find_ident(ident)
-                    .is_err()
-        })
+        self.exclusive_backend(thunderstore) == Some(Backend::Hexium)
+    }
+
+    /// Finds the backend of the first mod that isn't available on Thunderstore.
+    fn exclusive_backend(&self, thunderstore: &Thunderstore) -> Option<Backend> {
+        self.thunderstore_mods()
+            .map(|(package, _)| package)
+            .find(|package| {
+                package.id.backend != Backend::Thunderstore
+                    && thunderstore
+                        .backend(Backend::Thunderstore)
+                        .and_then(|backend| backend.find_ident(&package.ident))
+                        .is_err()
+            })
+            .map(|package| package.id.backend)
     }
 
     fn local_mods(&self) -> impl Iterator<Item = (&LocalMod, bool)> {
@@ -402,6 +450,23 @@ pub struct LocalMod {
     pub file_size: u64,
     pub readme: Option<String>,
     pub changelog: Option<String>,
+    /// The url or `github:` reference the mod was downloaded from.
+    pub source_url: Option<String>,
+}
+
+/// A local mod whose files are synced from a directory outside of the profile,
+/// such as the build output of a plugin.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkedMod {
+    #[serde(flatten)]
+    pub local: LocalMod,
+    pub source: PathBuf,
+    /// Fingerprint of the source directory as of the last sync.
+    ///
+    /// Used to skip syncing mods whose source hasn't changed while Gale was closed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
 }
 
 impl LocalMod {
@@ -597,7 +662,7 @@ impl ModManager {
                 continue;
             };
 
-            let profile = Profile {
+            let mut profile = Profile {
                 path,
                 game,
                 id: saved_profile.id,
@@ -611,12 +676,15 @@ impl ModManager {
                 sync: saved_profile.sync_data,
                 custom_args: saved_profile.custom_args,
                 missing,
+                install_rules: InstallRules {
+                    game: Vec::new(),
+                    profile: saved_profile.install_rules,
+                },
             };
 
-            manager
-                .ensure_game(game, false, prefs, db)?
-                .profiles
-                .push(profile);
+            let managed_game = manager.ensure_game(game, false, prefs, db)?;
+            profile.install_rules.game = managed_game.install_rules.clone();
+            managed_game.profiles.push(profile);
         }
 
         manager.ensure_game(manager.active_game, true, prefs, db)?;
@@ -724,6 +792,7 @@ impl ModManager {
             profiles: Vec::new(),
             favorite: false,
             active_profile_id: 0,
+            install_rules: Vec::new(),
         };
 
         if let Err(err) = managed.create_default_profile(db) {
@@ -764,6 +833,7 @@ impl ModManager {
             favorite: saved_game.favorite,
             active_profile_id: saved_game.active_profile_id,
             path: base_path.join(&*game.slug),
+            install_rules: saved_game.install_rules,
         };
 
         self.games.insert(game, managed_game);
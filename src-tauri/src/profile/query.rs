@@ -7,7 +7,10 @@ use tracing::warn;
 use super::{Dependant, LocalMod, Profile, ProfileMod, ProfileModKind};
 use crate::thunderstore::{
     self, BorrowedMod, FrontendProfileMod, IntoFrontendMod, Thunderstore,
-    query::{QueryModsArgs, Queryable, SortBy, SortOrder},
+    query::{
+        QueryModsArgs, Queryable, SortBy, SortOrder,
+        filter::{FilterContext, FilterData},
+    },
 };
 
 struct QueryableProfileMod<'a> {
@@ -30,6 +33,7 @@ impl<'a> QueryableProfileMod<'a> {
     ) -> Result<QueryableProfileMod<'a>> {
         let kind = match &profile_mod.kind {
             ProfileModKind::Local(local) => QueryableProfileModKind::Local(local),
+            ProfileModKind::Linked(linked) => QueryableProfileModKind::Local(&linked.local),
             ProfileModKind::Thunderstore(ts_mod) => {
                 let borrow = ts_mod.id.borrow(thunderstore)?;
                 QueryableProfileModKind::Thunderstore(borrow)
@@ -64,6 +68,15 @@ impl Queryable for QueryableProfileMod<'_> {
         }
     }
 
+    fn filter_data(&self) -> FilterData<'_> {
+        use QueryableProfileModKind as Kind;
+
+        match &self.kind {
+            Kind::Local(local) => local.filter_data(),
+            Kind::Thunderstore(remote) => remote.filter_data(),
+        }
+    }
+
     fn matches(&self, args: &QueryModsArgs) -> bool {
         use QueryableProfileModKind as Kind;
 
@@ -133,7 +146,9 @@ impl Profile {
                 }
             });
 
-        let found = thunderstore::query::query_mods(args, mods)
+        let ctx = FilterContext::new(self, thunderstore);
+
+        let found = thunderstore::query::query_mods(args, mods, &ctx)
             .map(|queryable| {
                 let (data, uuid) = match queryable.kind {
                     QueryableProfileModKind::Local(local) => {
@@ -173,6 +188,15 @@ impl Queryable for LocalMod {
         true
     }
 
+    fn filter_data(&self) -> FilterData<'_> {
+        FilterData {
+            uuid: Some(self.uuid),
+            owner: self.author.as_deref(),
+            dependencies: self.dependencies.as_deref().unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
     fn cmp(&self, other: &Self, args: &QueryModsArgs) -> Ordering {
         let order = match args.sort_by {
             SortBy::Name => other.name.cmp(&self.name),
@@ -1,6 +1,6 @@
 use tauri::{AppHandle, command};
 
-use crate::{state::ManagerExt, util::cmd::Result};
+use crate::{profile::install::plan::InstallPlan, state::ManagerExt, util::cmd::Result};
 
 use super::{ListedSyncProfile, SyncProfileMetadata, auth};
 
@@ -48,6 +48,13 @@ pub async fn delete_sync_profile(id: String, app: AppHandle) -> Result<()> {
     Ok(())
 }
 
+#[command]
+pub async fn plan_pull_sync_profile(app: AppHandle) -> Result<InstallPlan> {
+    let plan = super::plan_pull(&app).await?;
+
+    Ok(plan)
+}
+
 #[command]
 pub async fn pull_sync_profile(app: AppHandle) -> Result<()> {
     super::pull_profile(false, &app).await?;
@@ -7,7 +7,10 @@ use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 
 use crate::{
-    profile::{import::ImportOptions, install::InstallOptions},
+    profile::{
+        import::{ImportData, ImportOptions},
+        install::{InstallOptions, plan::InstallPlan},
+    },
     state::ManagerExt,
 };
 
@@ -267,13 +270,35 @@ pub async fn pull_profile(dry_run: bool, app: &AppHandle) -> Result<()> {
     }
 }
 
-async fn download_and_import_file(
-    override_name: Option<String>,
-    sync_profile: SyncProfileData,
-    app: &AppHandle,
-) -> Result<()> {
-    let path = format!("/profile/{}", sync_profile.id);
-    let bytes = request(Method::GET, path, app)
+/// Shows what pulling the active profile would do, without changing anything.
+///
+/// Unlike [`pull_profile`], this doesn't check if the profile has been updated since the last pull.
+pub async fn plan_pull(app: &AppHandle) -> Result<InstallPlan> {
+    let (id, name) = {
+        let manager = app.lock_manager();
+        let profile = manager.active_profile();
+
+        match &profile.sync {
+            Some(data) if data.missing => bail!("cannot pull from missing profile"),
+            Some(data) => (data.id.clone(), profile.name.clone()),
+            None => bail!("profile is not synced"),
+        }
+    };
+
+    let mut data = download_file(&id, app).await?;
+    data.manifest.name = name;
+
+    let plan = super::import::plan_import(&data, &ImportOptions::default(), app);
+
+    if data.delete_after_import {
+        std::fs::remove_dir_all(&data.path).ok();
+    }
+
+    plan
+}
+
+async fn download_file(id: &str, app: &AppHandle) -> Result<ImportData> {
+    let bytes = request(Method::GET, format!("/profile/{id}"), app)
         .await
         .send()
         .await?
@@ -281,8 +306,16 @@ async fn download_and_import_file(
         .bytes()
         .await?;
 
-    let mut data = super::import::read_file(Cursor::new(bytes), &app.lock_thunderstore())
-        .context("failed to read profile")?;
+    super::import::read_file(Cursor::new(bytes), &app.lock_thunderstore())
+        .context("failed to read profile")
+}
+
+async fn download_and_import_file(
+    override_name: Option<String>,
+    sync_profile: SyncProfileData,
+    app: &AppHandle,
+) -> Result<()> {
+    let mut data = download_file(&sync_profile.id, app).await?;
 
     if let Some(name) = override_name {
         data.manifest.name = name;
@@ -0,0 +1,129 @@
+//! Changelogs of the versions skipped over by updates, so they can be reviewed before updating.
+
+use eyre::{Context, Result};
+use futures_util::{StreamExt, stream};
+use itertools::Itertools;
+use tauri::AppHandle;
+use tracing::warn;
+
+use crate::{
+    state::ManagerExt,
+    thunderstore::{
+        ModId, VersionIdent,
+        cache::{self, MarkdownKind},
+        changelog::{self, Section},
+    },
+};
+
+/// Sections of the review document are nested under the package headings.
+const SECTION_LEVEL: usize = 3;
+
+/// How many changelogs to fetch at once, so large profiles don't flood the API.
+const CONCURRENT_FETCHES: usize = 4;
+
+/// Returns the changelog sections of the versions after `installed`, up to and including `target`.
+///
+/// `None` if the package has no changelog, or none of its sections are about these versions.
+pub async fn between(
+    installed: &VersionIdent,
+    target: &str,
+    app: &AppHandle,
+) -> Result<Option<String>> {
+    let (mod_id, from, to) = {
+        let thunderstore = app.lock_thunderstore();
+        let target = thunderstore.find_mod(installed.owner(), installed.name(), target)?;
+
+        let from: semver::Version = installed
+            .version()
+            .parse()
+            .context("invalid installed version")?;
+
+        (ModId::from(target), from, target.version.parsed_version())
+    };
+
+    let Some(markdown) = cache::get_markdown(MarkdownKind::Changelog, mod_id, app).await? else {
+        return Ok(None);
+    };
+
+    Ok(render(&changelog::between(&markdown, &from, &to)))
+}
+
+/// Combines the changelogs of every pending update in the active profile into a single document.
+///
+/// Updates that are ignored are left out.
+pub async fn review_updates(app: &AppHandle) -> Result<String> {
+    let updates = {
+        let manager = app.lock_manager();
+        let thunderstore = app.lock_thunderstore();
+
+        let profile = manager.active_profile();
+
+        profile
+            .mods
+            .iter()
+            .filter_map(|profile_mod| {
+                profile
+                    .find_update(profile_mod.uuid(), &thunderstore)
+                    .ok()
+                    .flatten()
+            })
+            .filter(|update| !profile.is_update_ignored(update))
+            .map(|update| {
+                (
+                    update.current.ident().clone(),
+                    update.latest.ident().clone(),
+                )
+            })
+            .sorted_by(|(a, _), (b, _)| a.full_name().cmp(b.full_name()))
+            .collect_vec()
+    };
+
+    // owned, since borrowing the updates in the stream keeps the future from being `Send`
+    let changelogs = stream::iter(updates.clone())
+        .map(|(current, latest)| async move { between(&current, latest.version(), app).await })
+        .buffered(CONCURRENT_FETCHES)
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut document = "# Changelog".to_string();
+
+    if updates.is_empty() {
+        document.push_str("\n\nAll mods are up to date.\n");
+        return Ok(document);
+    }
+
+    for ((current, latest), changelog) in updates.iter().zip(changelogs) {
+        document.push_str(&format!(
+            "\n\n## {} {} → {}\n\n",
+            current.full_name(),
+            current.version(),
+            latest.version()
+        ));
+
+        match changelog {
+            Ok(Some(changelog)) => document.push_str(changelog.trim_end()),
+            Ok(None) => document.push_str("No changelog entries found for these versions."),
+            Err(err) => {
+                warn!("failed to get changelog of {}: {:#}", latest, err);
+                document.push_str("Failed to get the changelog.");
+            }
+        }
+    }
+
+    document.push('\n');
+    Ok(document)
+}
+
+fn render(sections: &[Section<'_>]) -> Option<String> {
+    if sections.is_empty() {
+        return None;
+    }
+
+    let mut out = String::new();
+    for section in sections {
+        section.write_to(&mut out, SECTION_LEVEL);
+        out.push('\n');
+    }
+
+    Some(out)
+}
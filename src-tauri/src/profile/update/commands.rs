@@ -1,7 +1,11 @@
 use tauri::{AppHandle, command};
 use uuid::Uuid;
 
-use crate::{state::ManagerExt, thunderstore::ModId, util::cmd::Result};
+use crate::{
+    state::ManagerExt,
+    thunderstore::{ModId, VersionIdent},
+    util::cmd::Result,
+};
 
 #[command]
 pub async fn change_mod_version(id: ModId, app: AppHandle) -> Result<()> {
@@ -38,3 +42,23 @@ pub fn ignore_package_updates(package_uuid: Uuid, app: AppHandle) -> Result<()>
 
     Ok(())
 }
+
+/// Returns the changelog entries between an installed version and a target version of a package.
+#[command]
+pub async fn get_update_changelog(
+    installed: VersionIdent,
+    target: String,
+    app: AppHandle,
+) -> Result<Option<String>> {
+    let changelog = super::changelog::between(&installed, &target, &app).await?;
+
+    Ok(changelog)
+}
+
+/// Returns a markdown document with the changelogs of every pending update in the active profile.
+#[command]
+pub async fn review_updates(app: AppHandle) -> Result<String> {
+    let document = super::changelog::review_updates(&app).await?;
+
+    Ok(document)
+}
@@ -14,6 +14,7 @@ use crate::{
     thunderstore::{BorrowedMod, ModId, Thunderstore},
 };
 
+pub mod changelog;
 pub mod commands;
 
 pub struct AvailableUpdate<'a> {
@@ -51,6 +52,20 @@ impl Profile {
             return Ok(None); // a new version of this mod is installing or pending
         }
 
+        self.find_update(uuid, thunderstore)
+    }
+
+    /// Whether a newer version of the mod is available and its update isn't ignored.
+    pub fn has_update(&self, uuid: Uuid, thunderstore: &Thunderstore) -> bool {
+        self.find_update(uuid, thunderstore)
+            .is_ok_and(|update| update.is_some_and(|update| !self.is_update_ignored(&update)))
+    }
+
+    fn find_update<'a>(
+        &'a self,
+        uuid: Uuid,
+        thunderstore: &'a Thunderstore,
+    ) -> Result<Option<AvailableUpdate<'a>>> {
         let index = self.index_of(uuid)?;
         let profile_mod = &self.mods[index];
 
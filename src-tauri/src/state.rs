@@ -6,10 +6,11 @@ use serde::Serialize;
 use tauri::{AppHandle, Manager, command};
 
 use crate::{
+    config::watch::ConfigWatcher,
     db::{self, Db},
     events::EventBuffer,
     prefs::Prefs,
-    profile::{self, ModManager, install::queue::InstallQueue, sync},
+    profile::{self, ModManager, import::LinkedModWatcher, install::queue::InstallQueue, sync},
     thunderstore::{self, Thunderstore},
 };
 
@@ -23,6 +24,8 @@ pub struct AppState {
     sync_auth: sync::auth::State,
     sync_socket: sync::socket::State,
     event_buffer: EventBuffer,
+    config_watcher: ConfigWatcher,
+    linked_mod_watcher: LinkedModWatcher,
     is_first_run: bool,
 }
 
@@ -62,12 +65,18 @@ pub fn setup(app: &AppHandle) -> Result<()> {
         sync_socket: sync::socket::State::new(app.to_owned()),
         install_queue: InstallQueue::new(app.to_owned()),
         event_buffer: EventBuffer::new(app.to_owned()),
+        config_watcher: ConfigWatcher::default(),
+        linked_mod_watcher: LinkedModWatcher::default(),
         is_first_run: !db_existed && !migrated,
     };
 
     app.manage(state);
 
     thunderstore::start(app);
+    profile::install::cache::store::spawn_migration(app);
+
+    let handle = app.to_owned();
+    tauri::async_runtime::spawn_blocking(move || handle.linked_mod_watcher().start(&handle));
 
     let manager = app.lock_manager();
     manager.active_game().update_window_title(app).ok();
@@ -135,6 +144,14 @@ pub trait ManagerExt<R> {
         &self.app_state().event_buffer
     }
 
+    fn config_watcher(&self) -> &ConfigWatcher {
+        &self.app_state().config_watcher
+    }
+
+    fn linked_mod_watcher(&self) -> &LinkedModWatcher {
+        &self.app_state().linked_mod_watcher
+    }
+
     fn emit_buffered(&self, event: impl Into<String>, content: &impl Serialize) {
         self.event_buffer().emit(event, content);
     }
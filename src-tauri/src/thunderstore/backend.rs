@@ -1,46 +1,108 @@
+use super::fetch::{ChunkState, FetchStats};
 use crate::{
     game::Game,
-    thunderstore::{BorrowedMod, PackageIdent, PackageListing, VersionIdent, cache::MarkdownKind},
+    thunderstore::{
+        BorrowedMod, PackageIdent, PackageListing, VersionIdent, cache::MarkdownKind, token,
+    },
 };
-use eyre::eyre;
+use eyre::{Context, Result, ensure, eyre};
 use indexmap::IndexMap;
-use serde::{Deserialize, Serialize};
-use std::fmt::{Display, Formatter};
+use internment::Intern;
+use reqwest::Url;
+use reqwest_middleware::RequestBuilder;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    path::PathBuf,
+    sync::{LazyLock, RwLock},
+};
+use tracing::warn;
 use uuid::Uuid;
 
-#[derive(
-    Serialize, Deserialize, Debug, Clone, Copy, Ord, PartialOrd, PartialEq, Eq, Hash, Default,
-)]
+const CUSTOM_PREFIX: &str = "custom:";
+
+/// A source of packages with a Thunderstore-compatible API.
+///
+/// Serialized as `Thunderstore`, `Hexium` or `custom:<id>`.
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, PartialEq, Eq, Hash, Default)]
 pub enum Backend {
     #[default]
     Thunderstore,
     Hexium,
+    /// A repository configured by the user, identified by its [`CustomRepository::id`].
+    Custom(Intern<String>),
 }
 
 impl Display for Backend {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.write_str(match self {
-            Backend::Thunderstore => "thunderstore",
-            Backend::Hexium => "hexium",
-        })
+        match self {
+            Backend::Thunderstore => f.write_str("thunderstore"),
+            Backend::Hexium => f.write_str("hexium"),
+            Backend::Custom(id) => write!(f, "custom-{id}"),
+        }
+    }
+}
+
+impl Serialize for Backend {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Backend::Thunderstore => serializer.serialize_str("Thunderstore"),
+            Backend::Hexium => serializer.serialize_str("Hexium"),
+            Backend::Custom(id) => serializer.collect_str(&format_args!("{CUSTOM_PREFIX}{id}")),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Backend {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let str = String::deserialize(deserializer)?;
+
+        match str.as_str() {
+            "Thunderstore" => Ok(Backend::Thunderstore),
+            "Hexium" => Ok(Backend::Hexium),
+            _ => match str.strip_prefix(CUSTOM_PREFIX) {
+                Some(id) if !id.is_empty() => Ok(Backend::Custom(Intern::from_ref(id))),
+                _ => Err(serde::de::Error::custom(format!("unknown backend '{str}'"))),
+            },
+        }
     }
 }
 
 impl Backend {
     pub fn index_url(self, game: Game) -> Option<String> {
-        if game.backends.contains(&self) {
-            Some(match self {
-                Backend::Thunderstore => format!(
-                    "https://thunderstore.io/c/{}/api/v1/package-listing-index/",
-                    game.slug
-                ),
-                Backend::Hexium => format!(
-                    "https://{}.hexium.gg/api/v1/package-listing-index/",
-                    game.slug,
-                ),
-            })
-        } else {
-            None
+        match self {
+            Backend::Custom(id) => {
+                let repository = repository(id).ok()?;
+                let RepositorySource::Remote(remote) = &repository.source else {
+                    return None;
+                };
+
+                repository
+                    .serves(game)
+                    .then(|| remote.index_url_template.replace("{game}", &game.slug))
+            }
+            _ if !game.backends.contains(&self) => None,
+            Backend::Thunderstore => Some(format!(
+                "https://thunderstore.io/c/{}/api/v1/package-listing-index/",
+                game.slug
+            )),
+            Backend::Hexium => Some(format!(
+                "https://{}.hexium.gg/api/v1/package-listing-index/",
+                game.slug,
+            )),
+        }
+    }
+
+    /// The directory that packages are read from, if this is a local repository.
+    pub fn directory(self) -> Option<PathBuf> {
+        let Backend::Custom(id) = self else {
+            return None;
+        };
+
+        match repository(id).ok()?.source {
+            RepositorySource::Directory { path } => Some(path),
+            RepositorySource::Remote(_) => None,
         }
     }
 
@@ -48,36 +110,31 @@ impl Backend {
         matches!(self, Backend::Thunderstore)
     }
 
-    pub fn markdown_url(self, ident: &VersionIdent, cache: MarkdownKind) -> String {
-        match self {
-            Backend::Thunderstore => format!(
-                "https://thunderstore.io/api/experimental/package/{}/{}/{}/{}/",
-                ident.owner(),
-                ident.name(),
-                ident.version(),
-                cache
-            ),
-            Backend::Hexium => format!(
-                "https://hexium.gg/api/experimental/package/{}/{}/{}/{}/",
-                ident.owner(),
-                ident.name(),
-                ident.version(),
-                cache
-            ),
-        }
+    pub fn markdown_url(self, ident: &VersionIdent, cache: MarkdownKind) -> Result<String> {
+        Ok(format!(
+            "{}/package/{}/{}/{}/{}/",
+            self.api_url()?,
+            ident.owner(),
+            ident.name(),
+            ident.version(),
+            cache
+        ))
     }
 
-    pub fn owner_url(self, owner: &str, game: Game) -> String {
-        match self {
+    pub fn owner_url(self, owner: &str, game: Game) -> Result<String> {
+        Ok(match self {
             Backend::Thunderstore => {
                 format!("https://thunderstore.io/c/{}/p/{}/", game.slug, owner)
             }
             Backend::Hexium => format!("https://{}.hexium.gg/teams/{}", game.slug, owner),
-        }
+            Backend::Custom(id) => {
+                format!("{}/c/{}/p/{}/", remote(id)?.base_url, game.slug, owner)
+            }
+        })
     }
 
-    pub fn mod_url(self, package: &PackageIdent, game: Game) -> String {
-        match self {
+    pub fn mod_url(self, package: &PackageIdent, game: Game) -> Result<String> {
+        Ok(match self {
             Backend::Thunderstore => {
                 format!(
                     "https://thunderstore.io/c/{}/p/{}/{}/",
@@ -92,11 +149,18 @@ impl Backend {
                 package.name(),
                 package.name()
             ),
-        }
+            Backend::Custom(id) => format!(
+                "{}/c/{}/p/{}/{}/",
+                remote(id)?.base_url,
+                game.slug,
+                package.owner(),
+                package.name()
+            ),
+        })
     }
 
-    pub fn download_url(self, version: &VersionIdent) -> String {
-        match self {
+    pub fn download_url(self, version: &VersionIdent) -> Result<String> {
+        Ok(match self {
             Backend::Thunderstore => format!(
                 "https://thunderstore.io/package/download/{}/{}/{}",
                 version.owner(),
@@ -109,51 +173,222 @@ impl Backend {
                 version.name(),
                 version.version()
             ),
-        }
+            Backend::Custom(id) => remote(id)?
+                .download_url_template
+                .replace("{owner}", version.owner())
+                .replace("{name}", version.name())
+                .replace("{version}", version.version()),
+        })
     }
 
-    pub fn profile_import(self, key: &str) -> String {
-        match self {
-            Backend::Thunderstore => {
-                format!("https://thunderstore.io/api/experimental/legacyprofile/get/{key}/")
-            }
-            Backend::Hexium => {
-                format!("https://hexium.gg/api/experimental/legacyprofile/get/{key}/")
+    pub fn profile_import(self, key: &str) -> Result<String> {
+        Ok(format!("{}/legacyprofile/get/{key}/", self.api_url()?))
+    }
+
+    pub fn profile_export(self) -> Result<String> {
+        Ok(format!("{}/legacyprofile/create/", self.api_url()?))
+    }
+
+    pub fn category_url(self, game: Game) -> Result<String> {
+        Ok(format!(
+            "{}/community/{}/category/",
+            self.api_url()?,
+            game.slug
+        ))
+    }
+
+    pub fn modpack_upload_base_url(self) -> Result<String> {
+        self.api_url()
+    }
+
+    /// The base url of the backend's experimental API.
+    fn api_url(self) -> Result<String> {
+        Ok(match self {
+            Backend::Thunderstore => "https://thunderstore.io/api/experimental".to_owned(),
+            Backend::Hexium => "https://hexium.gg/api/experimental".to_owned(),
+            Backend::Custom(id) => format!("{}/api/experimental", remote(id)?.base_url),
+        })
+    }
+
+    /// Adds the API token of custom repositories to `request`, if one is set.
+    ///
+    /// Tokens for Thunderstore and Hexium are only sent when publishing modpacks.
+    pub fn authorize(self, request: RequestBuilder) -> RequestBuilder {
+        let Backend::Custom(_) = self else {
+            return request;
+        };
+
+        match cached_token(self) {
+            Ok(Some(token)) => request.bearer_auth(token),
+            Ok(None) => request,
+            Err(err) => {
+                warn!("failed to get token for {}: {:#}", self, err);
+                request
             }
         }
     }
+}
 
-    pub fn profile_export(self) -> &'static str {
-        match self {
-            Backend::Thunderstore => {
-                "https://thunderstore.io/api/experimental/legacyprofile/create/"
+/// A package repository configured by the user.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomRepository {
+    /// Identifies the repository in [`Backend::Custom`], its cache files and its API token.
+    pub id: String,
+    pub name: String,
+    #[serde(flatten)]
+    pub source: RepositorySource,
+    /// Slugs of the games the repository has packages for, or empty for every game.
+    #[serde(default)]
+    pub games: Vec<String>,
+}
+
+/// Where a [`CustomRepository`] gets its packages from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RepositorySource {
+    Remote(RemoteRepository),
+    /// A directory of package zips, see [`super::directory`].
+    Directory {
+        path: PathBuf,
+    },
+}
+
+/// A server with a Thunderstore-compatible API.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteRepository {
+    /// The root of the repository, like `https://mods.example.com`.
+    ///
+    /// The experimental API, as well as the package and team pages,
+    /// are expected at the same paths as on Thunderstore.
+    pub base_url: String,
+    /// Where to fetch the package listing index from, with `{game}` replaced by the game's slug.
+    pub index_url_template: String,
+    /// Where to download packages from, with `{owner}`, `{name}` and `{version}` replaced.
+    pub download_url_template: String,
+}
+
+impl CustomRepository {
+    pub fn backend(&self) -> Backend {
+        Backend::Custom(Intern::from_ref(self.id.as_str()))
+    }
+
+    pub fn serves(&self, game: Game) -> bool {
+        self.games.is_empty() || self.games.iter().any(|slug| *slug == *game.slug)
+    }
+
+    pub fn is_remote(&self) -> bool {
+        matches!(self.source, RepositorySource::Remote(_))
+    }
+
+    /// Checks that the repository is well-formed and normalizes its urls.
+    pub fn validate(&mut self) -> Result<()> {
+        ensure!(
+            !self.id.is_empty()
+                && self
+                    .id
+                    .chars()
+                    .all(|char| char.is_ascii_lowercase() || char.is_ascii_digit() || char == '-'),
+            "repository id '{}' may only contain lowercase letters, digits and dashes",
+            self.id
+        );
+
+        ensure!(
+            !self.name.trim().is_empty(),
+            "repository name cannot be empty"
+        );
+
+        match &mut self.source {
+            RepositorySource::Remote(remote) => remote.validate(),
+            RepositorySource::Directory { path } => {
+                ensure!(path.is_dir(), "{} is not a directory", path.display());
+                Ok(())
             }
-            Backend::Hexium => "https://hexium.gg/api/experimental/legacyprofile/create/",
         }
     }
+}
 
-    pub fn category_url(&self, game: Game) -> String {
-        match self {
-            Backend::Thunderstore => format!(
-                "https://thunderstore.io/api/experimental/community/{}/category/",
-                game.slug
-            ),
-            Backend::Hexium => format!(
-                "https://hexium.gg/api/experimental/community/{}/category/",
-                game.slug
-            ),
+impl RemoteRepository {
+    /// Removes trailing slashes from the base url.
+    fn validate(&mut self) -> Result<()> {
+        let base_url = self.base_url.trim().trim_end_matches('/').to_owned();
+        let url = Url::parse(&base_url).context("invalid base url")?;
+        ensure!(
+            matches!(url.scheme(), "http" | "https"),
+            "only http and https urls are supported"
+        );
+        self.base_url = base_url;
+
+        Url::parse(&self.index_url_template.replace("{game}", "game"))
+            .context("invalid index url template")?;
+
+        for placeholder in ["{owner}", "{name}", "{version}"] {
+            ensure!(
+                self.download_url_template.contains(placeholder),
+                "download url template is missing {}",
+                placeholder
+            );
         }
+
+        Url::parse(&self.download_url_template).context("invalid download url template")?;
+
+        Ok(())
     }
+}
 
-    pub fn modpack_upload_base_url(self) -> &'static str {
-        match self {
-            Backend::Thunderstore => "https://thunderstore.io/api/experimental",
-            Backend::Hexium => "https://hexium.gg/api/experimental",
-        }
+/// The custom repositories from [`Prefs`](crate::prefs::Prefs), by id.
+///
+/// Kept separately so that [`Backend`] can stay [`Copy`] and its urls can be built without
+/// locking the prefs. Updated with [`set_repositories`].
+static REPOSITORIES: LazyLock<RwLock<HashMap<Intern<String>, CustomRepository>>> =
+    LazyLock::new(Default::default);
+
+pub fn set_repositories(repositories: &[CustomRepository]) {
+    *REPOSITORIES.write().unwrap() = repositories
+        .iter()
+        .map(|repository| (Intern::from_ref(repository.id.as_str()), repository.clone()))
+        .collect();
+}
+
+/// API tokens of custom repositories, so the keyring isn't read for every request.
+///
+/// Repositories without a token are cached as [`None`]. Invalidated with [`forget_token`].
+static TOKENS: LazyLock<RwLock<HashMap<Backend, Option<String>>>> = LazyLock::new(Default::default);
+
+fn cached_token(backend: Backend) -> Result<Option<String>> {
+    if let Some(token) = TOKENS.read().unwrap().get(&backend) {
+        return Ok(token.clone());
     }
+
+    let token = token::get(backend)?;
+    TOKENS.write().unwrap().insert(backend, token.clone());
+
+    Ok(token)
+}
+
+/// Drops the cached API token of `backend`, after it has been changed or cleared.
+pub(super) fn forget_token(backend: Backend) {
+    TOKENS.write().unwrap().remove(&backend);
 }
 
-/// Registry for all Thunderstore-like mods for the active game (Hexium, Thunderstore).
+fn repository(id: Intern<String>) -> Result<CustomRepository> {
+    REPOSITORIES
+        .read()
+        .unwrap()
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| eyre!("repository '{id}' is not configured"))
+}
+
+fn remote(id: Intern<String>) -> Result<RemoteRepository> {
+    match repository(id)?.source {
+        RepositorySource::Remote(remote) => Ok(remote),
+        RepositorySource::Directory { .. } => Err(eyre!("repository '{id}' is a local directory")),
+    }
+}
+
+/// Registry for all Thunderstore-like mods for the active game from a single [`Backend`].
 pub struct ThunderstoreBackend {
     /// Whether packages have been succesfully fetched at least one since
     /// the last call to [`crate::thunderstore::Thunderstore::switch_game`].
@@ -164,6 +399,19 @@ pub struct ThunderstoreBackend {
     // since we iterate over all mods when resolving identifiers and querying.
     pub(super) packages: IndexMap<Uuid, PackageListing>,
     pub(super) backend: Backend,
+    /// Maps the full name of each package to its uuid.
+    ///
+    /// The keys are borrowed from the packages' interned identifiers.
+    names: HashMap<&'static str, Uuid>,
+    /// Maps the full name of each package to the packages whose latest version depends on it,
+    /// along with the version of it they depend on.
+    ///
+    /// Rebuilt with [`ThunderstoreBackend::rebuild_indices`].
+    dependants: HashMap<&'static str, Vec<(Uuid, VersionIdent)>>,
+    /// The package listing chunks from the last successful fetch, by url.
+    pub(super) chunks: HashMap<String, ChunkState>,
+    /// Totals of every fetch since the app started.
+    pub(super) fetch_stats: FetchStats,
 }
 
 impl ThunderstoreBackend {
@@ -173,6 +421,10 @@ impl ThunderstoreBackend {
             is_fetching: false,
             packages: IndexMap::new(),
             backend,
+            names: HashMap::new(),
+            dependants: HashMap::new(),
+            chunks: HashMap::new(),
+            fetch_stats: FetchStats::default(),
         }
     }
 
@@ -198,9 +450,9 @@ impl ThunderstoreBackend {
 
     /// Finds a package with the given `full_name` (formatted as `owner-name`).
     pub fn find_package(&self, full_name: &str) -> eyre::Result<&PackageListing> {
-        self.packages
-            .values()
-            .find(|package| package.ident.as_str() == full_name)
+        self.names
+            .get(full_name)
+            .and_then(|uuid| self.packages.get(uuid))
             .ok_or_else(|| eyre!("package {full_name} not found"))
     }
 
@@ -226,11 +478,7 @@ impl ThunderstoreBackend {
         name: &str,
         version: &str,
     ) -> eyre::Result<BorrowedMod<'a>> {
-        let package = self
-            .packages
-            .values()
-            .find(|package| package.owner() == owner && package.name() == name)
-            .ok_or_else(|| eyre!("package {}-{} not found", owner, name))?;
+        let package = self.find_package(&format!("{owner}-{name}"))?;
 
         let version = package.get_version_with_num(version).ok_or_else(|| {
             eyre!(
@@ -244,10 +492,114 @@ impl ThunderstoreBackend {
         Ok((package, version).into())
     }
 
+    /// Finds the packages whose latest version depends on the package `full_name`
+    /// (formatted as `owner-name`), along with the version of it they depend on.
+    pub fn dependants<'a>(
+        &'a self,
+        full_name: &str,
+    ) -> impl Iterator<Item = (BorrowedMod<'a>, &'a VersionIdent)> + 'a {
+        self.dependants
+            .get(full_name)
+            .into_iter()
+            .flatten()
+            .filter_map(|(uuid, dependency)| {
+                let package = self.packages.get(uuid)?;
+                Some((BorrowedMod::latest(package), dependency))
+            })
+    }
+
+    /// Adds packages, replacing any with the same uuid, and indexes their names.
+    ///
+    /// Dependants aren't indexed until the next [`ThunderstoreBackend::rebuild_indices`].
+    pub(super) fn extend_packages(&mut self, packages: impl IntoIterator<Item = PackageListing>) {
+        for package in packages {
+            self.names.insert(package.ident.as_str(), package.uuid);
+            self.packages.insert(package.uuid, package);
+        }
+    }
+
+    /// Indexes the names and dependencies of the current packages for
+    /// [`ThunderstoreBackend::find_package`] and [`ThunderstoreBackend::dependants`].
+    pub(super) fn rebuild_indices(&mut self) {
+        self.names = self
+            .packages
+            .values()
+            .map(|package| (package.ident.as_str(), package.uuid))
+            .collect();
+
+        let mut dependants: HashMap<&'static str, Vec<_>> = HashMap::new();
+
+        for borrowed in self.latest() {
+            for dependency in &borrowed.version.dependencies {
+                dependants
+                    .entry(dependency.full_name())
+                    .or_default()
+                    .push((borrowed.package.uuid, dependency.clone()));
+            }
+        }
+
+        self.dependants = dependants;
+    }
+
     /// Clear the package map.
     pub fn clear_packages(&mut self) {
         self.is_fetching = false;
         self.packages_fetched = false;
         self.packages = IndexMap::new();
+        self.names = HashMap::new();
+        self.dependants = HashMap::new();
+        self.chunks = HashMap::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_backends() {
+        for (backend, json) in [
+            (Backend::Thunderstore, "\"Thunderstore\""),
+            (Backend::Hexium, "\"Hexium\""),
+            (
+                Backend::Custom(Intern::from_ref("in-house")),
+                "\"custom:in-house\"",
+            ),
+        ] {
+            assert_eq!(serde_json::to_string(&backend).unwrap(), json);
+            assert_eq!(serde_json::from_str::<Backend>(json).unwrap(), backend);
+        }
+
+        assert!(serde_json::from_str::<Backend>("\"custom:\"").is_err());
+        assert!(serde_json::from_str::<Backend>("\"Nexus\"").is_err());
+    }
+
+    #[test]
+    fn validate_repository() {
+        let mut repository = CustomRepository {
+            id: "in-house".into(),
+            name: "In-house".into(),
+            source: RepositorySource::Remote(RemoteRepository {
+                base_url: "https://mods.example.com/".into(),
+                index_url_template:
+                    "https://mods.example.com/c/{game}/api/v1/package-listing-index/".into(),
+                download_url_template: "https://mods.example.com/download/{owner}/{name}/{version}"
+                    .into(),
+            }),
+            games: Vec::new(),
+        };
+
+        repository.validate().unwrap();
+
+        let RepositorySource::Remote(remote) = &mut repository.source else {
+            unreachable!();
+        };
+        assert_eq!(remote.base_url, "https://mods.example.com");
+
+        remote.download_url_template = "https://mods.example.com/{owner}/{name}".into();
+        assert!(repository.validate().is_err());
+
+        repository.id = "In House".into();
+        assert!(repository.validate().is_err());
     }
 }
@@ -0,0 +1,238 @@
+//! Package indices for the `index` benchmark, which compares the memory use and lookup
+//! speed of the package index against the representation it replaced.
+//!
+//! Only compiled with the `bench` feature, see `benches/index.rs`.
+
+use serde_json::json;
+use uuid::Uuid;
+
+use super::{Backend, PackageListing, ThunderstoreBackend};
+
+/// The number of packages in the generated index, about as many as Lethal Company has.
+pub const PACKAGE_COUNT: usize = 40_000;
+const CATEGORIES: [&str; 8] = [
+    "Mods",
+    "Items",
+    "Cosmetics",
+    "Client-side",
+    "Server-side",
+    "Libraries",
+    "Misc",
+    "Modpacks",
+];
+
+/// The package index before it was made compact: owned strings, hash sets,
+/// vectors with spare capacity and no index of package names.
+mod legacy {
+    use std::collections::{HashMap, HashSet};
+
+    use chrono::{DateTime, Utc};
+    use indexmap::IndexMap;
+    use internment::Intern;
+    use serde::Deserialize;
+    use uuid::Uuid;
+
+    use crate::thunderstore::VersionIdent;
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    pub struct PackageListing {
+        pub full_name: String,
+        pub categories: HashSet<Intern<String>>,
+        pub date_created: DateTime<Utc>,
+        pub date_updated: DateTime<Utc>,
+        pub donation_link: Option<String>,
+        pub has_nsfw_content: bool,
+        pub is_deprecated: bool,
+        pub is_pinned: bool,
+        pub package_url: String,
+        pub rating_score: u32,
+        pub uuid4: Uuid,
+        pub versions: Vec<PackageVersion>,
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    pub struct PackageVersion {
+        pub full_name: VersionIdent,
+        pub date_created: DateTime<Utc>,
+        pub dependencies: Vec<VersionIdent>,
+        #[serde(default)]
+        pub suggestions: Vec<VersionIdent>,
+        pub description: Intern<String>,
+        pub downloads: u32,
+        pub file_size: u64,
+        pub is_active: bool,
+        pub uuid4: Uuid,
+        pub website_url: Intern<String>,
+    }
+
+    pub struct Index {
+        pub packages: IndexMap<Uuid, PackageListing>,
+        pub dependants: HashMap<String, Vec<(Uuid, VersionIdent)>>,
+    }
+
+    impl Index {
+        pub fn new(packages: Vec<PackageListing>) -> Self {
+            let packages: IndexMap<_, _> = packages
+                .into_iter()
+                .map(|package| (package.uuid4, package))
+                .collect();
+
+            let mut dependants: HashMap<String, Vec<_>> = HashMap::new();
+            for package in packages.values() {
+                for dependency in &package.versions[0].dependencies {
+                    dependants
+                        .entry(dependency.full_name().to_owned())
+                        .or_default()
+                        .push((package.uuid4, dependency.clone()));
+                }
+            }
+
+            Self {
+                packages,
+                dependants,
+            }
+        }
+
+        pub fn find_package(&self, full_name: &str) -> Option<&PackageListing> {
+            self.packages
+                .values()
+                .find(|package| package.full_name == full_name)
+        }
+
+        pub fn get_mod(
+            &self,
+            package_uuid: Uuid,
+            version_uuid: Uuid,
+        ) -> Option<(&PackageListing, &PackageVersion)> {
+            let package = self.packages.get(&package_uuid)?;
+            let version = package.versions.iter().find(|v| v.uuid4 == version_uuid)?;
+            Some((package, version))
+        }
+    }
+}
+
+/// Generates a package index, formatted like Thunderstore's package listing chunks.
+pub fn generate_index() -> String {
+    let packages = (0..PACKAGE_COUNT)
+        .map(|i| {
+            let owner = format!("Author{}", i % 5_000);
+            let name = format!("Package_Number_{i}");
+
+            let versions = (0..=(i % 15))
+                .rev()
+                .map(|patch| {
+                    let dependencies = (0..(i + patch) % 5)
+                        .map(|j| {
+                            let dependency = (i * 31 + j * 7) % PACKAGE_COUNT;
+                            format!("Author{}-Package_Number_{dependency}-1.0.0", dependency % 5_000)
+                        })
+                        .collect::<Vec<_>>();
+
+                    json!({
+                        "full_name": format!("{owner}-{name}-1.0.{patch}"),
+                        "date_created": "2024-01-01T00:00:00.000000Z",
+                        "dependencies": dependencies,
+                        "description": format!("{name} adds {} new things to the game.", i % 100),
+                        "downloads": i * patch,
+                        "file_size": 100_000 + i,
+                        "is_active": true,
+                        "uuid4": Uuid::new_v4(),
+                        "website_url": format!("https://github.com/{owner}/{name}"),
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            json!({
+                "full_name": format!("{owner}-{name}"),
+                "categories": [CATEGORIES[i % 8], CATEGORIES[(i / 8) % 8]],
+                "date_created": "2024-01-01T00:00:00.000000Z",
+                "date_updated": "2024-06-01T00:00:00.000000Z",
+                "donation_link": (i % 4 == 0).then(|| format!("https://ko-fi.com/{owner}")),
+                "has_nsfw_content": false,
+                "is_deprecated": i % 50 == 0,
+                "is_pinned": false,
+                "package_url": format!("https://thunderstore.io/c/lethal-company/p/{owner}/{name}/"),
+                "rating_score": i % 1_000,
+                "uuid4": Uuid::new_v4(),
+                "versions": versions,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    serde_json::to_string(&packages).unwrap()
+}
+
+/// The package index before it was made compact.
+pub struct LegacyIndex(legacy::Index);
+
+impl LegacyIndex {
+    pub fn build(json: &str) -> Self {
+        Self(legacy::Index::new(serde_json::from_str(json).unwrap()))
+    }
+
+    pub fn package_count(&self) -> usize {
+        self.0.packages.len()
+    }
+
+    /// Returns the full name of every package.
+    pub fn names(&self) -> Vec<String> {
+        self.0
+            .packages
+            .values()
+            .map(|package| package.full_name.clone())
+            .collect()
+    }
+
+    /// Returns the uuids of every package and its oldest version.
+    pub fn ids(&self) -> Vec<(Uuid, Uuid)> {
+        self.0
+            .packages
+            .values()
+            .map(|package| (package.uuid4, package.versions.last().unwrap().uuid4))
+            .collect()
+    }
+
+    pub fn find_package(&self, full_name: &str) -> bool {
+        self.0.find_package(full_name).is_some()
+    }
+
+    pub fn get_mod(&self, package_uuid: Uuid, version_uuid: Uuid) -> bool {
+        self.0.get_mod(package_uuid, version_uuid).is_some()
+    }
+
+    pub fn dependant_count(&self, full_name: &str) -> usize {
+        self.0.dependants.get(full_name).map_or(0, Vec::len)
+    }
+}
+
+/// The package index as it's used by [`ThunderstoreBackend`].
+pub struct CompactIndex(ThunderstoreBackend);
+
+impl CompactIndex {
+    pub fn build(json: &str) -> Self {
+        let packages: Vec<PackageListing> = serde_json::from_str(json).unwrap();
+
+        let mut backend = ThunderstoreBackend::new(Backend::Thunderstore);
+        backend.extend_packages(packages);
+        backend.rebuild_indices();
+        Self(backend)
+    }
+
+    pub fn package_count(&self) -> usize {
+        self.0.packages.len()
+    }
+
+    pub fn find_package(&self, full_name: &str) -> bool {
+        self.0.find_package(full_name).is_ok()
+    }
+
+    pub fn get_mod(&self, package_uuid: Uuid, version_uuid: Uuid) -> bool {
+        self.0.get_mod(package_uuid, version_uuid).is_ok()
+    }
+
+    pub fn dependant_count(&self, full_name: &str) -> usize {
+        self.0.dependants(full_name).count()
+    }
+}
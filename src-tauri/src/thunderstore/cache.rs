@@ -7,7 +7,7 @@ use serde::Deserialize;
 use tauri::AppHandle;
 use tracing::{debug, info, warn};
 
-use super::{Backend, ModId, PackageListing};
+use super::{Backend, ModId, PackageListing, directory};
 use crate::{
     game::Game,
     prefs::Prefs,
@@ -42,6 +42,11 @@ pub async fn get_markdown(
     mod_id: ModId,
     app: &AppHandle,
 ) -> Result<Option<String>> {
+    if let Some(dir) = mod_id.backend.directory() {
+        let ident = mod_id.borrow(&app.lock_thunderstore())?.ident().clone();
+        return directory::read_markdown(&dir, &ident, cache);
+    }
+
     let (url, cache_mode) = {
         let thunderstore = app.lock_thunderstore();
         let ident = mod_id.borrow(&thunderstore)?.ident();
@@ -52,12 +57,12 @@ pub async fn get_markdown(
             CacheMode::Default
         };
 
-        (mod_id.backend.markdown_url(ident, cache), cache_mode)
+        (mod_id.backend.markdown_url(ident, cache)?, cache_mode)
     };
 
-    let response: MarkdownResponse = app
-        .http()
-        .get(url)
+    let response: MarkdownResponse = mod_id
+        .backend
+        .authorize(app.http().get(url))
         .with_extension(cache_mode)
         .send()
         .await?
@@ -71,11 +76,7 @@ pub async fn get_markdown(
 impl ThunderstoreBackend {
     pub fn read_and_insert_cache(&mut self, game: Game, prefs: &Prefs) {
         match get_packages(game, prefs, self.backend) {
-            Ok(Some(mods)) => {
-                for package in mods {
-                    self.packages.insert(package.uuid, package);
-                }
-            }
+            Ok(Some(mods)) => self.extend_packages(mods),
             Ok(None) => (),
             Err(err) => warn!("failed to read cache: {}", err),
         }
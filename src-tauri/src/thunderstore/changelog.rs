@@ -0,0 +1,240 @@
+//! Splits markdown changelogs into sections per version.
+//!
+//! Changelogs on Thunderstore don't follow a single format, so any heading that
+//! contains a version number, like `## 1.2.0`, `## [1.2.0] - 2024-05-01` or
+//! `### v1.2`, starts a new section.
+
+use std::fmt::Write;
+
+/// The part of a changelog about a single version.
+#[derive(Debug, Clone)]
+pub struct Section<'a> {
+    pub version: semver::Version,
+    /// The text of the section's heading, without the leading `#`s.
+    pub title: &'a str,
+    /// The heading level, where `#` is 1.
+    pub level: usize,
+    /// Everything between the heading and the start of the next section, trimmed.
+    pub body: &'a str,
+}
+
+impl Section<'_> {
+    /// Writes the section with its heading at `level`, adjusting
+    /// the headings inside the body to keep them nested below it.
+    pub fn write_to(&self, out: &mut String, level: usize) {
+        let _ = writeln!(out, "{} {}", "#".repeat(level), self.title);
+
+        for line in self.body.lines() {
+            match heading_level(line) {
+                Some(inner) => {
+                    let inner = (inner + level).saturating_sub(self.level).max(level + 1);
+                    let _ = writeln!(out, "{}{}", "#".repeat(inner), line.trim_start_matches('#'));
+                }
+                None => {
+                    out.push_str(line);
+                    out.push('\n');
+                }
+            }
+        }
+    }
+}
+
+/// Parses every versioned section of a changelog, in the order they appear.
+pub fn sections(markdown: &str) -> Vec<Section<'_>> {
+    let mut sections = Vec::new();
+    // the section being read and the byte offset of its body
+    let mut current: Option<(semver::Version, &str, usize, usize)> = None;
+    let mut offset = 0;
+
+    for line in markdown.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+
+        let Some(level) = heading_level(line) else {
+            continue;
+        };
+
+        let title = line[level..].trim();
+        let version = parse_title(title);
+
+        // unversioned headings, like "Unreleased", end the section
+        // unless they are nested inside it
+        let ends_current = version.is_some()
+            || current
+                .as_ref()
+                .is_some_and(|(_, _, current_level, _)| level <= *current_level);
+
+        if ends_current && let Some((version, title, level, body_start)) = current.take() {
+            sections.push(Section {
+                version,
+                title,
+                level,
+                body: markdown[body_start..start].trim(),
+            });
+        }
+
+        if let Some(version) = version {
+            current = Some((version, title, level, offset));
+        }
+    }
+
+    if let Some((version, title, level, body_start)) = current {
+        sections.push(Section {
+            version,
+            title,
+            level,
+            body: markdown[body_start..].trim(),
+        });
+    }
+
+    sections
+}
+
+/// Finds the section about `version`.
+pub fn find<'a>(markdown: &'a str, version: &semver::Version) -> Option<Section<'a>> {
+    sections(markdown)
+        .into_iter()
+        .find(|section| section.version == *version)
+}
+
+/// Returns the sections about versions newer than `from`, up to and including `to`.
+pub fn between<'a>(
+    markdown: &'a str,
+    from: &semver::Version,
+    to: &semver::Version,
+) -> Vec<Section<'a>> {
+    sections(markdown)
+        .into_iter()
+        .filter(|section| section.version > *from && section.version <= *to)
+        .collect()
+}
+
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|&char| char == '#').count();
+    let rest = &line[level..];
+
+    (level > 0 && (rest.starts_with(' ') || rest.trim().is_empty())).then_some(level)
+}
+
+/// Finds the first version number in a heading.
+fn parse_title(title: &str) -> Option<semver::Version> {
+    title
+        .split(|char: char| char.is_whitespace() || "[]()<>:,*_`".contains(char))
+        .map(|word| word.trim_start_matches(['v', 'V']))
+        .find_map(parse_version)
+}
+
+/// Parses a version number, allowing the minor and patch numbers to be left out.
+fn parse_version(word: &str) -> Option<semver::Version> {
+    if let Ok(version) = word.parse() {
+        return Some(version);
+    }
+
+    let mut parts = word.split('.');
+    let mut next = || parts.next().map(str::parse::<u64>);
+
+    let major = next()?.ok()?;
+    let minor = next().transpose().ok()?.unwrap_or(0);
+    let patch = next().transpose().ok()?.unwrap_or(0);
+
+    // single numbers are too likely to be something else
+    if !word.contains('.') || parts.next().is_some() {
+        return None;
+    }
+
+    Some(semver::Version::new(major, minor, patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANGELOG: &str = "# Changelog
+
+## Unreleased
+- Work in progress
+
+## [1.2.0] - 2024-05-01
+- Added a thing
+- Fixed another thing
+
+### Notes
+Some notes.
+
+## 1.1.10
+- Older changes
+
+## v1.1
+- Even older changes
+
+# Credits
+Thanks!
+";
+
+    fn versions(sections: &[Section]) -> Vec<String> {
+        sections
+            .iter()
+            .map(|section| section.version.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn parse_sections() {
+        let sections = sections(CHANGELOG);
+
+        assert_eq!(versions(&sections), ["1.2.0", "1.1.10", "1.1.0"]);
+        assert_eq!(sections[0].title, "[1.2.0] - 2024-05-01");
+        assert_eq!(
+            sections[0].body,
+            "- Added a thing\n- Fixed another thing\n\n### Notes\nSome notes."
+        );
+        assert_eq!(sections[2].body, "- Even older changes");
+    }
+
+    #[test]
+    fn parse_titles() {
+        let parse = |title| parse_title(title).map(|version| version.to_string());
+
+        assert_eq!(parse("1.0.0").as_deref(), Some("1.0.0"));
+        assert_eq!(parse("Version 2.3").as_deref(), Some("2.3.0"));
+        assert_eq!(parse("**v1.0.0-beta.1**").as_deref(), Some("1.0.0-beta.1"));
+        assert_eq!(parse("1.0.4 (2024-01-01)").as_deref(), Some("1.0.4"));
+        assert_eq!(parse("Part 2"), None);
+        assert_eq!(parse("Unreleased"), None);
+    }
+
+    #[test]
+    fn find_exact_version() {
+        let find = |version: &str| find(CHANGELOG, &version.parse().unwrap()).map(|s| s.body);
+
+        assert_eq!(find("1.1.10"), Some("- Older changes"));
+        assert_eq!(find("1.1.1"), None);
+    }
+
+    #[test]
+    fn sections_between() {
+        let between = |from: &str, to: &str| {
+            versions(&between(
+                CHANGELOG,
+                &from.parse().unwrap(),
+                &to.parse().unwrap(),
+            ))
+        };
+
+        assert_eq!(between("1.1.0", "1.2.0"), ["1.2.0", "1.1.10"]);
+        assert_eq!(between("1.1.0", "1.1.10"), ["1.1.10"]);
+        assert!(between("1.2.0", "1.3.0").is_empty());
+    }
+
+    #[test]
+    fn write_nested() {
+        let section = &sections(CHANGELOG)[0];
+        let mut out = String::new();
+        section.write_to(&mut out, 3);
+
+        assert_eq!(
+            out,
+            "### [1.2.0] - 2024-05-01\n- Added a thing\n- Fixed another thing\n\n#### Notes\nSome notes.\n"
+        );
+    }
+}
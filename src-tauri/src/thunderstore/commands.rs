@@ -1,10 +1,19 @@
+use std::collections::HashMap;
+
 use eyre::{ContextCompat, anyhow};
 use futures_util::TryFutureExt;
+use internment::Intern;
 use itertools::Itertools;
 use tauri::{AppHandle, command};
 use tracing::warn;
 
-use super::{Backend, models::FrontendMod, query::QueryModsArgs};
+use super::{
+    Backend, CustomRepository,
+    fetch::FetchStats,
+    models::{FrontendMod, PackageDependants},
+    query::{QueryModsArgs, filter::ModQuery},
+    watchlist::{WatchedPackage, WatchlistChange},
+};
 use crate::{
     game, logger,
     state::ManagerExt,
@@ -28,6 +37,12 @@ pub fn query_thunderstore(args: QueryModsArgs, app: AppHandle) -> Vec<FrontendMo
     result
 }
 
+/// Checks a search term for invalid filters, returning the error message if there are any.
+#[command]
+pub fn check_mod_query(query: String) -> Option<String> {
+    ModQuery::parse(&query).err().map(|err| format!("{err:#}"))
+}
+
 #[command]
 pub fn stop_querying_thunderstore(app: AppHandle) {
     app.lock_thunderstore().current_query = None;
@@ -60,6 +75,34 @@ pub fn trigger_mod_fetch(app: AppHandle) -> Result<()> {
     Ok(())
 }
 
+#[command]
+pub fn get_package_dependants(
+    mod_ref: ModId,
+    any_version: bool,
+    max_count: Option<usize>,
+    app: AppHandle,
+) -> Result<PackageDependants> {
+    let manager = app.lock_manager();
+    let thunderstore = app.lock_thunderstore();
+
+    let package = mod_ref.borrow(&thunderstore)?;
+    let dependants =
+        thunderstore.dependants_of(package, any_version, manager.active_profile(), max_count);
+
+    Ok(dependants)
+}
+
+/// Returns how much was downloaded and saved by fetches since the app started.
+#[command]
+pub fn get_fetch_stats(app: AppHandle) -> HashMap<Backend, FetchStats> {
+    let thunderstore = app.lock_thunderstore();
+
+    thunderstore
+        .backends()
+        .map(|backend| (backend.backend, backend.fetch_stats))
+        .collect()
+}
+
 #[command]
 pub async fn get_markdown(
     mod_ref: ModId,
@@ -91,10 +134,26 @@ pub fn clear_api_token(backend: Backend) -> Result<()> {
 pub async fn get_categories(game: &str, app: AppHandle) -> Result<Vec<PackageCategory>> {
     let game = game::from_slug(game).context("unknown game")?;
 
-    let tasks = game
-        .backends
-        .iter()
-        .map(|backend| super::get_categories(*backend, game, &app).map_err(|err| (*backend, err)));
+    let backends = {
+        let prefs = app.lock_prefs();
+
+        game.backends
+            .iter()
+            .copied()
+            .chain(
+                prefs
+                    .repositories
+                    .iter()
+                    // local repositories have no categories
+                    .filter(|repository| repository.is_remote() && repository.serves(game))
+                    .map(CustomRepository::backend),
+            )
+            .collect_vec()
+    };
+
+    let tasks = backends.into_iter().map(|backend| {
+        super::get_categories(backend, game, &app).map_err(move |err| (backend, err))
+    });
     let categories = futures_util::future::join_all(tasks)
         .await
         .into_iter()
@@ -112,3 +171,74 @@ pub async fn get_categories(game: &str, app: AppHandle) -> Result<Vec<PackageCat
 
     Ok(categories)
 }
+
+#[command]
+pub fn get_repositories(app: AppHandle) -> Vec<CustomRepository> {
+    app.lock_prefs().repositories.clone()
+}
+
+/// Adds a custom repository, or updates the one with the same id, then refetches packages.
+#[command]
+pub fn set_repository(repository: CustomRepository, app: AppHandle) -> Result<()> {
+    app.lock_prefs().set_repository(repository, app.db())?;
+
+    reload_packages(&app);
+    Ok(())
+}
+
+#[command]
+pub fn remove_repository(id: String, app: AppHandle) -> Result<()> {
+    let backend = Backend::Custom(Intern::from_ref(id.as_str()));
+
+    app.lock_prefs().remove_repository(&id, app.db())?;
+    super::token::clear(backend)?;
+
+    reload_packages(&app);
+    Ok(())
+}
+
+fn reload_packages(app: &AppHandle) {
+    let game = app.lock_manager().active_game;
+    app.lock_thunderstore().switch_game(game, app.clone());
+}
+
+#[command]
+pub fn get_watchlist(app: AppHandle) -> Result<Vec<WatchedPackage>> {
+    let game = app.lock_manager().active_game;
+    let watchlist = app.db().watchlist(&game.slug)?;
+
+    Ok(watchlist)
+}
+
+#[command]
+pub fn watch_package(full_name: String, app: AppHandle) -> Result<()> {
+    let game = app.lock_manager().active_game;
+    super::watchlist::watch(full_name, game, &app)?;
+
+    Ok(())
+}
+
+#[command]
+pub fn unwatch_package(full_name: &str, app: AppHandle) -> Result<()> {
+    let game = app.lock_manager().active_game;
+    app.db().unwatch_package(&game.slug, full_name)?;
+
+    Ok(())
+}
+
+/// Returns the changes to watched packages that haven't been marked as seen yet.
+#[command]
+pub fn get_watchlist_changes(app: AppHandle) -> Result<Vec<WatchlistChange>> {
+    let game = app.lock_manager().active_game;
+    let changes = app.db().unseen_watchlist_changes(&game.slug)?;
+
+    Ok(changes)
+}
+
+#[command]
+pub fn mark_watchlist_seen(app: AppHandle) -> Result<()> {
+    let game = app.lock_manager().active_game;
+    app.db().mark_watchlist_seen(&game.slug)?;
+
+    Ok(())
+}
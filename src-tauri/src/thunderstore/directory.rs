@@ -0,0 +1,233 @@
+//! Packages read from a local directory instead of a server, for setups without
+//! internet access.
+//!
+//! The directory contains package zips named `Owner-Name-Version.zip`, just like the
+//! files downloaded from Thunderstore, each with a `manifest.json` at its root.
+
+use std::{
+    cmp::Reverse,
+    fs::{self, File},
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use eyre::{Context, OptionExt, Result, bail, ensure};
+use internment::Intern;
+use itertools::Itertools;
+use tracing::warn;
+use uuid::Uuid;
+use zip::ZipArchive;
+
+use super::{Backend, PackageListing, PackageVersion, VersionIdent, cache::MarkdownKind};
+use crate::{profile::import::read_manifest, util::error::IoResultExt};
+
+/// The path of a package version's zip in `dir`.
+pub fn package_path(dir: &Path, ident: &VersionIdent) -> PathBuf {
+    dir.join(format!("{ident}.zip"))
+}
+
+/// Reads the manifests of every package zip in `dir`.
+///
+/// Zips that can't be read are skipped with a warning, so one broken file doesn't hide the others.
+pub fn read_packages(dir: &Path, backend: Backend) -> Result<Vec<PackageListing>> {
+    let entries = fs::read_dir(dir).fs_context("reading package directory", dir)?;
+
+    let versions = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "zip"))
+        .filter_map(|path| match read_version(&path, backend) {
+            Ok(version) => Some(version),
+            Err(err) => {
+                warn!("skipping package at {}: {:#}", path.display(), err);
+                None
+            }
+        })
+        .into_group_map_by(|version| version.ident.without_version());
+
+    let packages = versions
+        .into_iter()
+        .map(|(ident, mut versions)| {
+            // the latest version comes first, like in listings from Thunderstore
+            versions.sort_by_key(|version| Reverse(version.parsed_version()));
+
+            let date_created = versions.iter().map(|v| v.date_created).min().unwrap();
+            let date_updated = versions.iter().map(|v| v.date_created).max().unwrap();
+
+            PackageListing {
+                uuid: derive_uuid(backend, ident.as_str()),
+                ident,
+                backend,
+                categories: Default::default(),
+                date_created,
+                date_updated,
+                donation_link: None,
+                has_nsfw_content: false,
+                is_deprecated: false,
+                is_pinned: false,
+                rating_score: 0,
+                versions: versions.into(),
+            }
+        })
+        .collect();
+
+    Ok(packages)
+}
+
+fn read_version(path: &Path, backend: Backend) -> Result<PackageVersion> {
+    let ident: VersionIdent = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.parse().ok())
+        .ok_or_eyre("file name is not formatted as Owner-Name-Version.zip")?;
+
+    ident
+        .version()
+        .parse::<semver::Version>()
+        .context("invalid version number")?;
+
+    let file = File::open(path).fs_context("opening package", path)?;
+    let metadata = file
+        .metadata()
+        .fs_context("reading package metadata", path)?;
+    let mut archive = ZipArchive::new(BufReader::new(file)).context("failed to open zip")?;
+
+    let Some(manifest) = read_manifest(&mut archive)? else {
+        bail!("package has no manifest.json");
+    };
+
+    ensure!(
+        manifest.name == ident.name() && manifest.version_number.to_string() == ident.version(),
+        "manifest describes {}-{} instead",
+        manifest.name,
+        manifest.version_number
+    );
+
+    let date_created = metadata
+        .modified()
+        .map(DateTime::<Utc>::from)
+        .unwrap_or_else(|_| Utc::now());
+
+    Ok(PackageVersion {
+        uuid: derive_uuid(backend, ident.as_str()),
+        ident,
+        date_created,
+        dependencies: manifest.dependencies.into(),
+        suggestions: Box::default(),
+        description: Intern::new(manifest.description),
+        downloads: 0,
+        file_size: metadata.len(),
+        is_active: true,
+        website_url: Intern::new(manifest.website_url),
+    })
+}
+
+/// Derives a stable id for a package or version, so mods installed from the
+/// directory keep their ids when it's read again.
+fn derive_uuid(backend: Backend, name: &str) -> Uuid {
+    let hash = blake3::hash(format!("{backend}/{name}").as_bytes());
+    let mut bytes = [0; 16];
+    bytes.copy_from_slice(&hash.as_bytes()[..16]);
+
+    uuid::Builder::from_custom_bytes(bytes).into_uuid()
+}
+
+/// Reads the readme or changelog from a package's zip, if it has one.
+pub fn read_markdown(
+    dir: &Path,
+    ident: &VersionIdent,
+    kind: MarkdownKind,
+) -> Result<Option<String>> {
+    let path = package_path(dir, ident);
+    let file = File::open(&path).fs_context("opening package", &path)?;
+    let mut archive = ZipArchive::new(BufReader::new(file)).context("failed to open zip")?;
+
+    let file_name = match kind {
+        MarkdownKind::Readme => "README.md",
+        MarkdownKind::Changelog => "CHANGELOG.md",
+    };
+
+    let Ok(mut file) = archive.by_name(file_name) else {
+        return Ok(None);
+    };
+
+    let mut markdown = String::new();
+    file.read_to_string(&mut markdown)
+        .with_context(|| format!("failed to read {file_name}"))?;
+
+    Ok(Some(markdown))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    use super::*;
+
+    fn write_package(dir: &Path, file_name: &str, name: &str, version: &str) {
+        let file = File::create(dir.join(file_name)).unwrap();
+        let mut zip = ZipWriter::new(file);
+
+        let manifest = serde_json::json!({
+            "name": name,
+            "description": "A mod",
+            "version_number": version,
+            "dependencies": [],
+            "website_url": "",
+        });
+
+        zip.start_file("manifest.json", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(manifest.to_string().as_bytes()).unwrap();
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn group_versions_by_package() {
+        let dir = tempfile::tempdir().unwrap();
+
+        write_package(dir.path(), "Owner-Mod-1.0.0.zip", "Mod", "1.0.0");
+        write_package(dir.path(), "Owner-Mod-1.2.0.zip", "Mod", "1.2.0");
+        write_package(dir.path(), "Owner-Mod-1.10.0.zip", "Mod", "1.10.0");
+        write_package(dir.path(), "Owner-Other-0.1.0.zip", "Other", "0.1.0");
+        fs::write(dir.path().join("README.md"), "not a package").unwrap();
+
+        let mut packages = read_packages(dir.path(), Backend::Thunderstore).unwrap();
+        packages.sort_by(|a, b| a.ident.cmp(&b.ident));
+
+        assert_eq!(packages.len(), 2);
+
+        let versions = packages[0]
+            .versions
+            .iter()
+            .map(|version| version.ident.version())
+            .collect_vec();
+
+        assert_eq!(packages[0].ident.as_str(), "Owner-Mod");
+        assert_eq!(versions, ["1.10.0", "1.2.0", "1.0.0"]);
+        assert_eq!(packages[1].ident.as_str(), "Owner-Other");
+
+        // ids stay the same when the directory is read again
+        let again = read_packages(dir.path(), Backend::Thunderstore).unwrap();
+        assert!(again.iter().any(|package| package.uuid == packages[0].uuid));
+    }
+
+    #[test]
+    fn skip_mismatched_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+
+        write_package(dir.path(), "Owner-Mod-1.0.0.zip", "Mod", "1.0.0");
+        write_package(dir.path(), "Owner-Mod-2.0.0.zip", "Mod", "1.0.0");
+        write_package(dir.path(), "Owner-Renamed-1.0.0.zip", "Mod", "1.0.0");
+        write_package(dir.path(), "Mod.zip", "Mod", "1.0.0");
+
+        let packages = read_packages(dir.path(), Backend::Thunderstore).unwrap();
+
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].versions.len(), 1);
+        assert_eq!(packages[0].versions[0].ident.as_str(), "Owner-Mod-1.0.0");
+    }
+}
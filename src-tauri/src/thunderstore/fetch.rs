@@ -1,32 +1,41 @@
 use core::str;
 use std::{
+    collections::{HashMap, HashSet},
     io::Read,
+    path::PathBuf,
     sync::LazyLock,
     time::{Duration, Instant},
 };
 
 use bytes::Bytes;
-use eyre::{Context, Report, Result};
+use eyre::{Context, OptionExt, Report, Result, ensure};
 use flate2::read::GzDecoder;
 use futures_util::{TryFutureExt, future};
+use http_cache_reqwest::CacheMode;
 use indexmap::IndexMap;
+use itertools::Itertools;
+use reqwest::{
+    StatusCode,
+    header::{self, HeaderMap},
+};
 use serde::Serialize;
 use tauri::AppHandle;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
+use uuid::Uuid;
 
 use crate::{
     game::Game,
     logger,
     state::ManagerExt,
-    thunderstore::{Backend, PackageListing},
+    thunderstore::{Backend, PackageListing, directory, watchlist},
 };
 
 pub async fn fetch_package_loop(game: Game, app: AppHandle) {
     let backends = app.lock_prefs().enabled_backends(game);
     future::join_all(
         backends
-            .iter()
+            .into_iter()
             .map(|backend| fetch_single_package_loop(game, app.clone(), backend)),
     )
     .await;
@@ -48,7 +57,7 @@ pub(super) async fn fetch_single_package_loop(game: Game, app: AppHandle, backen
 
         if let Err(err) = loop_iter(game, &mut is_first, &app, backend).await {
             logger::log_webview_err(
-                format!("Error while fetching packages from {backend:?}"),
+                format!("Error while fetching packages from {backend}"),
                 err,
                 &app,
             );
@@ -85,6 +94,21 @@ pub(super) async fn fetch_single_package_loop(game: Game, app: AppHandle, backen
     }
 }
 
+/// Where the packages of a backend are read from.
+enum PackageSource {
+    Index(String),
+    Directory(PathBuf),
+}
+
+impl PackageSource {
+    fn of(backend: Backend, game: Game) -> Option<Self> {
+        match backend.directory() {
+            Some(path) => Some(PackageSource::Directory(path)),
+            None => backend.index_url(game).map(PackageSource::Index),
+        }
+    }
+}
+
 const EXCLUDED_PACKAGES_STR: &str = include_str!("../../excluded_packages.txt");
 
 static EXCLUDED_PACKAGES: LazyLock<Vec<&'static str>> =
@@ -96,7 +120,7 @@ pub(super) async fn fetch_packages(
     app: &AppHandle,
 ) -> Vec<(Backend, Report)> {
     let backends = app.lock_prefs().enabled_backends(game);
-    let result = future::join_all(backends.iter().map(|backend| {
+    let result = future::join_all(backends.into_iter().map(|backend| {
         fetch_single_packages(game, write_directly, app, backend).map_err(move |err| (backend, err))
     }))
     .await
@@ -118,7 +142,7 @@ async fn fetch_single_packages(
 ) -> Result<()> {
     let start_time = Instant::now();
 
-    let Some(index_url) = backend.index_url(game) else {
+    let Some(source) = PackageSource::of(backend, game) else {
         app.lock_thunderstore()
             .backend_mut(backend)
             .packages_fetched = true;
@@ -127,7 +151,10 @@ async fn fetch_single_packages(
 
     emit_event(FetchEvent::Start { backend }, app);
 
-    let res = try_fetch(index_url, write_directly, app, backend).await;
+    let res = match source {
+        PackageSource::Index(index_url) => try_fetch(index_url, write_directly, app, backend).await,
+        PackageSource::Directory(path) => read_directory(path, app, backend).await,
+    };
 
     emit_event(FetchEvent::Done { backend }, app);
 
@@ -141,6 +168,10 @@ async fn fetch_single_packages(
                 start_time.elapsed()
             );
 
+            if let Err(err) = watchlist::check(game, app).await {
+                warn!("failed to check watchlist: {:#}", err);
+            }
+
             Ok(())
         }
         Err(err) => Err(err),
@@ -152,9 +183,8 @@ async fn fetch_single_packages(
         app: &AppHandle,
         backend: Backend,
     ) -> Result<usize> {
-        let bytes = app
-            .http()
-            .get(index_url)
+        let bytes = backend
+            .authorize(app.http().get(index_url))
             .send()
             .await?
             .error_for_status()?
@@ -162,78 +192,215 @@ async fn fetch_single_packages(
             .await?;
 
         let urls: Vec<String> = serde_json::from_reader(GzDecoder::new(&bytes[..]))?;
+        let url_count = urls.len();
+
+        let prev_chunks = app.lock_thunderstore().backend_mut(backend).chunks.clone();
+
+        let requests = urls
+            .into_iter()
+            .map(|url| {
+                let validators = prev_chunks.get(&url).map(|chunk| chunk.validators.clone());
+                (url, validators)
+            })
+            .collect();
 
         let mut package_count = 0;
-        let mut package_buffer = IndexMap::new();
+        let mut stats = FetchStats::default();
+        let mut chunks = HashMap::with_capacity(url_count);
+        // packages from chunks that changed, and those from chunks that didn't
+        let mut changed_packages = IndexMap::new();
+        let mut unchanged_packages = HashSet::new();
 
-        let (tx, mut rx) = mpsc::channel(urls.len());
+        let (tx, mut rx) = mpsc::channel(url_count);
 
         let handle = app.to_owned();
         tokio::spawn(async move {
-            if let Err(err) = fetch_chunks(tx, urls, handle).await {
+            if let Err(err) = fetch_chunks(tx, requests, backend, handle).await {
                 error!("failed to request package listing chunks: {:#}", err);
             }
         });
 
-        while let Some(chunk) = rx.recv().await {
-            let mut text = String::new();
-            let mut decoder = GzDecoder::new(&chunk[..]);
-            decoder.read_to_string(&mut text)?;
-
-            let packages: Vec<PackageListing> = serde_json::from_str(&text)?;
-
-            let packages = packages
-                .into_iter()
-                .filter(|package| {
-                    !EXCLUDED_PACKAGES
-                        .iter()
-                        .any(|excluded| package.full_name() == *excluded)
-                })
-                .map(|package| (package.uuid, PackageListing { backend, ..package }));
-
+        while let Some(response) = rx.recv().await {
             let prev_package_count = package_count;
 
-            if write_directly {
-                let mut state = app.lock_thunderstore();
-                let backend_state = state.backend_mut(backend);
-                let prev_count = backend_state.packages.len();
-                backend_state.packages.extend(packages);
-
-                package_count += backend_state.packages.len() - prev_count;
-            } else {
-                package_buffer.extend(packages);
-
-                package_count = package_buffer.len();
+            let (url, chunk) = match response {
+                ChunkResponse::NotModified { url } => {
+                    let chunk = prev_chunks
+                        .get(&url)
+                        .cloned()
+                        .ok_or_eyre("server responded to an unconditional request with 304")?;
+
+                    stats.unchanged_chunks += 1;
+                    stats.saved_bytes += chunk.size;
+
+                    package_count += chunk.packages.len();
+                    unchanged_packages.extend(chunk.packages.iter().copied());
+
+                    (url, chunk)
+                }
+                ChunkResponse::Modified {
+                    url,
+                    validators,
+                    bytes,
+                } => {
+                    let mut text = String::new();
+                    let mut decoder = GzDecoder::new(&bytes[..]);
+                    decoder.read_to_string(&mut text)?;
+
+                    let packages: Vec<PackageListing> = serde_json::from_str(&text)?;
+
+                    let packages = packages
+                        .into_iter()
+                        .filter(|package| {
+                            !EXCLUDED_PACKAGES
+                                .iter()
+                                .any(|excluded| package.full_name() == *excluded)
+                        })
+                        .map(|package| (package.uuid, PackageListing { backend, ..package }))
+                        .collect_vec();
+
+                    stats.changed_chunks += 1;
+                    stats.downloaded_bytes += bytes.len() as u64;
+
+                    let chunk = ChunkState {
+                        validators,
+                        size: bytes.len() as u64,
+                        packages: packages.iter().map(|(uuid, _)| *uuid).collect(),
+                    };
+
+                    if write_directly {
+                        let mut state = app.lock_thunderstore();
+                        let backend_state = state.backend_mut(backend);
+                        let prev_count = backend_state.packages.len();
+                        backend_state
+                            .extend_packages(packages.into_iter().map(|(_, package)| package));
+
+                        package_count += backend_state.packages.len() - prev_count;
+                    } else {
+                        changed_packages.extend(packages);
+                    }
+
+                    (url, chunk)
+                }
+            };
+
+            chunks.insert(url, chunk);
+
+            if !write_directly {
+                package_count = changed_packages.len() + unchanged_packages.len();
             }
 
             emit_event(
                 FetchEvent::Progress {
                     backend,
-                    mods: package_count - prev_package_count,
+                    mods: package_count.saturating_sub(prev_package_count),
                 },
                 app,
             );
         }
 
+        // otherwise the packages of the missing chunks would be removed
+        ensure!(
+            chunks.len() == url_count,
+            "only received {} out of {} package listing chunks",
+            chunks.len(),
+            url_count
+        );
+
+        debug!(
+            "{} of {} {:?} package chunks were unchanged, saving {} bytes",
+            stats.unchanged_chunks, url_count, backend, stats.saved_bytes
+        );
+
         let mut state = app.lock_thunderstore();
         let backend_state = state.backend_mut(backend);
         backend_state.packages_fetched = true;
 
         if !write_directly {
-            backend_state.packages = package_buffer;
+            // drop packages from chunks that changed or are no longer listed
+            backend_state
+                .packages
+                .retain(|uuid, _| unchanged_packages.contains(uuid));
+            backend_state.packages.extend(changed_packages);
         }
 
-        Ok(backend_state.packages.len())
+        backend_state.chunks = chunks;
+        backend_state.fetch_stats.add(&stats);
+
+        let count = backend_state.packages.len();
+        backend_state.rebuild_indices();
+        state.rebuild_search_index();
+
+        emit_event(FetchEvent::Stats { backend, stats }, app);
+
+        Ok(count)
+    }
+
+    async fn read_directory(path: PathBuf, app: &AppHandle, backend: Backend) -> Result<usize> {
+        let packages =
+            tauri::async_runtime::spawn_blocking(move || directory::read_packages(&path, backend))
+                .await??;
+
+        let count = packages.len();
+        emit_event(
+            FetchEvent::Progress {
+                backend,
+                mods: count,
+            },
+            app,
+        );
+
+        let mut state = app.lock_thunderstore();
+        let backend_state = state.backend_mut(backend);
+
+        backend_state.packages_fetched = true;
+        backend_state.packages = IndexMap::new();
+        backend_state.extend_packages(packages);
+
+        backend_state.rebuild_indices();
+        state.rebuild_search_index();
+
+        Ok(count)
     }
 
     async fn fetch_chunks(
-        tx: mpsc::Sender<Bytes>,
-        urls: Vec<String>,
+        tx: mpsc::Sender<ChunkResponse>,
+        requests: Vec<(String, Option<ChunkValidators>)>,
+        backend: Backend,
         app: AppHandle,
     ) -> Result<()> {
-        for url in urls {
-            let bytes = app.http().get(url).send().await?.bytes().await?;
-            tx.send(bytes)
+        for (url, validators) in requests {
+            // conditional requests are handled here, so keep the http cache out of the way
+            let mut request = backend
+                .authorize(app.http().get(&url))
+                .with_extension(CacheMode::NoStore);
+
+            if let Some(validators) = &validators {
+                if let Some(etag) = &validators.etag {
+                    request = request.header(header::IF_NONE_MATCH, etag);
+                }
+
+                if let Some(last_modified) = &validators.last_modified {
+                    request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+                }
+            }
+
+            let response = request.send().await?.error_for_status()?;
+
+            let chunk = if response.status() == StatusCode::NOT_MODIFIED && validators.is_some() {
+                ChunkResponse::NotModified { url }
+            } else {
+                let validators = ChunkValidators::from_headers(response.headers());
+                let bytes = response.bytes().await?;
+
+                ChunkResponse::Modified {
+                    url,
+                    validators,
+                    bytes,
+                }
+            };
+
+            tx.send(chunk)
                 .await
                 .context("chunk channel closed too early")?;
         }
@@ -242,11 +409,76 @@ async fn fetch_single_packages(
     }
 }
 
+enum ChunkResponse {
+    NotModified {
+        url: String,
+    },
+    Modified {
+        url: String,
+        validators: ChunkValidators,
+        bytes: Bytes,
+    },
+}
+
+/// Headers used to check if a chunk has changed since it was last fetched.
+#[derive(Debug, Clone, Default)]
+pub(super) struct ChunkValidators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl ChunkValidators {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let get = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_owned)
+        };
+
+        Self {
+            etag: get(header::ETAG),
+            last_modified: get(header::LAST_MODIFIED),
+        }
+    }
+}
+
+/// A package listing chunk from the last fetch.
+#[derive(Debug, Clone, Default)]
+pub(super) struct ChunkState {
+    validators: ChunkValidators,
+    /// Size of the compressed chunk in bytes.
+    size: u64,
+    /// The packages that were listed in the chunk.
+    packages: Vec<Uuid>,
+}
+
+/// How much was downloaded while fetching packages, and how much was skipped
+/// because the chunks were unchanged since the previous fetch.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchStats {
+    pub changed_chunks: usize,
+    pub unchanged_chunks: usize,
+    pub downloaded_bytes: u64,
+    pub saved_bytes: u64,
+}
+
+impl FetchStats {
+    fn add(&mut self, other: &FetchStats) {
+        self.changed_chunks += other.changed_chunks;
+        self.unchanged_chunks += other.unchanged_chunks;
+        self.downloaded_bytes += other.downloaded_bytes;
+        self.saved_bytes += other.saved_bytes;
+    }
+}
+
 #[derive(Debug, Serialize, Clone)]
 #[serde(rename_all = "camelCase", tag = "type")]
 enum FetchEvent {
     Start { backend: Backend },
     Progress { backend: Backend, mods: usize },
+    Stats { backend: Backend, stats: FetchStats },
     Done { backend: Backend },
 }
 
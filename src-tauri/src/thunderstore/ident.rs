@@ -44,8 +44,9 @@ impl VersionIdent {
         &self.repr[self.name_start as usize..self.version_start as usize - 1]
     }
 
-    pub fn full_name(&self) -> &str {
-        &self.repr[..self.version_start as usize - 1]
+    /// The `owner-name` part of the identifier, which lives for the rest of the program.
+    pub fn full_name(&self) -> &'static str {
+        &self.repr.as_ref()[..self.version_start as usize - 1]
     }
 
     pub fn version(&self) -> &str {
@@ -57,7 +58,7 @@ impl VersionIdent {
     }
 
     pub fn without_version(&self) -> PackageIdent {
-        let repr = self.repr[..self.version_start as usize - 1].to_string();
+        let repr = Intern::from_ref(self.full_name());
 
         PackageIdent {
             repr,
@@ -205,18 +206,18 @@ where
 
 /// A unique identifier for a package, often formatted as `owner-name`.
 #[derive(Eq, Clone, Serialize, Deserialize)]
-#[serde(into = "String", try_from = "String")]
+#[serde(into = "Intern<String>", try_from = "Intern<String>")]
 pub struct PackageIdent {
-    repr: String,
+    repr: Intern<String>,
     name_start: u32,
 }
 
 impl PackageIdent {
     /// Creates a new identifier with the given parts.
     ///
-    /// This allocates a new string and copies the slices into it.
+    /// This allocates a new string.
     pub fn new(owner: &str, name: &str) -> Self {
-        let repr = format!("{owner}-{name}");
+        let repr = format!("{owner}-{name}").into();
         let name_start = owner.len() as u32 + 1;
         Self { repr, name_start }
     }
@@ -248,13 +249,14 @@ impl PackageIdent {
     }
 
     #[inline]
-    pub fn into_string(self) -> String {
+    pub fn into_string(self) -> Intern<String> {
         self.repr
     }
 
+    /// The interned string, which lives for the rest of the program.
     #[inline]
-    pub fn as_str(&self) -> &str {
-        &self.repr
+    pub fn as_str(&self) -> &'static str {
+        self.repr.as_ref()
     }
 }
 
@@ -288,7 +290,7 @@ impl AsRef<str> for PackageIdent {
     }
 }
 
-impl From<PackageIdent> for String {
+impl From<PackageIdent> for Intern<String> {
     fn from(id: PackageIdent) -> Self {
         id.into_string()
     }
@@ -306,13 +308,13 @@ impl Debug for PackageIdent {
     }
 }
 
-impl TryFrom<String> for PackageIdent {
+impl TryFrom<Intern<String>> for PackageIdent {
     type Error = ParseError;
 
-    /// Parses a string into a `VersionIdent`.
+    /// Parses a string into a `PackageIdent`.
     ///
     /// This does not allocate or copy memory.
-    fn try_from(value: String) -> Result<Self, ParseError> {
+    fn try_from(value: Intern<String>) -> Result<Self, ParseError> {
         let mut indices = value.match_indices('-').map(|(i, _)| i);
 
         let name_start = indices.next_back().ok_or(ParseError)? as u32 + 1;
@@ -328,7 +330,7 @@ impl FromStr for PackageIdent {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, ParseError> {
-        s.to_string().try_into()
+        Intern::<String>::from_ref(s).try_into()
     }
 }
 
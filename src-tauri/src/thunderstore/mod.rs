@@ -1,22 +1,30 @@
-use eyre::Result;
+use eyre::{Result, eyre};
 use itertools::Itertools;
 use query::QueryModsArgs;
+use search::SearchIndex;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashSet, VecDeque},
+    cmp::Reverse,
+    collections::{BTreeMap, HashSet, VecDeque},
     hash::Hash,
     iter::FusedIterator,
     str::{self},
+    time::Instant,
 };
 use tauri::{AppHandle, async_runtime::JoinHandle};
+use tracing::debug;
 use uuid::Uuid;
 
-use crate::{game::Game, state::ManagerExt, thunderstore::query::Queryable};
+use crate::{game::Game, profile::Profile, state::ManagerExt, thunderstore::query::Queryable};
 
 pub mod cache;
+pub mod changelog;
 pub mod commands;
+pub mod directory;
 pub mod query;
+pub mod search;
 pub mod token;
+pub mod watchlist;
 
 mod fetch;
 pub use fetch::wait_for_fetch;
@@ -28,8 +36,10 @@ mod ident;
 pub use ident::*;
 
 mod backend;
-pub use backend::Backend;
+#[cfg(feature = "bench")]
+pub mod bench;
 use backend::ThunderstoreBackend;
+pub use backend::{Backend, CustomRepository, set_repositories};
 
 pub fn start(app: &AppHandle) {
     query::setup(app);
@@ -128,8 +138,10 @@ pub struct Thunderstore {
     /// Whether a [`fetch_mods`] task is currently running.
     is_fetching: bool,
     current_query: Option<QueryModsArgs>,
-    thunderstore_backend: ThunderstoreBackend,
-    hexium_backend: ThunderstoreBackend,
+    /// Thunderstore and Hexium are always present, custom repositories are added
+    /// once they're enabled for the active game and dropped in [`Thunderstore::switch_game`].
+    backends: BTreeMap<Backend, ThunderstoreBackend>,
+    search_index: SearchIndex,
 }
 
 impl Thunderstore {
@@ -138,18 +150,27 @@ impl Thunderstore {
             fetch_loop_handle: None,
             is_fetching: false,
             current_query: None,
-            thunderstore_backend: ThunderstoreBackend::new(Backend::Thunderstore),
-            hexium_backend: ThunderstoreBackend::new(Backend::Hexium),
+            backends: [Backend::Thunderstore, Backend::Hexium]
+                .into_iter()
+                .map(|backend| (backend, ThunderstoreBackend::new(backend)))
+                .collect(),
+            search_index: SearchIndex::default(),
         }
     }
 
     /// Whether packages have been succesfully fetched at least one since
     /// the last call to [`Thunderstore::switch_game`].
+    ///
+    /// Custom repositories aren't waited for, since they may be unreachable indefinitely.
     pub fn packages_fetched(&self, app: &AppHandle, game: Game) -> bool {
         let backends = app.lock_prefs().enabled_backends(game);
         backends
-            .iter()
-            .all(|backend| self.backend(backend).packages_fetched())
+            .into_iter()
+            .filter(|backend| !matches!(backend, Backend::Custom(_)))
+            .all(|backend| {
+                self.backend(backend)
+                    .is_ok_and(ThunderstoreBackend::packages_fetched)
+            })
     }
 
     pub fn deduplicate<T: Queryable>(mods: impl Iterator<Item = T>) -> impl Iterator<Item = T> {
@@ -166,54 +187,63 @@ impl Thunderstore {
     /// Returns an iterator over the latest versions of every package.
     /// Without deduplication, usable for filtering. Call [`Thunderstore::deduplicate`] afterwards.
     pub fn latest(&self) -> impl Iterator<Item = BorrowedMod<'_>> {
-        self.thunderstore_backend
-            .latest()
-            .chain(self.hexium_backend.latest())
+        self.backends.values().flat_map(ThunderstoreBackend::latest)
     }
 
-    fn resolve_thunderstore_vs_hexium<'a, R: 'a>(
+    /// Calls `f` with every backend and picks the best of the results with `cmp`.
+    ///
+    /// If every backend fails, the error of the first one is returned.
+    fn resolve_across_backends<'a, R: 'a>(
         &'a self,
         f: impl Fn(&'a ThunderstoreBackend) -> Result<R>,
         cmp: impl Fn(R, R) -> R,
     ) -> Result<R> {
-        let thunderstore = f(&self.thunderstore_backend);
-        let hexium = f(&self.hexium_backend);
-        match (thunderstore, hexium) {
-            (Ok(thunderstore), Ok(hexium)) => Ok(cmp(thunderstore, hexium)),
-            (Ok(thunderstore), Err(_)) => Ok(thunderstore),
-            (Err(_), Ok(hexium)) => Ok(hexium),
-            (Err(e), Err(_)) => Err(e),
+        let mut first_err = None;
+        let mut best = None;
+
+        for result in self.backends.values().map(f) {
+            match (result, best.take()) {
+                (Ok(value), Some(current)) => best = Some(cmp(current, value)),
+                (Ok(value), None) => best = Some(value),
+                (Err(err), current) => {
+                    first_err.get_or_insert(err);
+                    best = current;
+                }
+            }
+        }
+
+        match (best, first_err) {
+            (Some(best), _) => Ok(best),
+            (None, Some(err)) => Err(err),
+            (None, None) => Err(eyre!("no backends available")),
         }
     }
 
     fn cmp_package_listing<'a>(
-        thunderstore: &'a PackageListing,
-        hexium: &'a PackageListing,
+        first: &'a PackageListing,
+        second: &'a PackageListing,
     ) -> &'a PackageListing {
-        if thunderstore.latest().version() >= hexium.latest().version() {
-            thunderstore
+        if first.latest().version() >= second.latest().version() {
+            first
         } else {
-            hexium
+            second
         }
     }
 
     pub fn get_package(&self, uuid: Uuid) -> Result<&PackageListing> {
-        self.resolve_thunderstore_vs_hexium(|b| b.get_package(uuid), Self::cmp_package_listing)
+        self.resolve_across_backends(|b| b.get_package(uuid), Self::cmp_package_listing)
     }
 
     /// Finds a package with the given `full_name` (formatted as `owner-name`).
     pub fn find_package(&self, full_name: &str) -> Result<&PackageListing> {
-        self.resolve_thunderstore_vs_hexium(
-            |b| b.find_package(full_name),
-            Self::cmp_package_listing,
-        )
+        self.resolve_across_backends(|b| b.find_package(full_name), Self::cmp_package_listing)
     }
 
-    fn cmp_borrowed_mod<T: Queryable>(thunderstore: T, hexium: T) -> T {
-        if thunderstore.version() >= hexium.version() {
-            thunderstore
+    fn cmp_borrowed_mod<T: Queryable>(first: T, second: T) -> T {
+        if first.version() >= second.version() {
+            first
         } else {
-            hexium
+            second
         }
     }
 
@@ -223,7 +253,7 @@ impl Thunderstore {
         version_uuid: Uuid,
         backend: Backend,
     ) -> Result<BorrowedMod<'_>> {
-        self.backend(backend).get_mod(package_uuid, version_uuid)
+        self.backend(backend)?.get_mod(package_uuid, version_uuid)
     }
 
     pub fn find_ident(&self, ident: &VersionIdent) -> Result<BorrowedMod<'_>> {
@@ -236,10 +266,7 @@ impl Thunderstore {
         name: &str,
         version: &str,
     ) -> Result<BorrowedMod<'a>> {
-        self.resolve_thunderstore_vs_hexium(
-            |b| b.find_mod(owner, name, version),
-            Self::cmp_borrowed_mod,
-        )
+        self.resolve_across_backends(|b| b.find_mod(owner, name, version), Self::cmp_borrowed_mod)
     }
 
     /// Switches the active game, clearing the package map and aborting ongoing fetch tasks.
@@ -252,32 +279,55 @@ impl Thunderstore {
 
         {
             let prefs = app.lock_prefs();
+            let enabled = prefs.enabled_backends(game);
 
-            self.thunderstore_backend.clear_packages();
-            self.hexium_backend.clear_packages();
+            // drop custom repositories that were removed or don't serve this game
+            self.backends.retain(|backend, _| {
+                !matches!(backend, Backend::Custom(_)) || enabled.contains(backend)
+            });
 
-            for backend in prefs.enabled_backends(game).iter() {
+            for backend in self.backends.values_mut() {
+                backend.clear_packages();
+            }
+
+            for backend in enabled {
                 let backend = self.backend_mut(backend);
                 backend.read_and_insert_cache(game, &prefs);
+                backend.rebuild_indices();
             }
         }
 
+        self.rebuild_search_index();
+
         let load_mods_handle = tauri::async_runtime::spawn(fetch::fetch_package_loop(game, app));
         self.fetch_loop_handle = Some(load_mods_handle);
     }
 
-    pub fn backend(&self, backend: Backend) -> &ThunderstoreBackend {
-        match backend {
-            Backend::Thunderstore => &self.thunderstore_backend,
-            Backend::Hexium => &self.hexium_backend,
-        }
+    /// Indexes the currently fetched packages for [`query::query_mods`].
+    pub fn rebuild_search_index(&mut self) {
+        let start = Instant::now();
+
+        let index = SearchIndex::build(self.latest());
+        self.search_index = index;
+
+        debug!("rebuilt search index in {:?}", start.elapsed());
     }
 
+    pub fn backend(&self, backend: Backend) -> Result<&ThunderstoreBackend> {
+        self.backends
+            .get(&backend)
+            .ok_or_else(|| eyre!("{backend} is not enabled"))
+    }
+
+    /// Gets the state of `backend`, adding it if it's a newly enabled custom repository.
     pub fn backend_mut(&mut self, backend: Backend) -> &mut ThunderstoreBackend {
-        match backend {
-            Backend::Thunderstore => &mut self.thunderstore_backend,
-            Backend::Hexium => &mut self.hexium_backend,
-        }
+        self.backends
+            .entry(backend)
+            .or_insert_with(|| ThunderstoreBackend::new(backend))
+    }
+
+    pub fn backends(&self) -> impl Iterator<Item = &ThunderstoreBackend> {
+        self.backends.values()
     }
 }
 
@@ -336,6 +386,43 @@ impl Thunderstore {
             thunderstore: self,
         }
     }
+
+    /// Finds the packages in every backend whose latest version depends on `package`,
+    /// sorted by descending downloads.
+    ///
+    /// If `any_version` is false, only packages that depend on the given version are included.
+    pub fn dependants_of(
+        &self,
+        package: BorrowedMod<'_>,
+        any_version: bool,
+        profile: &Profile,
+        max_count: Option<usize>,
+    ) -> PackageDependants {
+        let full_name = package.package.full_name();
+        let version = package.version.version();
+
+        let mut dependants = self
+            .backends
+            .values()
+            .flat_map(|backend| backend.dependants(full_name))
+            .filter(|(_, dependency)| any_version || dependency.version() == version)
+            .map(|(dependant, dependency)| PackageDependant {
+                full_name: dependant.package.full_name().to_owned(),
+                id: dependant.into(),
+                dependency_version: dependency.version().to_owned(),
+                downloads: dependant.package.total_downloads(),
+                rating: dependant.package.rating_score,
+                is_installed: profile.has_mod(dependant.package.uuid),
+            })
+            .collect_vec();
+
+        dependants.sort_by_key(|dependant| Reverse(dependant.downloads));
+
+        let total = dependants.len();
+        dependants.truncate(max_count.unwrap_or(usize::MAX));
+
+        PackageDependants { total, dependants }
+    }
 }
 
 async fn get_categories(
@@ -343,10 +430,9 @@ async fn get_categories(
     game: Game,
     app: &AppHandle,
 ) -> Result<Vec<PackageCategory>> {
-    let url = backend.category_url(game);
-    let response: CategoryResponse = app
-        .http()
-        .get(url)
+    let url = backend.category_url(game)?;
+    let response: CategoryResponse = backend
+        .authorize(app.http().get(url))
         .send()
         .await?
         .error_for_status()?
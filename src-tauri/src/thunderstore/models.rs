@@ -1,8 +1,4 @@
-use std::{
-    collections::{HashMap, HashSet},
-    hash::Hash,
-    path::PathBuf,
-};
+use std::{collections::HashMap, hash::Hash, path::PathBuf};
 
 use chrono::{DateTime, Utc};
 use internment::Intern;
@@ -10,27 +6,30 @@ use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use super::{Backend, PackageIdent, VersionIdent};
+use super::{Backend, ModId, PackageIdent, VersionIdent};
 use crate::{game::Game, profile::Profile};
 
+/// A package from the mod index.
+///
+/// Every game has tens of thousands of these in memory at once, so strings are interned
+/// and lists are boxed slices, which don't keep any spare capacity around.
 #[derive(Serialize, Deserialize, Debug, Clone, Eq)]
 pub struct PackageListing {
     #[serde(rename = "full_name")]
     pub ident: PackageIdent,
     #[serde(default, skip_serializing)]
     pub backend: Backend,
-    pub categories: HashSet<Intern<String>>,
+    pub categories: Box<[Intern<String>]>,
     pub date_created: DateTime<Utc>,
     pub date_updated: DateTime<Utc>,
-    pub donation_link: Option<String>,
+    pub donation_link: Option<Intern<String>>,
     pub has_nsfw_content: bool,
     pub is_deprecated: bool,
     pub is_pinned: bool,
-    pub package_url: String,
     pub rating_score: u32,
     #[serde(rename = "uuid4")]
     pub uuid: Uuid,
-    pub versions: Vec<PackageVersion>,
+    pub versions: Box<[PackageVersion]>,
 }
 
 impl PackageListing {
@@ -74,11 +73,11 @@ impl PackageListing {
         self.versions.iter().map(|v| v.downloads).sum()
     }
 
-    pub fn owner_url(&self, game: Game) -> String {
+    pub fn owner_url(&self, game: Game) -> eyre::Result<String> {
         self.backend.owner_url(self.owner(), game)
     }
 
-    pub fn url(&self, game: Game) -> String {
+    pub fn url(&self, game: Game) -> eyre::Result<String> {
         self.backend.mod_url(&self.ident, game)
     }
 }
@@ -101,9 +100,9 @@ pub struct PackageVersion {
     #[serde(rename = "full_name")]
     pub ident: VersionIdent,
     pub date_created: DateTime<Utc>,
-    pub dependencies: Vec<VersionIdent>,
+    pub dependencies: Box<[VersionIdent]>,
     #[serde(default)]
-    pub suggestions: Vec<VersionIdent>,
+    pub suggestions: Box<[VersionIdent]>,
     pub description: Intern<String>,
     pub downloads: u32,
     pub file_size: u64,
@@ -290,6 +289,27 @@ pub struct FrontendProfileMod {
     pub data: FrontendMod,
 }
 
+/// A package that depends on another, see [`super::Thunderstore::dependants_of`].
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageDependant {
+    pub full_name: String,
+    pub id: ModId,
+    /// The version of the dependency that the package depends on.
+    pub dependency_version: String,
+    pub downloads: u32,
+    pub rating: u32,
+    pub is_installed: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageDependants {
+    /// The number of dependants before `max_count` was applied.
+    pub total: usize,
+    pub dependants: Vec<PackageDependant>,
+}
+
 pub trait IntoFrontendMod {
     fn into_frontend(self, profile: Option<&Profile>) -> FrontendMod;
 }
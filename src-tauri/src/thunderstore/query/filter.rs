@@ -0,0 +1,395 @@
+//! Filters that can be written in the search bar along with the search term, like
+//! `author:Owner cat:Items -cat:Cosmetics downloads:>10k updated:<30d installed`.
+//!
+//! Any filter can be negated with a leading `-`. Values with spaces can be quoted,
+//! for example `cat:"Server-side"`. Everything that isn't a filter is part of the search term.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use eyre::{OptionExt, Result, bail, eyre};
+use internment::Intern;
+use uuid::Uuid;
+
+use super::{QueryModsArgs, Queryable};
+use crate::{
+    profile::Profile,
+    thunderstore::{Thunderstore, VersionIdent, search::Search},
+};
+
+const FILTER_KEYS: &str = "author, cat, updated, downloads, rating and depends-on";
+
+/// A parsed search term with its filters.
+#[derive(Debug, Default)]
+pub struct ModQuery {
+    pub search: Search,
+    filters: Vec<Filter>,
+}
+
+impl ModQuery {
+    pub fn parse(query: &str) -> Result<Self> {
+        let mut text = Vec::new();
+        let mut filters = Vec::new();
+
+        for word in split_words(query)? {
+            match Filter::parse(&word)? {
+                Some(filter) => filters.push(filter),
+                None => text.push(word),
+            }
+        }
+
+        Ok(Self {
+            search: Search::new(&text.join(" ")),
+            filters,
+        })
+    }
+
+    /// Parses the search term of `args`.
+    ///
+    /// Returns `None` if it's invalid, in which case nothing should match.
+    /// The error is shown to the user through [`check_mod_query`](crate::thunderstore::commands::check_mod_query).
+    pub fn from_args(args: &QueryModsArgs) -> Option<Self> {
+        match &args.search_term {
+            Some(term) => Self::parse(term).ok(),
+            None => Some(Self::default()),
+        }
+    }
+
+    /// Whether `queryable` passes every filter of the query.
+    pub fn matches(&self, queryable: &impl Queryable, ctx: &FilterContext) -> bool {
+        if self.filters.is_empty() {
+            return true;
+        }
+
+        let data = queryable.filter_data();
+        self.filters.iter().all(|filter| filter.matches(&data, ctx))
+    }
+}
+
+/// Splits `query` at whitespace, except inside of double quotes.
+fn split_words(query: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for char in query.chars() {
+        match char {
+            '"' => in_quotes = !in_quotes,
+            char if char.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+            }
+            char => current.push(char),
+        }
+    }
+
+    if in_quotes {
+        bail!("missing closing quote");
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    Ok(words)
+}
+
+/// The data of a package that filters are checked against.
+///
+/// Fields that don't apply to a package, like the downloads of a local mod, are left empty
+/// and don't match any filter.
+#[derive(Debug, Default)]
+pub struct FilterData<'a> {
+    pub uuid: Option<Uuid>,
+    pub owner: Option<&'a str>,
+    pub categories: Option<&'a [Intern<String>]>,
+    pub date_updated: Option<DateTime<Utc>>,
+    pub downloads: Option<u32>,
+    pub rating: Option<u32>,
+    pub dependencies: &'a [VersionIdent],
+}
+
+/// State outside of the packages that some filters depend on.
+pub struct FilterContext<'a> {
+    profile: &'a Profile,
+    thunderstore: &'a Thunderstore,
+    now: DateTime<Utc>,
+}
+
+impl<'a> FilterContext<'a> {
+    pub fn new(profile: &'a Profile, thunderstore: &'a Thunderstore) -> Self {
+        Self {
+            profile,
+            thunderstore,
+            now: Utc::now(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Filter {
+    negated: bool,
+    kind: FilterKind,
+}
+
+#[derive(Debug, PartialEq)]
+enum FilterKind {
+    Author(String),
+    Category(String),
+    /// Compares the time since the package was last updated.
+    Updated(Comparison<TimeDelta>),
+    Downloads(Comparison<u64>),
+    Rating(Comparison<u64>),
+    Installed,
+    HasUpdate,
+    DependsOn(String),
+}
+
+impl Filter {
+    /// Parses a single word of the query, or returns `None` if it's not a filter.
+    fn parse(word: &str) -> Result<Option<Self>> {
+        let (negated, filter) = match word.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, word),
+        };
+
+        let kind = match filter.to_lowercase().as_str() {
+            "installed" => Some(FilterKind::Installed),
+            "has-update" => Some(FilterKind::HasUpdate),
+            _ => None,
+        };
+
+        if let Some(kind) = kind {
+            return Ok(Some(Filter { negated, kind }));
+        }
+
+        let Some((key, value)) = filter.split_once(':') else {
+            return Ok(None);
+        };
+
+        if value.is_empty() {
+            bail!("missing value after '{}:'", key);
+        }
+
+        let kind = match key.to_lowercase().as_str() {
+            "author" => FilterKind::Author(value.to_owned()),
+            "cat" => FilterKind::Category(value.to_owned()),
+            "updated" => FilterKind::Updated(Comparison::parse(value, Op::Less, parse_age)?),
+            "downloads" => FilterKind::Downloads(Comparison::parse(value, Op::Equal, parse_count)?),
+            "rating" => FilterKind::Rating(Comparison::parse(value, Op::Equal, parse_count)?),
+            "depends-on" => FilterKind::DependsOn(value.to_owned()),
+            _ => bail!(
+                "unknown filter '{}:', the available filters are {}",
+                key,
+                FILTER_KEYS
+            ),
+        };
+
+        Ok(Some(Filter { negated, kind }))
+    }
+
+    fn matches(&self, data: &FilterData, ctx: &FilterContext) -> bool {
+        let matches = match &self.kind {
+            FilterKind::Author(author) => data
+                .owner
+                .is_some_and(|owner| owner.eq_ignore_ascii_case(author)),
+            FilterKind::Category(category) => data.categories.is_some_and(|categories| {
+                categories
+                    .iter()
+                    .any(|candidate| candidate.eq_ignore_ascii_case(category))
+            }),
+            FilterKind::Updated(cmp) => data
+                .date_updated
+                .is_some_and(|date| cmp.matches(ctx.now - date)),
+            FilterKind::Downloads(cmp) => data
+                .downloads
+                .is_some_and(|downloads| cmp.matches(downloads as u64)),
+            FilterKind::Rating(cmp) => data.rating.is_some_and(|rating| cmp.matches(rating as u64)),
+            FilterKind::Installed => data.uuid.is_some_and(|uuid| ctx.profile.has_mod(uuid)),
+            FilterKind::HasUpdate => data
+                .uuid
+                .is_some_and(|uuid| ctx.profile.has_update(uuid, ctx.thunderstore)),
+            FilterKind::DependsOn(full_name) => data
+                .dependencies
+                .iter()
+                .any(|dependency| dependency.full_name().eq_ignore_ascii_case(full_name)),
+        };
+
+        matches != self.negated
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+#[derive(Debug, PartialEq)]
+struct Comparison<T> {
+    op: Op,
+    value: T,
+}
+
+impl<T: PartialOrd> Comparison<T> {
+    /// Parses an optional operator followed by a value, like `>=100`.
+    fn parse(str: &str, default_op: Op, parse_value: impl Fn(&str) -> Result<T>) -> Result<Self> {
+        const OPS: [(&str, Op); 5] = [
+            (">=", Op::GreaterOrEqual),
+            ("<=", Op::LessOrEqual),
+            (">", Op::Greater),
+            ("<", Op::Less),
+            ("=", Op::Equal),
+        ];
+
+        let (op, value) = OPS
+            .iter()
+            .find_map(|(prefix, op)| str.strip_prefix(prefix).map(|rest| (*op, rest)))
+            .unwrap_or((default_op, str));
+
+        Ok(Self {
+            op,
+            value: parse_value(value)?,
+        })
+    }
+
+    fn matches(&self, value: T) -> bool {
+        match self.op {
+            Op::Less => value < self.value,
+            Op::LessOrEqual => value <= self.value,
+            Op::Equal => value == self.value,
+            Op::GreaterOrEqual => value >= self.value,
+            Op::Greater => value > self.value,
+        }
+    }
+}
+
+/// Parses a number with an optional `k` (thousands) or `m` (millions) suffix.
+fn parse_count(str: &str) -> Result<u64> {
+    let (number, multiplier) = match str.chars().last().map(|char| char.to_ascii_lowercase()) {
+        Some('k') => (&str[..str.len() - 1], 1_000),
+        Some('m') => (&str[..str.len() - 1], 1_000_000),
+        _ => (str, 1),
+    };
+
+    let Ok(number) = number.parse::<u64>() else {
+        bail!("expected a number like 500 or 10k, found '{}'", str);
+    };
+
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| eyre!("number '{}' is too large", str))
+}
+
+/// Parses a duration like `12h`, `30d`, `2w`, `6m` or `1y`.
+fn parse_age(str: &str) -> Result<TimeDelta> {
+    let unit = str
+        .chars()
+        .last()
+        .ok_or_eyre("expected a duration like 30d")?;
+
+    let amount = str[..str.len() - unit.len_utf8()].parse::<i64>().ok();
+
+    let too_long = || eyre!("duration '{}' is too long", str);
+
+    let days = match (amount, unit.to_ascii_lowercase()) {
+        (Some(amount), 'h') => return TimeDelta::try_hours(amount).ok_or_else(too_long),
+        (Some(amount), 'd') => Some(amount),
+        (Some(amount), 'w') => amount.checked_mul(7),
+        (Some(amount), 'm') => amount.checked_mul(30),
+        (Some(amount), 'y') => amount.checked_mul(365),
+        _ => bail!(
+            "expected a duration like 30d, with a unit of h, d, w, m or y, found '{}'",
+            str
+        ),
+    };
+
+    days.and_then(TimeDelta::try_days).ok_or_else(too_long)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_filters(query: &str) -> Vec<Filter> {
+        ModQuery::parse(query).unwrap().filters
+    }
+
+    #[test]
+    fn parse_filter_kinds() {
+        assert_eq!(
+            parse_filters("author:Owner -cat:Cosmetics installed"),
+            [
+                Filter {
+                    negated: false,
+                    kind: FilterKind::Author("Owner".into()),
+                },
+                Filter {
+                    negated: true,
+                    kind: FilterKind::Category("Cosmetics".into()),
+                },
+                Filter {
+                    negated: false,
+                    kind: FilterKind::Installed,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_comparisons() {
+        assert_eq!(
+            parse_filters("downloads:>10k updated:30d"),
+            [
+                Filter {
+                    negated: false,
+                    kind: FilterKind::Downloads(Comparison {
+                        op: Op::Greater,
+                        value: 10_000,
+                    }),
+                },
+                Filter {
+                    negated: false,
+                    kind: FilterKind::Updated(Comparison {
+                        op: Op::Less,
+                        value: TimeDelta::days(30),
+                    }),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_quotes() {
+        let query = ModQuery::parse("cat:\"Server side\" more suits").unwrap();
+
+        assert_eq!(
+            query.filters,
+            [Filter {
+                negated: false,
+                kind: FilterKind::Category("Server side".into()),
+            }]
+        );
+        assert!(!query.search.is_empty());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(ModQuery::parse("foo:bar").is_err());
+        assert!(ModQuery::parse("author:").is_err());
+        assert!(ModQuery::parse("downloads:>lots").is_err());
+        assert!(ModQuery::parse("updated:<30x").is_err());
+        assert!(ModQuery::parse("cat:\"Items").is_err());
+    }
+
+    #[test]
+    fn parse_overflow() {
+        assert!(ModQuery::parse("updated:<99999999999999d").is_err());
+        assert!(ModQuery::parse("updated:<9999999999999999h").is_err());
+        assert!(ModQuery::parse("updated:<9223372036854775807y").is_err());
+        assert!(ModQuery::parse("downloads:>99999999999999999m").is_err());
+        assert!(ModQuery::parse("rating:>18446744073709551615k").is_err());
+    }
+}
@@ -11,11 +11,14 @@ use super::{
     BorrowedMod, Thunderstore,
     models::{FrontendMod, FrontendModKind, FrontendVersion, IntoFrontendMod},
 };
+
+pub mod filter;
 use crate::{
     profile::{LocalMod, ModManager, Profile},
     state::ManagerExt,
     util,
 };
+use filter::{FilterContext, FilterData, ModQuery};
 
 pub fn setup(app: &AppHandle) {
     tauri::async_runtime::spawn(query_loop(app.clone()));
@@ -33,6 +36,8 @@ pub enum SortBy {
     InstallDate,
     Custom,
     DiskSpace,
+    /// How well the package matches the search term, see [`super::search`].
+    Relevance,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -100,6 +105,9 @@ pub trait Queryable {
     fn description(&self) -> Option<&str> {
         None
     }
+
+    /// The data that search bar filters are checked against.
+    fn filter_data(&self) -> FilterData<'_>;
 }
 
 impl Queryable for BorrowedMod<'_> {
@@ -115,6 +123,20 @@ impl Queryable for BorrowedMod<'_> {
         Some(&self.version.description)
     }
 
+    fn filter_data(&self) -> FilterData<'_> {
+        let pkg = self.package;
+
+        FilterData {
+            uuid: Some(pkg.uuid),
+            owner: Some(pkg.owner()),
+            categories: Some(&pkg.categories),
+            date_updated: Some(pkg.date_updated),
+            downloads: Some(pkg.total_downloads()),
+            rating: Some(pkg.rating_score),
+            dependencies: &self.version.dependencies,
+        }
+    }
+
     fn matches(&self, args: &QueryModsArgs) -> bool {
         let pkg = self.package;
 
@@ -124,15 +146,17 @@ impl Queryable for BorrowedMod<'_> {
             return false;
         }
 
-        if !args.include_categories.is_empty()
-            && args.include_categories.is_disjoint(&pkg.categories)
-        {
+        let has_category = |categories: &HashSet<Intern<String>>| {
+            pkg.categories
+                .iter()
+                .any(|category| categories.contains(category))
+        };
+
+        if !args.include_categories.is_empty() && !has_category(&args.include_categories) {
             return false;
         }
 
-        if !args.exclude_categories.is_empty()
-            && !args.exclude_categories.is_disjoint(&pkg.categories)
-        {
+        if !args.exclude_categories.is_empty() && has_category(&args.exclude_categories) {
             return false;
         }
 
@@ -153,6 +177,7 @@ impl Queryable for BorrowedMod<'_> {
                 SortBy::DiskSpace => self.version.file_size.cmp(&other.version.file_size),
                 SortBy::InstallDate => Ordering::Equal,
                 SortBy::Custom => Ordering::Equal,
+                SortBy::Relevance => Ordering::Equal,
             };
 
             match args.sort_order {
@@ -186,9 +211,9 @@ impl IntoFrontendMod for BorrowedMod<'_> {
             } else {
                 Some(vers.website_url.to_string())
             },
-            donate_url: pkg.donation_link.clone(),
-            dependencies: Some(vers.dependencies.clone()),
-            suggestions: Some(vers.suggestions.clone()),
+            donate_url: pkg.donation_link.map(|link| link.to_string()),
+            dependencies: Some(vers.dependencies.to_vec()),
+            suggestions: Some(vers.suggestions.to_vec()),
             is_pinned: pkg.is_pinned,
             is_deprecated: pkg.is_deprecated,
             contains_nsfw: pkg.has_nsfw_content,
@@ -248,51 +273,130 @@ impl Thunderstore {
     ) -> Vec<FrontendMod> {
         let profile = manager.active_profile();
 
-        query_mods(
-            args,
-            self.latest()
-                .filter(|borrowed| !manager.hidden_mods.contains(&borrowed.package.uuid)),
-        )
-        .map(|m| m.into_frontend(Some(profile)))
-        .collect()
+        let query = ModQuery::from_args(args);
+        let ctx = FilterContext::new(profile, self);
+
+        let scores = query
+            .as_ref()
+            .map(|query| self.search_index.search(&query.search))
+            .unwrap_or_default();
+
+        let mods = self
+            .latest()
+            .filter(|borrowed| !manager.hidden_mods.contains(&borrowed.package.uuid))
+            .filter_map(|borrowed| {
+                let query = query.as_ref()?;
+                if !query.matches(&borrowed, &ctx) {
+                    return None;
+                }
+
+                let uuid = borrowed.package.uuid;
+                let relevance = if self.search_index.contains(uuid) {
+                    scores.get(&uuid).copied()
+                } else {
+                    // the package was fetched after the index was last built
+                    query
+                        .search
+                        .score(borrowed.full_name(), borrowed.description())
+                }?;
+
+                Some(Ranked {
+                    inner: borrowed,
+                    relevance,
+                })
+            });
+
+        rank(args, mods)
+            .map(|m| m.into_frontend(Some(profile)))
+            .collect()
     }
 }
 
 /// Sorts and filters `mods` according to `args`.
-pub fn query_mods<'a, T, I>(args: &QueryModsArgs, mods: I) -> impl Iterator<Item = T> + 'a
+pub fn query_mods<'a, T, I>(
+    args: &QueryModsArgs,
+    mods: I,
+    ctx: &'a FilterContext,
+) -> impl Iterator<Item = T> + 'a
 where
     T: Queryable + 'a,
     I: Iterator<Item = T> + 'a,
 {
-    let search_terms = args.search_term.as_ref().map(|str| {
-        let full = str.to_lowercase().trim().to_owned();
-        let package = full.replace(' ', "_");
-        // search for packages with underscores and descriptions with spaces
-        (full, package)
+    let query = ModQuery::from_args(args);
+
+    let mods = mods.filter_map(move |queryable| {
+        let query = query.as_ref()?;
+        if !query.matches(&queryable, ctx) {
+            return None;
+        }
+
+        let relevance = query
+            .search
+            .score(queryable.full_name(), queryable.description())?;
+
+        Some(Ranked {
+            inner: queryable,
+            relevance,
+        })
     });
 
-    let results = mods.filter(|queryable| {
-        if let Some((full_search, package_search)) = &search_terms {
-            let name_match = queryable
-                .full_name()
-                .to_lowercase()
-                .contains(package_search);
+    rank(args, mods)
+}
 
-            let description_match = queryable
-                .description()
-                .is_some_and(|description| description.to_lowercase().contains(full_search));
+/// A package along with how well it matches the search term.
+struct Ranked<T> {
+    inner: T,
+    relevance: f32,
+}
 
-            if !name_match && !description_match {
-                return false;
-            }
-        }
+impl<T: Queryable> Queryable for Ranked<T> {
+    fn full_name(&self) -> &str {
+        self.inner.full_name()
+    }
 
-        queryable.matches(args)
-    });
+    fn version(&self) -> Option<semver::Version> {
+        self.inner.version()
+    }
+
+    fn description(&self) -> Option<&str> {
+        self.inner.description()
+    }
+
+    fn matches(&self, args: &QueryModsArgs) -> bool {
+        self.inner.matches(args)
+    }
+
+    fn filter_data(&self) -> FilterData<'_> {
+        self.inner.filter_data()
+    }
+
+    fn cmp(&self, other: &Self, args: &QueryModsArgs) -> Ordering {
+        let SortBy::Relevance = args.sort_by else {
+            return self.inner.cmp(&other.inner, args);
+        };
+
+        let order = self.relevance.total_cmp(&other.relevance);
+        let order = match args.sort_order {
+            SortOrder::Ascending => order,
+            SortOrder::Descending => order.reverse(),
+        };
+
+        order.then_with(|| self.inner.cmp(&other.inner, args))
+    }
+}
+
+/// Filters, deduplicates and sorts `mods`, keeping at most `args.max_count` of them.
+fn rank<T, I>(args: &QueryModsArgs, mods: I) -> impl Iterator<Item = T> + use<T, I>
+where
+    T: Queryable,
+    I: Iterator<Item = Ranked<T>>,
+{
+    let results = mods.filter(|ranked| ranked.matches(args));
     let mut results = Thunderstore::deduplicate(results).collect_vec();
 
     results.sort_by(|a, b| a.cmp(b, args));
     results
         .into_iter()
         .take(args.max_count.unwrap_or(usize::MAX))
+        .map(|ranked| ranked.inner)
 }
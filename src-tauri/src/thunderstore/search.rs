@@ -0,0 +1,316 @@
+//! Relevance ranked package search.
+//!
+//! Each word of the search term is matched against a package's name, owner and
+//! description, in that order of importance. Names and owners also match words with
+//! a typo or two. Among equally good matches, more popular packages rank higher.
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use super::BorrowedMod;
+use crate::util::fuzzy;
+
+const NAME_WEIGHT: f32 = 3.0;
+const OWNER_WEIGHT: f32 = 2.0;
+const DESCRIPTION_WEIGHT: f32 = 1.0;
+
+/// Splits `text` into lowercase words, also at camelCase boundaries.
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut prev_lowercase = false;
+
+    for char in text.chars() {
+        if !char.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            prev_lowercase = false;
+            continue;
+        }
+
+        if char.is_uppercase() && prev_lowercase && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+
+        prev_lowercase = char.is_lowercase();
+        current.extend(char.to_lowercase());
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Name,
+    Owner,
+}
+
+impl Field {
+    fn weight(self) -> f32 {
+        match self {
+            Field::Name => NAME_WEIGHT,
+            Field::Owner => OWNER_WEIGHT,
+        }
+    }
+}
+
+/// A parsed search term.
+#[derive(Debug, Default)]
+pub struct Search {
+    terms: Vec<String>,
+}
+
+impl Search {
+    pub fn new(query: &str) -> Self {
+        Self {
+            terms: tokenize(query),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Scores a package directly from its fields, without an index.
+    ///
+    /// Returns `None` if any of the words don't match the package.
+    pub fn score(&self, full_name: &str, description: Option<&str>) -> Option<f32> {
+        let (owner, name) = full_name.split_once('-').unwrap_or(("", full_name));
+        let fields = Fields {
+            name: name.to_lowercase(),
+            owner: owner.to_lowercase(),
+            description: description.unwrap_or_default().to_lowercase(),
+        };
+
+        let words = tokenize(name)
+            .into_iter()
+            .map(|word| (word, Field::Name))
+            .chain(tokenize(owner).into_iter().map(|word| (word, Field::Owner)))
+            .collect::<Vec<_>>();
+
+        self.terms
+            .iter()
+            .map(|term| {
+                let typo = words
+                    .iter()
+                    .filter_map(|(word, field)| typo_score(term, word).map(|s| s * field.weight()))
+                    .reduce(f32::max);
+
+                max_score(fields.score(term), typo)
+            })
+            .sum()
+    }
+}
+
+/// The lowercase text of a package that's searched through.
+struct Fields {
+    name: String,
+    owner: String,
+    description: String,
+}
+
+impl Fields {
+    fn score(&self, term: &str) -> Option<f32> {
+        let name = text_score(term, &self.name).map(|score| score * NAME_WEIGHT);
+        let owner = text_score(term, &self.owner).map(|score| score * OWNER_WEIGHT);
+        let description =
+            text_score(term, &self.description).map(|score| score * DESCRIPTION_WEIGHT);
+
+        max_score(max_score(name, owner), description)
+    }
+}
+
+/// Scores `term` appearing in `text` as a substring.
+///
+/// Scattered characters don't count as a match, since longer names and descriptions
+/// contain almost any term's characters in order somewhere. Misspelled words are
+/// matched by [`typo_score`] instead.
+fn text_score(term: &str, text: &str) -> Option<f32> {
+    text.contains(term)
+        .then(|| fuzzy::score(term, text))
+        .flatten()
+        .map(|score| score as f32)
+}
+
+/// Scores a misspelled match of `term` with a whole word.
+///
+/// Exact matches are already covered by [`text_score`].
+fn typo_score(term: &str, word: &str) -> Option<f32> {
+    fuzzy::typo_score(term, word).map(|score| score as f32)
+}
+
+fn max_score(a: Option<f32>, b: Option<f32>) -> Option<f32> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// How much to boost a package's score by, based on its downloads and rating.
+fn popularity(downloads: u32, rating: u32) -> f32 {
+    1.0 + (downloads as f32).ln_1p() / 16.0 + (rating as f32).ln_1p() / 32.0
+}
+
+struct Entry {
+    uuid: Uuid,
+    fields: Fields,
+    popularity: f32,
+}
+
+/// An in-memory search index of the packages of the active game.
+///
+/// Rebuilt whenever a fetch finishes. Packages that were added since then aren't
+/// included, and have to be scored with [`Search::score`] instead.
+#[derive(Default)]
+pub struct SearchIndex {
+    entries: Vec<Entry>,
+    uuids: HashMap<Uuid, usize>,
+    /// Every word in the names and owners of the packages, used to find typos
+    /// without checking each package separately.
+    words: HashMap<String, Vec<(usize, Field)>>,
+}
+
+impl SearchIndex {
+    pub fn build<'a>(mods: impl Iterator<Item = BorrowedMod<'a>>) -> Self {
+        let mut index = SearchIndex::default();
+
+        for borrowed in mods {
+            let package = borrowed.package;
+            let position = index.entries.len();
+
+            for (text, field) in [
+                (package.name(), Field::Name),
+                (package.owner(), Field::Owner),
+            ] {
+                for word in tokenize(text) {
+                    let postings = index.words.entry(word).or_default();
+
+                    if !postings.contains(&(position, field)) {
+                        postings.push((position, field));
+                    }
+                }
+            }
+
+            index.uuids.insert(package.uuid, position);
+            index.entries.push(Entry {
+                uuid: package.uuid,
+                fields: Fields {
+                    name: package.name().to_lowercase(),
+                    owner: package.owner().to_lowercase(),
+                    description: borrowed.version.description.to_lowercase(),
+                },
+                popularity: popularity(package.total_downloads(), package.rating_score),
+            });
+        }
+
+        index
+    }
+
+    pub fn contains(&self, uuid: Uuid) -> bool {
+        self.uuids.contains_key(&uuid)
+    }
+
+    /// Scores every indexed package that matches `search`, boosted by its popularity.
+    ///
+    /// If the search is empty, every package is included and ranked by popularity alone.
+    pub fn search(&self, search: &Search) -> HashMap<Uuid, f32> {
+        let mut totals = vec![Some(0.0f32); self.entries.len()];
+
+        for term in &search.terms {
+            let mut typos = HashMap::new();
+
+            for (word, postings) in &self.words {
+                let Some(score) = typo_score(term, word) else {
+                    continue;
+                };
+
+                for &(position, field) in postings {
+                    let score = score * field.weight();
+                    let best = typos.entry(position).or_insert(score);
+                    *best = best.max(score);
+                }
+            }
+
+            for (position, (entry, total)) in self.entries.iter().zip(&mut totals).enumerate() {
+                let Some(current) = total else {
+                    continue;
+                };
+
+                let typo = typos.get(&position).copied();
+
+                match max_score(entry.fields.score(term), typo) {
+                    Some(score) => *current += score,
+                    None => *total = None,
+                }
+            }
+        }
+
+        self.entries
+            .iter()
+            .zip(totals)
+            .filter_map(|(entry, total)| {
+                let score = if search.is_empty() { 1.0 } else { total? };
+
+                Some((entry.uuid, score * entry.popularity))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_words() {
+        assert_eq!(
+            tokenize("LethalCompany_InputUtils"),
+            ["lethal", "company", "input", "utils"]
+        );
+        assert_eq!(tokenize("  more suits "), ["more", "suits"]);
+        assert_eq!(tokenize("BepInExPack"), ["bep", "in", "ex", "pack"]);
+    }
+
+    #[test]
+    fn name_beats_description() {
+        let search = Search::new("suits");
+
+        let name = search
+            .score("x753-More_Suits", Some("Adds suit racks"))
+            .unwrap();
+        let description = search
+            .score("Someone-Cosmetics", Some("Lets you wear more suits"))
+            .unwrap();
+
+        assert!(name > description);
+    }
+
+    #[test]
+    fn typos() {
+        let search = Search::new("lethl compnay");
+
+        assert!(search.score("Owner-LethalCompany", None).is_some());
+        assert!(search.score("Owner-LethalThings", None).is_none());
+    }
+
+    #[test]
+    fn every_term_must_match() {
+        let search = Search::new("more suits");
+
+        assert!(search.score("x753-More_Suits", None).is_some());
+        assert!(search.score("x753-More_Company", None).is_none());
+    }
+
+    #[test]
+    fn scattered_letters_dont_match() {
+        let search = Search::new("suits");
+
+        assert!(search.score("Someone-SuperUnits", None).is_none());
+    }
+}
@@ -1,24 +1,16 @@
-use std::sync::LazyLock;
-
 use crate::thunderstore::Backend;
 use eyre::Result;
 use keyring::Entry;
 use tracing::info;
 
-static THUNDERSTORE_ENTRY: LazyLock<keyring::Result<Entry>> =
-    LazyLock::new(|| Entry::new("thunderstore", "api_token"));
-static HEXIUM_ENTRY: LazyLock<keyring::Result<Entry>> =
-    LazyLock::new(|| Entry::new("hexium", "api_token"));
-
-fn entry(backend: Backend) -> Result<&'static keyring::Entry> {
+fn entry(backend: Backend) -> Result<Entry> {
     let entry = match backend {
-        Backend::Thunderstore => &*THUNDERSTORE_ENTRY,
-        Backend::Hexium => &*HEXIUM_ENTRY,
-    };
-    match entry {
-        Ok(entry) => Ok(entry),
-        Err(err) => Err(err.into()),
-    }
+        Backend::Thunderstore => Entry::new("thunderstore", "api_token"),
+        Backend::Hexium => Entry::new("hexium", "api_token"),
+        Backend::Custom(id) => Entry::new("gale-repository", &id),
+    }?;
+
+    Ok(entry)
 }
 
 pub fn get(backend: Backend) -> Result<Option<String>> {
@@ -32,13 +24,17 @@ pub fn get(backend: Backend) -> Result<Option<String>> {
 pub fn set(backend: Backend, token: &str) -> Result<()> {
     info!("setting {backend} token");
     entry(backend)?.set_password(token)?;
+    super::backend::forget_token(backend);
     Ok(())
 }
 
 pub fn clear(backend: Backend) -> Result<()> {
     info!("clearing {backend} token");
-    match entry(backend)?.delete_credential() {
+    let result = match entry(backend)?.delete_credential() {
         Ok(_) | Err(keyring::Error::NoEntry) => Ok(()),
         Err(err) => Err(err.into()),
-    }
+    };
+
+    super::backend::forget_token(backend);
+    result
 }
@@ -0,0 +1,247 @@
+//! Tracks packages the user wants to follow, whether they're installed or not.
+//!
+//! After each fetch, the watched packages are compared against the state they were in
+//! last time. New versions and deprecations are recorded in the database and reported
+//! to the frontend with a `watchlist_event` until the user marks them as seen.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tracing::warn;
+
+use super::{
+    BorrowedMod, ModId, PackageListing,
+    cache::{self, MarkdownKind},
+    changelog,
+};
+use crate::{game::Game, state::ManagerExt};
+
+/// Changelog snippets are cut off after this many characters.
+const MAX_SNIPPET_LEN: usize = 500;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchedPackage {
+    pub full_name: String,
+    /// The latest version when the package was last checked.
+    ///
+    /// `None` if the package hasn't been found in any of the game's repositories yet.
+    pub last_version: Option<String>,
+    pub is_deprecated: bool,
+    pub added_at: DateTime<Utc>,
+}
+
+impl WatchedPackage {
+    fn new(full_name: String, package: Option<&PackageListing>) -> Self {
+        Self {
+            full_name,
+            last_version: package.map(|package| package.latest().version().to_owned()),
+            is_deprecated: package.is_some_and(|package| package.is_deprecated),
+            added_at: Utc::now(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChangeKind {
+    NewVersion,
+    Deprecated,
+}
+
+/// Something that happened to a watched package.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageChange {
+    pub full_name: String,
+    pub kind: ChangeKind,
+    /// The new version, for [`ChangeKind::NewVersion`].
+    pub version: Option<String>,
+    /// The version's section of the package's changelog, if it has one.
+    pub changelog: Option<String>,
+}
+
+/// A [`PackageChange`] as recorded in the database.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchlistChange {
+    pub id: i64,
+    #[serde(flatten)]
+    pub change: PackageChange,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// Adds a package to the watchlist of `game`.
+///
+/// The package's current state is stored right away, so only
+/// changes made after this point are reported.
+pub fn watch(full_name: String, game: Game, app: &AppHandle) -> Result<()> {
+    let package = {
+        let thunderstore = app.lock_thunderstore();
+        let package = thunderstore.find_package(&full_name).ok();
+        WatchedPackage::new(full_name, package)
+    };
+
+    app.db().watch_package(&game.slug, &package)
+}
+
+/// Compares the watched packages of `game` against the fetched packages,
+/// records any changes and notifies the frontend about them.
+pub(super) async fn check(game: Game, app: &AppHandle) -> Result<()> {
+    if app.lock_manager().active_game.slug != game.slug {
+        // the packages in memory belong to another game now
+        return Ok(());
+    }
+
+    let (mut changes, changelogs) = {
+        // keep the lock while updating the database, so checks
+        // from concurrent fetches don't record the same changes twice
+        let thunderstore = app.lock_thunderstore();
+
+        let mut watched = app.db().watchlist(&game.slug)?;
+        let mut changes = Vec::new();
+        let mut changelogs = HashMap::new();
+
+        for entry in &mut watched {
+            let Ok(package) = thunderstore.find_package(&entry.full_name) else {
+                continue;
+            };
+
+            let new_versions = package_changes(entry, package);
+            if new_versions
+                .iter()
+                .any(|change| change.kind == ChangeKind::NewVersion)
+            {
+                changelogs.insert(
+                    entry.full_name.clone(),
+                    ModId::from(BorrowedMod {
+                        package,
+                        version: package.latest(),
+                    }),
+                );
+            }
+
+            changes.extend(new_versions);
+        }
+
+        app.db().update_watched_packages(&game.slug, &watched)?;
+
+        (changes, changelogs)
+    };
+
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    for (full_name, mod_id) in changelogs {
+        let markdown = match cache::get_markdown(MarkdownKind::Changelog, mod_id, app).await {
+            Ok(markdown) => markdown,
+            Err(err) => {
+                warn!("failed to fetch changelog of {}: {:#}", full_name, err);
+                None
+            }
+        };
+
+        let Some(markdown) = markdown else {
+            continue;
+        };
+
+        for change in changes
+            .iter_mut()
+            .filter(|change| change.full_name == full_name)
+        {
+            change.changelog = change
+                .version
+                .as_deref()
+                .and_then(|version| changelog_snippet(&markdown, version));
+        }
+    }
+
+    let db = app.db();
+    db.insert_watchlist_changes(&game.slug, &changes)?;
+    app.emit_buffered("watchlist_event", &db.unseen_watchlist_changes(&game.slug)?);
+
+    Ok(())
+}
+
+/// Finds what changed about a package since `entry` was last updated, then updates it.
+fn package_changes(entry: &mut WatchedPackage, package: &PackageListing) -> Vec<PackageChange> {
+    let mut changes = Vec::new();
+
+    // packages that weren't available when they were added are
+    // treated as new, without reporting all of their versions
+    if let Some(last_version) = entry
+        .last_version
+        .as_deref()
+        .and_then(|version| version.parse::<semver::Version>().ok())
+    {
+        // versions are sorted newest first
+        changes.extend(
+            package
+                .versions
+                .iter()
+                .take_while(|version| version.parsed_version() > last_version)
+                .map(|version| PackageChange {
+                    full_name: entry.full_name.clone(),
+                    kind: ChangeKind::NewVersion,
+                    version: Some(version.version().to_owned()),
+                    changelog: None,
+                }),
+        );
+    }
+
+    if package.is_deprecated && !entry.is_deprecated {
+        changes.push(PackageChange {
+            full_name: entry.full_name.clone(),
+            kind: ChangeKind::Deprecated,
+            version: None,
+            changelog: None,
+        });
+    }
+
+    entry.last_version = Some(package.latest().version().to_owned());
+    entry.is_deprecated = package.is_deprecated;
+
+    changes
+}
+
+/// Extracts the section about `version` from a markdown changelog, cut off if it's too long.
+fn changelog_snippet(markdown: &str, version: &str) -> Option<String> {
+    let version = version.parse().ok()?;
+    let section = changelog::find(markdown, &version)?.body;
+
+    if section.is_empty() {
+        return None;
+    }
+
+    Some(match section.char_indices().nth(MAX_SNIPPET_LEN) {
+        Some((end, _)) => format!("{}…", section[..end].trim_end()),
+        None => section.to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snippet_is_truncated() {
+        let markdown = format!("## 1.0.0\n{}", "a".repeat(MAX_SNIPPET_LEN * 2));
+        let snippet = changelog_snippet(&markdown, "1.0.0").unwrap();
+
+        assert_eq!(snippet.chars().count(), MAX_SNIPPET_LEN + 1);
+        assert!(snippet.ends_with('…'));
+    }
+
+    #[test]
+    fn empty_snippet() {
+        assert_eq!(
+            changelog_snippet("## 1.0.0\n\n## 0.9.0\n- Stuff", "1.0.0"),
+            None
+        );
+        assert_eq!(changelog_snippet("Version 1.0.0\n- Stuff", "1.0.0"), None);
+    }
+}
@@ -1,13 +1,12 @@
 use std::{
     fs::{self, File},
-    io::{self, BufReader, BufWriter, Read},
+    io::{self, BufWriter, Read},
     path::{Path, PathBuf},
 };
 
 use serde::{Serialize, de::DeserializeOwned};
 use tracing::warn;
 use walkdir::WalkDir;
-use zip::ZipArchive;
 
 use super::error::IoResultExt;
 
@@ -105,13 +104,6 @@ pub fn read_json<T: DeserializeOwned>(path: impl AsRef<Path>) -> eyre::Result<T>
     Ok(result)
 }
 
-pub fn open_zip(path: impl AsRef<Path>) -> eyre::Result<ZipArchive<BufReader<File>>> {
-    let reader = File::open(path).map(BufReader::new)?;
-    let archive = ZipArchive::new(reader)?;
-
-    Ok(archive)
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum JsonStyle {
     Pretty,
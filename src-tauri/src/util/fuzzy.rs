@@ -0,0 +1,151 @@
+/// Scores how well `query` matches `text`, or `None` if it doesn't match at all.
+///
+/// Both strings are expected to be lowercase. Substring matches always score higher
+/// than fuzzy matches, where the characters of the query only appear in order.
+pub fn score(query: &str, text: &str) -> Option<u32> {
+    if query.is_empty() {
+        return None;
+    }
+
+    if text == query {
+        return Some(1000);
+    }
+
+    if let Some(index) = text.find(query) {
+        let word_start = index == 0 || !text[..index].ends_with(char::is_alphanumeric);
+        let bonus = if word_start { 100 } else { 0 };
+
+        return Some(500 + bonus - (index as u32).min(100));
+    }
+
+    subsequence_score(query, text)
+}
+
+fn subsequence_score(query: &str, text: &str) -> Option<u32> {
+    let mut query_chars = query.chars().peekable();
+    let mut score = 100u32;
+    let mut gap = 0u32;
+    let mut consecutive = 0u32;
+
+    for char in text.chars() {
+        let Some(&next) = query_chars.peek() else {
+            break;
+        };
+
+        if char == next {
+            query_chars.next();
+            consecutive += 1;
+            score += consecutive * 5;
+            score = score.saturating_sub(gap.min(10));
+            gap = 0;
+        } else {
+            consecutive = 0;
+            gap += 1;
+        }
+    }
+
+    match query_chars.peek() {
+        Some(_) => None,
+        None => Some(score.clamp(1, 499)),
+    }
+}
+
+/// Scores a misspelled match of `term` with a whole word, or `None` if it isn't one.
+///
+/// Longer terms may have more typos, while terms shorter than four characters
+/// never match. Exact matches are left to [`score`].
+pub fn typo_score(term: &str, word: &str) -> Option<u32> {
+    let max_typos = match term.chars().count() {
+        0..4 => return None,
+        4..8 => 1,
+        _ => 2,
+    };
+
+    match edit_distance(term, word, max_typos)? {
+        0 => None,
+        distance => Some(300 - 100 * distance as u32),
+    }
+}
+
+/// The number of single character edits (insertions, deletions, substitutions and
+/// swaps of adjacent characters) needed to turn `a` into `b`, or `None` if it's more than `max`.
+pub fn edit_distance(a: &str, b: &str, max: usize) -> Option<usize> {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+
+    // only the last two rows are needed to detect swaps
+    let mut prev_prev = vec![0; b.len() + 1];
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        current[0] = i;
+        let mut row_min = i;
+
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+
+            current[j] = (prev[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(prev[j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(prev_prev[j - 2] + 1);
+            }
+
+            row_min = row_min.min(current[j]);
+        }
+
+        // the distance can only grow from here
+        if row_min > max {
+            return None;
+        }
+
+        (prev_prev, prev, current) = (prev, current, prev_prev);
+    }
+
+    Some(prev[b.len()]).filter(|distance| *distance <= max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn substring_beats_fuzzy() {
+        let substring = score("speed", "walk speed").unwrap();
+        let fuzzy = score("speed", "spawn enemy death").unwrap();
+
+        assert!(substring > fuzzy);
+    }
+
+    #[test]
+    fn no_match() {
+        assert_eq!(score("xyz", "walk speed"), None);
+        assert_eq!(score("", "walk speed"), None);
+    }
+
+    #[test]
+    fn edit_distance_typos() {
+        assert_eq!(edit_distance("speed", "speed", 1), Some(0));
+        assert_eq!(edit_distance("sped", "speed", 1), Some(1));
+        assert_eq!(edit_distance("seped", "speed", 1), Some(1));
+        assert_eq!(edit_distance("spead", "speed", 2), Some(1));
+        assert_eq!(edit_distance("spd", "speed", 1), None);
+    }
+
+    #[test]
+    fn typos_depend_on_length() {
+        assert_eq!(typo_score("sped", "speed"), Some(200));
+        assert_eq!(typo_score("multipler", "multiplier"), Some(200));
+        assert_eq!(typo_score("multplir", "multiplier"), Some(100));
+
+        assert_eq!(typo_score("speed", "speed"), None);
+        assert_eq!(typo_score("spd", "sped"), None);
+        assert_eq!(typo_score("spede", "spawn"), None);
+    }
+}
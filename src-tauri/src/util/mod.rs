@@ -6,6 +6,7 @@ pub mod cmd;
 pub mod color;
 pub mod error;
 pub mod fs;
+pub mod fuzzy;
 pub mod path;
 pub mod window;
 pub mod zip;
@@ -1,5 +1,15 @@
 import { invoke } from '$lib/invoke';
-import type { BaseConfigFile, ConfigEntryId, ConfigFile, ConfigValue } from '$lib/types';
+import type {
+	BaseConfigFile,
+	ConfigChange,
+	ConfigEntryId,
+	ConfigFile,
+	ConfigSearchHit,
+	ConfigValidationReport,
+	ConfigValue,
+	OrphanAction,
+	OrphanedConfigFile
+} from '$lib/types';
 
 const idToArgs = (id: ConfigEntryId) => ({
 	file: id.file.relativePath,
@@ -8,6 +18,9 @@ const idToArgs = (id: ConfigEntryId) => ({
 });
 
 export const getFiles = () => invoke<ConfigFile[]>('get_config_files');
+export const search = (query: string, allProfiles: boolean, maxResults: number) =>
+	invoke<ConfigSearchHit[]>('search_config', { query, allProfiles, maxResults });
+export const validate = () => invoke<ConfigValidationReport>('validate_profile_config');
 export const setEntry = (id: ConfigEntryId, value: ConfigValue) =>
 	invoke('set_config_entry', {
 		...idToArgs(id),
@@ -25,3 +38,16 @@ export const openFile = (file: BaseConfigFile) =>
 	invoke('open_config_file', { file: file.relativePath });
 export const deleteFile = (file: BaseConfigFile) =>
 	invoke('delete_config_file', { file: file.relativePath });
+export const findOrphaned = () => invoke<OrphanedConfigFile[]>('find_orphaned_config');
+export const resolveOrphaned = (files: string[], action: OrphanAction) =>
+	invoke('resolve_orphaned_config', { files, action });
+export const getHistory = (file: BaseConfigFile, limit: number, id?: ConfigEntryId) =>
+	invoke<ConfigChange[]>('get_config_history', {
+		file: file.relativePath,
+		section: id?.section.name ?? null,
+		entry: id?.entry.name ?? null,
+		limit
+	});
+export const undoChange = (id: number) => invoke('undo_config_change', { id });
+export const revertFile = (file: BaseConfigFile, time: string) =>
+	invoke('revert_config_file', { file: file.relativePath, time });
@@ -1,14 +1,20 @@
 import { invoke } from '$lib/invoke';
-import type { LegacyImportData, R2ImportData, ImportOptions } from '$lib/types';
+import type { LegacyImportData, R2ImportData, ImportOptions, InstallPlan } from '$lib/types';
 
 export const profile = (data: LegacyImportData, options: ImportOptions) =>
 	invoke('import_profile', { data, options });
+export const planProfile = (data: LegacyImportData, options: ImportOptions) =>
+	invoke<InstallPlan>('plan_import_profile', { data, options });
 export const readCode = (key: string) => invoke<LegacyImportData>('read_profile_code', { key });
 export const readFile = (path: string) => invoke<LegacyImportData>('read_profile_file', { path });
 export const readBase64 = (base64: string) =>
 	invoke<LegacyImportData>('read_profile_base64', { base64 });
 export const localMod = (path: string) => invoke('import_local_mod', { path });
-export const localModBase64 = (base64: string) => invoke('import_local_mod_base64', { base64 });
+export const localModBase64 = (base64: string, fileName?: string) =>
+	invoke('import_local_mod_base64', { base64, fileName });
+export const remoteMod = (source: string) => invoke('import_remote_mod', { source });
+export const linkLocalMod = (path: string) => invoke('link_local_mod', { path });
+export const syncLinkedMod = (uuid: string) => invoke('sync_linked_mod', { uuid });
 export const getR2modmanInfo = (path: string | null) =>
 	invoke<R2ImportData | null>('get_r2modman_info', { path });
 export const r2modman = (path: string, include: boolean[]) =>
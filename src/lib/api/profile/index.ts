@@ -8,7 +8,8 @@ import type {
 	QueryModsArgs,
 	ModId,
 	MarkdownType,
-	Dependant
+	Dependant,
+	HealthReport
 } from '$lib/types';
 
 export * as export from './export';
@@ -51,3 +52,4 @@ export const setProfilePath = (profileId: number, newPath: string) =>
 export const forgetProfile = (profileId: number) => invoke('forget_profile', { profileId });
 export const toggleHiddenMod = (uuid: string) => invoke('toggle_hidden_mod', { uuid });
 export const getHiddenMods = () => invoke<Dependant[]>('get_hidden_mods');
+export const checkHealth = () => invoke<HealthReport>('check_profile_health');
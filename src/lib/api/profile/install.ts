@@ -1,13 +1,34 @@
 import { invoke } from '$lib/invoke';
-import type { ModId } from '$lib/types';
+import type {
+	CacheStoreStats,
+	CacheUsage,
+	CacheVerifyReport,
+	InstallPlan,
+	InstallRule,
+	InstallRules,
+	IntegrityReport,
+	ModId,
+	RepairSummary
+} from '$lib/types';
 
 export const allMods = () => invoke('install_all_mods');
 export const mod = (id: ModId) => invoke('install_mod', { id });
+export const plan = (mods: ModId[]) => invoke<InstallPlan>('plan_install', { mods });
 export const cancelAll = () => invoke('cancel_all_installs');
 export const clearDownloadCache = (soft: boolean) =>
 	invoke<number>('clear_download_cache', { soft });
+export const getCacheUsage = () => invoke<CacheUsage>('get_cache_usage');
+export const getCacheStoreStats = () => invoke<CacheStoreStats>('get_cache_store_stats');
 export const getDownloadSize = (modId: ModId) =>
 	invoke<number>('get_download_size', { modRef: modId });
 export const hasPendingInstallations = () => invoke<boolean>('has_pending_installations');
 export const isInstalling = (packageUuid: string) =>
 	invoke<boolean>('is_installing', { packageUuid });
+export const verifyProfile = () => invoke<IntegrityReport>('verify_profile');
+export const repairProfile = () => invoke<RepairSummary>('repair_profile');
+export const verifyCache = () => invoke<CacheVerifyReport>('verify_cache');
+export const getRules = () => invoke<InstallRules>('get_install_rules');
+export const setGameRules = (rules: InstallRule[]) => invoke('set_game_install_rules', { rules });
+export const setProfileRules = (rules: InstallRule[]) =>
+	invoke('set_profile_install_rules', { rules });
+export const reinstallAffected = () => invoke<number>('reinstall_affected_mods');
@@ -7,3 +7,6 @@ export const mods = (packageUuids: string[], respectIgnored: boolean) =>
 export const ignore = (versionUuid: string) => invoke('ignore_update', { versionUuid });
 export const ignorePackage = (packageUuid: string) =>
 	invoke('ignore_package_updates', { packageUuid });
+export const changelog = (installed: string, target: string) =>
+	invoke<string | null>('get_update_changelog', { installed, target });
+export const review = () => invoke<string>('review_updates');
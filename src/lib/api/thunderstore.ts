@@ -1,21 +1,42 @@
 import { invoke } from '$lib/invoke';
 import {
 	type Backend,
+	type CustomBackend,
+	type CustomRepository,
+	type FetchStats,
 	type MarkdownType,
 	type Mod,
 	type ModId,
 	type PackageCategory,
-	type QueryModsArgs
+	type PackageDependants,
+	type QueryModsArgs,
+	type WatchedPackage,
+	type WatchlistChange
 } from '$lib/types';
 
 export const query = (args: QueryModsArgs) => invoke<Mod[]>('query_thunderstore', { args });
+export const checkQuery = (query: string) => invoke<string | null>('check_mod_query', { query });
 export const stopQuerying = () => invoke('stop_querying_thunderstore');
 export const triggerModFetch = () => invoke('trigger_mod_fetch');
+export const getFetchStats = () => invoke<Record<Backend | CustomBackend, FetchStats>>('get_fetch_stats');
 export const getMarkdown = (id: ModId, type: MarkdownType) =>
 	invoke<string | null>('get_markdown', { modRef: id, kind: type });
-export const setToken = (backend: Backend, token: string) =>
+export const getDependants = (id: ModId, anyVersion: boolean, maxCount: number | null = null) =>
+	invoke<PackageDependants>('get_package_dependants', { modRef: id, anyVersion, maxCount });
+export const setToken = (backend: Backend | CustomBackend, token: string) =>
 	invoke('set_api_token', { backend, token });
-export const hasToken = (backend: Backend) => invoke<boolean>('has_api_token', { backend });
-export const clearToken = (backend: Backend) => invoke('clear_api_token', { backend });
+export const hasToken = (backend: Backend | CustomBackend) =>
+	invoke<boolean>('has_api_token', { backend });
+export const clearToken = (backend: Backend | CustomBackend) =>
+	invoke('clear_api_token', { backend });
 export const getCategories = (gameSlug: string) =>
 	invoke<PackageCategory[]>('get_categories', { game: gameSlug });
+export const getRepositories = () => invoke<CustomRepository[]>('get_repositories');
+export const setRepository = (repository: CustomRepository) =>
+	invoke('set_repository', { repository });
+export const removeRepository = (id: string) => invoke('remove_repository', { id });
+export const getWatchlist = () => invoke<WatchedPackage[]>('get_watchlist');
+export const watchPackage = (fullName: string) => invoke('watch_package', { fullName });
+export const unwatchPackage = (fullName: string) => invoke('unwatch_package', { fullName });
+export const getWatchlistChanges = () => invoke<WatchlistChange[]>('get_watchlist_changes');
+export const markWatchlistSeen = () => invoke('mark_watchlist_seen');
@@ -1,6 +1,7 @@
 import { goto } from '$app/navigation';
 import * as api from '$lib/api';
-import type { BaseConfigFile, ConfigFile, ConfigSection } from '$lib/types';
+import type { BaseConfigFile, ConfigChangedEvent, ConfigFile, ConfigSection } from '$lib/types';
+import { listen } from '@tauri-apps/api/event';
 import { untrack } from 'svelte';
 import profiles from './profile.svelte';
 
@@ -17,6 +18,45 @@ class ConfigState {
 				untrack(() => this.refresh());
 			});
 		});
+
+		// files changed outside of the app, for example by the game
+		listen<ConfigChangedEvent>('config_changed', ({ payload }) => {
+			if (payload.profileId !== profiles.activeId) return;
+			this.applyChanges(payload);
+		});
+	}
+
+	private applyChanges({ changed, removed }: ConfigChangedEvent) {
+		const files = this.files.filter((file) => !removed.includes(file.relativePath));
+
+		for (const file of changed) {
+			const index = files.findIndex((f) => f.relativePath === file.relativePath);
+
+			if (index === -1) {
+				files.push(file);
+			} else {
+				files[index] = file;
+			}
+		}
+
+		this.files = files;
+
+		const selectedPath = this.selectedFile?.relativePath;
+		if (selectedPath && removed.includes(selectedPath)) {
+			this.selectedFile = null;
+			this.selectedSection = null;
+		}
+
+		if (!selectedPath || !changed.some((file) => file.relativePath === selectedPath)) return;
+
+		const sectionName = this.selectedSection?.name;
+		this.selectedFile = this.findFileByPath(selectedPath);
+		this.selectedSection = null;
+
+		if (this.selectedFile?.type === 'ok' && sectionName) {
+			this.selectedSection =
+				this.selectedFile.sections.find((section) => section.name === sectionName) ?? null;
+		}
 	}
 
 	async refresh() {
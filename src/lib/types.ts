@@ -53,6 +53,60 @@ export type ConfigFile = BaseConfigFile &
 		| ConfigFileType<'unsupported'>
 	);
 
+export type ConfigValidationError =
+	| { type: 'outOfRange'; value: string; min: string; max: string }
+	| { type: 'unknownOption'; option: string }
+	| { type: 'typeMismatch'; expected: string };
+
+export type InvalidConfigEntry = {
+	file: string;
+	section: string;
+	entry: string;
+	message: string;
+	error: ConfigValidationError;
+};
+
+export type ConfigValidationReport = {
+	invalidEntries: InvalidConfigEntry[];
+	unreadableFiles: { file: string; error: string }[];
+};
+
+export type ConfigChangedEvent = {
+	profileId: number;
+	changed: ConfigFile[];
+	removed: string[];
+};
+
+export type OrphanedConfigFile = {
+	relativePath: string;
+	displayName: string;
+	pluginName: string | null;
+};
+
+export type OrphanAction = 'archive' | 'delete' | 'exclude';
+
+export type ConfigChange = {
+	id: number;
+	file: string;
+	section: string;
+	entry: string;
+	oldValue: ConfigValue | null;
+	newValue: ConfigValue;
+	changedAt: string;
+};
+
+export type ConfigSearchHit = {
+	profileId: number;
+	profileName: string;
+	file: string;
+	fileName: string;
+	section: string;
+	entry: string;
+	value: string;
+	owner: { uuid: string; name: string } | null;
+	score: number;
+};
+
 export type ProfileInfo = {
 	id: number;
 	name: string;
@@ -102,6 +156,39 @@ export enum Backend {
 	Hexium = 'Hexium'
 }
 
+export type CustomBackend = `custom:${string}`;
+
+export type CustomRepository = {
+	id: string;
+	name: string;
+	games: string[];
+} & RepositorySource;
+
+export type RepositorySource =
+	| {
+			type: 'remote';
+			baseUrl: string;
+			indexUrlTemplate: string;
+			downloadUrlTemplate: string;
+	  }
+	| { type: 'directory'; path: string };
+
+export type WatchedPackage = {
+	fullName: string;
+	lastVersion: string | null;
+	isDeprecated: boolean;
+	addedAt: string;
+};
+
+export type WatchlistChange = {
+	id: number;
+	fullName: string;
+	kind: 'newVersion' | 'deprecated';
+	version: string | null;
+	changelog: string | null;
+	detectedAt: string;
+};
+
 export type Mod = {
 	name: string;
 	description: string | null;
@@ -127,7 +214,7 @@ export type Mod = {
 	enabled?: boolean | null;
 	icon: string | null;
 	configFile: string | null;
-	backend: Backend;
+	backend: Backend | CustomBackend;
 };
 
 export type ModVersion = {
@@ -149,7 +236,8 @@ export type SortBy =
 	| 'rating'
 	| 'installDate'
 	| 'custom'
-	| 'diskSpace';
+	| 'diskSpace'
+	| 'relevance';
 
 export type SortOrder = 'ascending' | 'descending';
 
@@ -177,19 +265,33 @@ export type ConfigEntryId = {
 export type Dependant = {
 	fullName: string;
 	uuid: string;
-	backend: Backend;
+	backend: Backend | CustomBackend;
 };
 
 export type DependantWithVersion = {
 	fullName: string;
 	preferredVersion: string | null;
-	backend: Backend;
+	backend: Backend | CustomBackend;
 };
 
 export type ModId = {
 	packageUuid: string;
 	versionUuid: string;
-	backend: Backend;
+	backend: Backend | CustomBackend;
+};
+
+export type PackageDependant = {
+	fullName: string;
+	id: ModId;
+	dependencyVersion: string;
+	downloads: number;
+	rating: number;
+	isInstalled: boolean;
+};
+
+export type PackageDependants = {
+	total: number;
+	dependants: PackageDependant[];
 };
 
 export type ModActionResponse =
@@ -206,9 +308,17 @@ export type InstallEvent =
 	| { type: 'setTask'; name: string; task: InstallTask };
 
 export type FetchEvent =
-	| { type: 'start'; backend: Backend }
-	| { type: 'progress'; backend: Backend; mods: number }
-	| { type: 'done'; backend: Backend };
+	| { type: 'start'; backend: Backend | CustomBackend }
+	| { type: 'progress'; backend: Backend | CustomBackend; mods: number }
+	| { type: 'stats'; backend: Backend | CustomBackend; stats: FetchStats }
+	| { type: 'done'; backend: Backend | CustomBackend };
+
+export type FetchStats = {
+	changedChunks: number;
+	unchangedChunks: number;
+	downloadedBytes: number;
+	savedBytes: number;
+};
 
 export type ModpackArgs = {
 	name: string;
@@ -233,7 +343,7 @@ export type ModpackInfo = {
 
 export type ExportCode = {
 	code: string;
-	backend: Backend;
+	backend: Backend | CustomBackend;
 };
 
 export type Game = {
@@ -320,7 +430,7 @@ type ProfileManifestMod = {
 		minor: number;
 		patch: number;
 	};
-	source: Backend;
+	source: Backend | CustomBackend;
 };
 
 export type R2ImportData = {
@@ -343,8 +453,13 @@ export type Prefs = {
 	language: string;
 	gamePrefs: Map<string, GamePrefs>;
 	backendSkipConfirm: boolean;
+	cacheSizeLimit: number | null;
+	installMethod: FileInstallMethod;
+	repositories: CustomRepository[];
 };
 
+export type FileInstallMethod = 'Link' | 'Reflink' | 'Copy' | 'Symlink';
+
 export enum Backends {
 	All = 'All',
 	Thunderstore = 'Thunderstore',
@@ -360,6 +475,123 @@ export type GamePrefs = {
 	backend: Backends;
 };
 
+export type CacheUsage = {
+	totalSize: number;
+	unreferencedSize: number;
+	sizeLimit: number | null;
+	games: { slug: string | null; size: number; versionCount: number }[];
+	packages: PackageCacheUsage[];
+};
+
+export type PackageCacheUsage = {
+	fullName: string;
+	size: number;
+	versions: {
+		version: string;
+		size: number;
+		lastUsed: string;
+		referencedBy: string[];
+	}[];
+};
+
+export type PlannedMod = {
+	uuid: string;
+	fullName: string;
+	version: string;
+};
+
+export type PlannedInstall = PlannedMod & {
+	reason: 'requested' | 'dependency';
+	previousVersion: string | null;
+	cached: boolean;
+	downloadSize: number;
+};
+
+export type InstallPlan = {
+	installs: PlannedInstall[];
+	removals: PlannedMod[];
+	conflicts: { path: string; mods: string[] }[];
+	unchecked: string[];
+	downloadSize: number;
+};
+
+export type CacheVerifyReport = {
+	checked: number;
+	removed: string[];
+	freed: number;
+};
+
+export type ModIntegrity = {
+	uuid: string;
+	fullName: string;
+	version: string;
+	missing: string[];
+	modified: string[];
+	extra: string[];
+	needsDownload: boolean;
+};
+
+export type IntegrityReport = {
+	checked: number;
+	mods: ModIntegrity[];
+	unverified: string[];
+};
+
+export type HealthIssueKind = 'deprecated' | 'removed' | 'versionDelisted';
+
+export type ReplacementReason =
+	| 'linkedInReadme'
+	| 'mentionedInDescription'
+	| 'sameName'
+	| 'latestVersion';
+
+export type Replacement = {
+	fullName: string;
+	id: ModId;
+	reason: ReplacementReason;
+};
+
+export type HealthIssue = {
+	uuid: string;
+	fullName: string;
+	version: string;
+	kind: HealthIssueKind;
+	replacements: Replacement[];
+};
+
+export type HealthReport = {
+	checked: number;
+	issues: HealthIssue[];
+	unchecked: string[];
+};
+
+export type RepairSummary = {
+	repairedFiles: number;
+	redownloadedMods: number;
+};
+
+export type SubdirMode = 'separate' | 'separateFlatten' | 'track' | 'none';
+
+export type InstallRule = {
+	name: string;
+	target: string;
+	mode: SubdirMode;
+	mutable: boolean;
+	extension: string | null;
+};
+
+export type InstallRules = {
+	game: InstallRule[];
+	profile: InstallRule[];
+};
+
+export type CacheStoreStats = {
+	objectCount: number;
+	storedSize: number;
+	linkedSize: number;
+	savedSize: number;
+};
+
 export type Platform = 'steam' | 'epicGames' | 'oculus' | 'origin' | 'xboxStore';
 
 export type ContextItem = {
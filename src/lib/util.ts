@@ -1,6 +1,7 @@
 import {
 	Backend,
 	type ConfigEntry,
+	type CustomBackend,
 	type Game,
 	type MarkdownType,
 	type Mod,
@@ -133,7 +134,7 @@ export function hasNonReleaseUpgrade(mod: Mod): boolean {
 	return isNonReleaseVersion(mod.versions[0].name);
 }
 
-export function communityUrl(backend: Backend, author: string, mod?: string) {
+export function communityUrl(backend: Backend | CustomBackend, author: string, mod?: string) {
 	if (backend === Backend.Hexium) {
 		return `https://${games.active?.slug}.hexium.gg/${mod === undefined ? `teams/${author}` : `mods/${author}/${mod}`}`;
 	} else {